@@ -88,6 +88,16 @@ impl Ast {
     pub fn const0(&self) -> &Const {
         self.files.last().unwrap().elements[0].to_const().unwrap()
     }
+
+    #[cfg(test)]
+    pub fn import0(&self) -> &Import {
+        self.files.last().unwrap().elements[0].to_import().unwrap()
+    }
+
+    #[cfg(test)]
+    pub fn use0(&self) -> &Use {
+        self.files.last().unwrap().elements[0].to_use().unwrap()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -113,6 +123,8 @@ pub enum Elem {
     ElemTrait(Trait),
     ElemImpl(Impl),
     ElemModule(Module),
+    ElemImport(Import),
+    ElemUse(Use),
     ElemGlobal(Global),
     ElemConst(Const),
     ElemEnum(Enum),
@@ -127,6 +139,8 @@ impl Elem {
             &ElemTrait(ref t) => t.id,
             &ElemImpl(ref i) => i.id,
             &ElemModule(ref m) => m.id,
+            &ElemImport(ref i) => i.id,
+            &ElemUse(ref u) => u.id,
             &ElemGlobal(ref g) => g.id,
             &ElemConst(ref c) => c.id,
             &ElemEnum(ref e) => e.id,
@@ -188,6 +202,20 @@ impl Elem {
             _ => None,
         }
     }
+
+    pub fn to_import(&self) -> Option<&Import> {
+        match *self {
+            ElemImport(ref import) => Some(import),
+            _ => None,
+        }
+    }
+
+    pub fn to_use(&self) -> Option<&Use> {
+        match *self {
+            ElemUse(ref xuse) => Some(xuse),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -197,16 +225,36 @@ pub struct Global {
     pub span: Span,
     pub name: Name,
     pub reassignable: bool,
+    pub is_pub: bool,
     pub data_type: Type,
     pub expr: Option<Box<Expr>>,
 }
 
+// `import a::b;` makes module `b` available to the file
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+    pub path: Vec<Name>,
+}
+
+// `use a::b;` makes item `b` of module `a` available to the file
+#[derive(Clone, Debug)]
+pub struct Use {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+    pub path: Vec<Name>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Const {
     pub id: NodeId,
     pub pos: Position,
     pub span: Span,
     pub name: Name,
+    pub is_pub: bool,
     pub data_type: Type,
     pub expr: Box<Expr>,
 }
@@ -217,7 +265,17 @@ pub struct Enum {
     pub pos: Position,
     pub span: Span,
     pub name: Name,
-    pub values: Vec<Box<Expr>>,
+    pub is_pub: bool,
+    pub values: Vec<EnumValue>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EnumValue {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+    pub name: Name,
+    pub types: Option<Vec<Type>>,
 }
 
 #[derive(Clone, Debug)]
@@ -226,6 +284,7 @@ pub struct Struct {
     pub pos: Position,
     pub span: Span,
     pub name: Name,
+    pub is_pub: bool,
     pub fields: Vec<StructField>,
 }
 
@@ -280,6 +339,9 @@ pub struct TypeBasicType {
 
     pub name: Name,
     pub params: Vec<Box<Type>>,
+
+    // modules in front of the name, e.g. `a::b` for `a::b::Foo`
+    pub path: Vec<Name>,
 }
 
 impl Type {
@@ -291,6 +353,7 @@ impl Type {
         id: NodeId,
         pos: Position,
         span: Span,
+        path: Vec<Name>,
         name: Name,
         params: Vec<Box<Type>>,
     ) -> Type {
@@ -300,6 +363,7 @@ impl Type {
             span,
             name,
             params,
+            path,
         })
     }
 
@@ -338,7 +402,7 @@ impl Type {
     pub fn to_basic_without_type_params(&self) -> Option<Name> {
         match *self {
             Type::TypeBasic(ref basic) => {
-                if basic.params.len() == 0 {
+                if basic.params.len() == 0 && basic.path.is_empty() {
                     Some(basic.name)
                 } else {
                     None
@@ -374,7 +438,16 @@ impl Type {
     pub fn to_string(&self, interner: &Interner) -> String {
         match *self {
             Type::TypeSelf(_) => "Self".into(),
-            Type::TypeBasic(ref val) => format!("{}", *interner.str(val.name)),
+            Type::TypeBasic(ref val) => {
+                let mut path: Vec<String> = val
+                    .path
+                    .iter()
+                    .map(|&n| interner.str(n).to_string())
+                    .collect();
+                path.push(interner.str(val.name).to_string());
+
+                path.join("::")
+            }
 
             Type::TypeTuple(ref val) => {
                 let types: Vec<String> =
@@ -429,6 +502,7 @@ pub struct Trait {
     pub name: Name,
     pub pos: Position,
     pub span: Span,
+    pub is_pub: bool,
     pub methods: Vec<Function>,
 }
 
@@ -441,6 +515,7 @@ pub struct Class {
     pub parent_class: Option<ParentClass>,
     pub has_open: bool,
     pub is_abstract: bool,
+    pub is_pub: bool,
     pub internal: bool,
     pub has_constructor: bool,
 
@@ -1242,6 +1317,7 @@ pub enum Expr {
     ExprLitStr(ExprLitStrType),
     ExprTemplate(ExprTemplateType),
     ExprLitBool(ExprLitBoolType),
+    ExprLitStruct(ExprLitStructType),
     ExprIdent(ExprIdentType),
     ExprCall(ExprCallType),
     ExprTypeParam(ExprTypeParamType),
@@ -1257,6 +1333,7 @@ pub enum Expr {
     ExprBlock(ExprBlockType),
     ExprIf(ExprIfType),
     ExprTuple(ExprTupleType),
+    ExprMatch(ExprMatchType),
 }
 
 impl Expr {
@@ -1544,22 +1621,23 @@ impl Expr {
         })
     }
 
-    pub fn create_lambda(
+    pub fn create_lambda(id: NodeId, pos: Position, span: Span, fct: Box<Function>) -> Expr {
+        Expr::ExprLambda(ExprLambdaType { id, pos, span, fct })
+    }
+
+    pub fn create_lit_struct(
         id: NodeId,
         pos: Position,
         span: Span,
-        params: Vec<Param>,
-        ret: Option<Box<Type>>,
-        block: Box<Stmt>,
+        path: Name,
+        args: Vec<StructArg>,
     ) -> Expr {
-        Expr::ExprLambda(ExprLambdaType {
+        Expr::ExprLitStruct(ExprLitStructType {
             id,
             pos,
             span,
-
-            params,
-            ret,
-            block,
+            path,
+            args,
         })
     }
 
@@ -1572,6 +1650,22 @@ impl Expr {
         })
     }
 
+    pub fn create_match(
+        id: NodeId,
+        pos: Position,
+        span: Span,
+        expr: Box<Expr>,
+        cases: Vec<MatchCaseType>,
+    ) -> Expr {
+        Expr::ExprMatch(ExprMatchType {
+            id,
+            pos,
+            span,
+            expr,
+            cases,
+        })
+    }
+
     pub fn to_un(&self) -> Option<&ExprUnType> {
         match *self {
             Expr::ExprUn(ref val) => Some(val),
@@ -1845,6 +1939,20 @@ impl Expr {
         }
     }
 
+    pub fn to_lit_struct(&self) -> Option<&ExprLitStructType> {
+        match *self {
+            Expr::ExprLitStruct(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_lit_struct(&self) -> bool {
+        match *self {
+            Expr::ExprLitStruct(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_tuple(&self) -> Option<&ExprTupleType> {
         match *self {
             Expr::ExprTuple(ref val) => Some(val),
@@ -1887,10 +1995,25 @@ impl Expr {
         }
     }
 
+    pub fn to_match(&self) -> Option<&ExprMatchType> {
+        match *self {
+            Expr::ExprMatch(ref val) => Some(val),
+            _ => None,
+        }
+    }
+
+    pub fn is_match(&self) -> bool {
+        match *self {
+            Expr::ExprMatch(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn needs_semicolon(&self) -> bool {
         match self {
             &Expr::ExprBlock(_) => false,
             &Expr::ExprIf(_) => false,
+            &Expr::ExprMatch(_) => false,
             _ => true,
         }
     }
@@ -1905,6 +2028,7 @@ impl Expr {
             Expr::ExprLitStr(ref val) => val.pos,
             Expr::ExprTemplate(ref val) => val.pos,
             Expr::ExprLitBool(ref val) => val.pos,
+            Expr::ExprLitStruct(ref val) => val.pos,
             Expr::ExprIdent(ref val) => val.pos,
             Expr::ExprCall(ref val) => val.pos,
             Expr::ExprTypeParam(ref val) => val.pos,
@@ -1920,6 +2044,7 @@ impl Expr {
             Expr::ExprBlock(ref val) => val.pos,
             Expr::ExprIf(ref val) => val.pos,
             Expr::ExprTuple(ref val) => val.pos,
+            Expr::ExprMatch(ref val) => val.pos,
         }
     }
 
@@ -1933,6 +2058,7 @@ impl Expr {
             Expr::ExprLitStr(ref val) => val.span,
             Expr::ExprTemplate(ref val) => val.span,
             Expr::ExprLitBool(ref val) => val.span,
+            Expr::ExprLitStruct(ref val) => val.span,
             Expr::ExprIdent(ref val) => val.span,
             Expr::ExprCall(ref val) => val.span,
             Expr::ExprTypeParam(ref val) => val.span,
@@ -1948,6 +2074,7 @@ impl Expr {
             Expr::ExprBlock(ref val) => val.span,
             Expr::ExprIf(ref val) => val.span,
             Expr::ExprTuple(ref val) => val.span,
+            Expr::ExprMatch(ref val) => val.span,
         }
     }
 
@@ -1961,6 +2088,7 @@ impl Expr {
             Expr::ExprLitStr(ref val) => val.id,
             Expr::ExprTemplate(ref val) => val.id,
             Expr::ExprLitBool(ref val) => val.id,
+            Expr::ExprLitStruct(ref val) => val.id,
             Expr::ExprIdent(ref val) => val.id,
             Expr::ExprCall(ref val) => val.id,
             Expr::ExprTypeParam(ref val) => val.id,
@@ -1976,6 +2104,7 @@ impl Expr {
             Expr::ExprBlock(ref val) => val.id,
             Expr::ExprIf(ref val) => val.id,
             Expr::ExprTuple(ref val) => val.id,
+            Expr::ExprMatch(ref val) => val.id,
         }
     }
 }
@@ -1991,6 +2120,77 @@ pub struct ExprIfType {
     pub else_block: Option<Box<Expr>>,
 }
 
+#[derive(Clone, Debug)]
+pub struct ExprMatchType {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+
+    pub expr: Box<Expr>,
+    pub cases: Vec<MatchCaseType>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchCaseType {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+
+    pub patterns: Vec<MatchPattern>,
+    pub value: Box<Expr>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchPattern {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+
+    pub data: MatchPatternData,
+}
+
+#[derive(Clone, Debug)]
+pub enum MatchPatternData {
+    Underscore,
+    Ident(MatchPatternIdent),
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchPatternIdent {
+    pub path: Vec<Name>,
+    pub params: Option<Vec<MatchPatternParam>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchPatternParam {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+
+    // None for `_`
+    pub name: Option<Name>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExprLitStructType {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+
+    pub path: Name,
+    pub args: Vec<StructArg>,
+}
+
+#[derive(Clone, Debug)]
+pub struct StructArg {
+    pub id: NodeId,
+    pub pos: Position,
+    pub span: Span,
+
+    pub name: Name,
+    pub expr: Box<Expr>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ExprTupleType {
     pub id: NodeId,
@@ -2193,9 +2393,7 @@ pub struct ExprLambdaType {
     pub pos: Position,
     pub span: Span,
 
-    pub params: Vec<Param>,
-    pub ret: Option<Box<Type>>,
-    pub block: Box<Stmt>,
+    pub fct: Box<Function>,
 }
 
 #[derive(Clone, Debug)]
@@ -78,10 +78,19 @@ impl<'a> AstDumper<'a> {
                 ElemGlobal(ref global) => self.dump_global(global),
                 ElemConst(ref xconst) => self.dump_const(xconst),
                 ElemEnum(ref xenum) => self.dump_enum(xenum),
+                ElemImport(ref import) => {
+                    self.dump_path("import", import.pos, import.id, &import.path)
+                }
+                ElemUse(ref xuse) => self.dump_path("use", xuse.pos, xuse.id, &xuse.path),
             }
         }
     }
 
+    fn dump_path(&mut self, kind: &str, pos: Position, id: NodeId, path: &[Name]) {
+        let path: Vec<String> = path.iter().map(|&n| self.str(n).to_string()).collect();
+        dump!(self, "{} {} @ {} {}", kind, path.join("::"), pos, id);
+    }
+
     fn dump_global(&mut self, global: &Global) {
         dump!(
             self,
@@ -128,7 +137,21 @@ impl<'a> AstDumper<'a> {
 
         self.indent(|d| {
             for value in &xenum.values {
-                d.dump_expr(value)
+                dump!(
+                    d,
+                    "value {} @ {} {}",
+                    d.str(value.name),
+                    value.pos,
+                    value.id
+                );
+
+                if let Some(ref types) = value.types {
+                    d.indent(|d| {
+                        for ty in types {
+                            d.dump_type(ty);
+                        }
+                    });
+                }
             }
         });
     }
@@ -504,6 +527,8 @@ impl<'a> AstDumper<'a> {
             ExprBlock(ref expr) => self.dump_expr_block(expr),
             ExprIf(ref expr) => self.dump_expr_if(expr),
             ExprTuple(ref expr) => self.dump_expr_tuple(expr),
+            ExprLitStruct(ref expr) => self.dump_expr_lit_struct(expr),
+            ExprMatch(ref expr) => self.dump_expr_match(expr),
         }
     }
 
@@ -645,7 +670,7 @@ impl<'a> AstDumper<'a> {
 
     fn dump_expr_lambda(&mut self, expr: &ExprLambdaType) {
         dump!(self, "lambda @ {} {}", expr.pos, expr.id);
-        self.indent(|d| d.dump_stmt(&expr.block));
+        self.indent(|d| d.dump_expr_block(expr.fct.block()));
     }
 
     fn dump_expr_tuple(&mut self, expr: &ExprTupleType) {
@@ -657,6 +682,66 @@ impl<'a> AstDumper<'a> {
         });
     }
 
+    fn dump_expr_lit_struct(&mut self, expr: &ExprLitStructType) {
+        dump!(
+            self,
+            "lit struct {} @ {} {}",
+            self.str(expr.path),
+            expr.pos,
+            expr.id
+        );
+
+        self.indent(|d| {
+            for arg in &expr.args {
+                dump!(d, "arg {} @ {} {}", d.str(arg.name), arg.pos, arg.id);
+                d.indent(|d| d.dump_expr(&arg.expr));
+            }
+        });
+    }
+
+    fn dump_expr_match(&mut self, expr: &ExprMatchType) {
+        dump!(self, "match @ {} {}", expr.pos, expr.id);
+
+        self.indent(|d| {
+            d.dump_expr(&expr.expr);
+
+            for case in &expr.cases {
+                dump!(d, "case @ {} {}", case.pos, case.id);
+
+                d.indent(|d| {
+                    for pattern in &case.patterns {
+                        match pattern.data {
+                            MatchPatternData::Underscore => {
+                                dump!(d, "pattern _ @ {} {}", pattern.pos, pattern.id);
+                            }
+
+                            MatchPatternData::Ident(ref ident) => {
+                                let path = ident
+                                    .path
+                                    .iter()
+                                    .map(|&name| d.str(name).to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("::");
+                                dump!(d, "pattern {} @ {} {}", path, pattern.pos, pattern.id);
+
+                                for param in ident.params.iter().flatten() {
+                                    let name = param
+                                        .name
+                                        .map_or("_".to_string(), |name| d.str(name).to_string());
+                                    d.indent(|d| {
+                                        dump!(d, "param {} @ {} {}", name, param.pos, param.id)
+                                    });
+                                }
+                            }
+                        }
+                    }
+
+                    d.dump_expr(&case.value);
+                });
+            }
+        });
+    }
+
     fn dump_expr_dot(&mut self, expr: &ExprDotType) {
         self.indent(|d| d.dump_expr(&expr.rhs));
         dump!(self, "dot @ {} {}", expr.pos, expr.id);
@@ -100,6 +100,7 @@ pub fn walk_file<'v, V: Visitor<'v>>(v: &mut V, f: &'v File) {
             ElemGlobal(ref g) => v.visit_global(g),
             ElemConst(ref c) => v.visit_const(c),
             ElemEnum(ref e) => v.visit_enum(e),
+            ElemImport(_) | ElemUse(_) => {}
         }
     }
 }
@@ -157,8 +158,12 @@ pub fn walk_const<'v, V: Visitor<'v>>(v: &mut V, c: &'v Const) {
     v.visit_expr(&c.expr);
 }
 
-pub fn walk_enum<'v, V: Visitor<'v>>(_v: &mut V, _e: &'v Enum) {
-    // nothing to do
+pub fn walk_enum<'v, V: Visitor<'v>>(v: &mut V, e: &'v Enum) {
+    for value in &e.values {
+        for ty in value.types.iter().flatten() {
+            v.visit_type(ty);
+        }
+    }
 }
 
 pub fn walk_struct<'v, V: Visitor<'v>>(v: &mut V, s: &'v Struct) {
@@ -334,15 +339,21 @@ pub fn walk_expr<'v, V: Visitor<'v>>(v: &mut V, e: &'v Expr) {
         }
 
         ExprLambda(ref value) => {
-            for param in &value.params {
+            for param in &value.fct.params {
                 v.visit_type(&param.data_type);
             }
 
-            if let Some(ref ret) = value.ret {
+            if let Some(ref ret) = value.fct.return_type {
                 v.visit_type(ret);
             }
 
-            v.visit_stmt(&value.block);
+            for stmt in &value.fct.block().stmts {
+                v.visit_stmt(stmt);
+            }
+
+            if let Some(ref expr) = value.fct.block().expr {
+                v.visit_expr(expr);
+            }
         }
 
         ExprBlock(ref value) => {
@@ -376,6 +387,20 @@ pub fn walk_expr<'v, V: Visitor<'v>>(v: &mut V, e: &'v Expr) {
             }
         }
 
+        ExprLitStruct(ref value) => {
+            for arg in &value.args {
+                v.visit_expr(&arg.expr);
+            }
+        }
+
+        ExprMatch(ref value) => {
+            v.visit_expr(&value.expr);
+
+            for case in &value.cases {
+                v.visit_expr(&case.value);
+            }
+        }
+
         ExprSuper(_) => {}
         ExprSelf(_) => {}
         ExprLitChar(_) => {}
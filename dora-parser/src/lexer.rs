@@ -322,6 +322,9 @@ impl Lexer {
                     } else {
                         TokenKind::EqEq
                     }
+                } else if nch == '>' {
+                    self.read_char();
+                    TokenKind::DoubleArrow
                 } else {
                     TokenKind::Eq
                 }
@@ -592,6 +595,8 @@ fn keywords_in_map() -> HashMap<&'static str, TokenKind> {
     keywords.insert("struct", TokenKind::Struct);
     keywords.insert("trait", TokenKind::Trait);
     keywords.insert("module", TokenKind::Module);
+    keywords.insert("import", TokenKind::Import);
+    keywords.insert("use", TokenKind::Use);
     keywords.insert("throws", TokenKind::Throws);
     keywords.insert("throw", TokenKind::Throw);
     keywords.insert("try", TokenKind::Try);
@@ -602,6 +607,7 @@ fn keywords_in_map() -> HashMap<&'static str, TokenKind> {
     keywords.insert("is", TokenKind::Is);
     keywords.insert("as", TokenKind::As);
     keywords.insert("const", TokenKind::Const);
+    keywords.insert("match", TokenKind::Match);
 
     keywords
 }
@@ -1057,8 +1063,9 @@ mod tests {
         assert_tok(&mut reader, TokenKind::Impl, 1, 8);
         assert_tok(&mut reader, TokenKind::CapitalThis, 1, 13);
 
-        let mut reader = Lexer::from_str("defer");
+        let mut reader = Lexer::from_str("defer match");
         assert_tok(&mut reader, TokenKind::Defer, 1, 1);
+        assert_tok(&mut reader, TokenKind::Match, 1, 7);
     }
 
     #[test]
@@ -1090,8 +1097,10 @@ mod tests {
         assert_tok(&mut reader, TokenKind::Ne, 1, 1);
         assert_tok(&mut reader, TokenKind::Not, 1, 3);
 
-        let mut reader = Lexer::from_str("->");
+        let mut reader = Lexer::from_str("->=>=");
         assert_tok(&mut reader, TokenKind::Arrow, 1, 1);
+        assert_tok(&mut reader, TokenKind::DoubleArrow, 1, 3);
+        assert_tok(&mut reader, TokenKind::Eq, 1, 5);
 
         let mut reader = Lexer::from_str("try!try?1");
         assert_tok(&mut reader, TokenKind::TryForce, 1, 1);
@@ -50,10 +50,13 @@ pub enum TokenKind {
     Trait,
     Impl,
     Module,
+    Import,
+    Use,
     Const,
 
     Underscore,
     Defer,
+    Match,
 
     // Operators
     Add,
@@ -75,6 +78,7 @@ pub enum TokenKind {
     Colon,
     Sep, // ::
     Arrow,
+    DoubleArrow,
     Tilde,
     BitOr,
     BitAnd,
@@ -157,10 +161,13 @@ impl TokenKind {
             TokenKind::Trait => "trait",
             TokenKind::Impl => "impl",
             TokenKind::Module => "module",
+            TokenKind::Import => "import",
+            TokenKind::Use => "use",
             TokenKind::Const => "const",
 
             TokenKind::Underscore => "_",
             TokenKind::Defer => "defer",
+            TokenKind::Match => "match",
 
             // Operators
             TokenKind::Add => "+",
@@ -181,7 +188,8 @@ impl TokenKind {
             TokenKind::Dot => ".",
             TokenKind::Colon => ":",
             TokenKind::Sep => "::",
-            TokenKind::Arrow => "=>",
+            TokenKind::Arrow => "->",
+            TokenKind::DoubleArrow => "=>",
             TokenKind::Tilde => "~",
             TokenKind::BitOr => "|",
             TokenKind::BitAnd => "&",
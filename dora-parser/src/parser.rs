@@ -104,6 +104,7 @@ impl<'a> Parser<'a> {
                         Modifier::Optimize,
                         Modifier::Test,
                         Modifier::Cannon,
+                        Modifier::Pub,
                     ],
                 )?;
                 let fct = self.parse_function(&modifiers)?;
@@ -118,6 +119,7 @@ impl<'a> Parser<'a> {
                         Modifier::Open,
                         Modifier::Internal,
                         Modifier::Cannon,
+                        Modifier::Pub,
                     ],
                 )?;
                 let class = self.parse_class(&modifiers)?;
@@ -125,14 +127,14 @@ impl<'a> Parser<'a> {
             }
 
             TokenKind::Struct => {
-                self.ban_modifiers(&modifiers)?;
-                let struc = self.parse_struct()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let struc = self.parse_struct(&modifiers)?;
                 elements.push(ElemStruct(struc))
             }
 
             TokenKind::Trait => {
-                self.ban_modifiers(&modifiers)?;
-                let xtrait = self.parse_trait()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let xtrait = self.parse_trait(&modifiers)?;
                 elements.push(ElemTrait(xtrait));
             }
 
@@ -149,22 +151,34 @@ impl<'a> Parser<'a> {
             }
 
             TokenKind::Let | TokenKind::Var => {
-                self.ban_modifiers(&modifiers)?;
-                self.parse_global(elements)?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                self.parse_global(&modifiers, elements)?;
             }
 
             TokenKind::Const => {
-                self.ban_modifiers(&modifiers)?;
-                let xconst = self.parse_const()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let xconst = self.parse_const(&modifiers)?;
                 elements.push(ElemConst(xconst));
             }
 
             TokenKind::Enum => {
-                self.ban_modifiers(&modifiers)?;
-                let xenum = self.parse_enum()?;
+                self.restrict_modifiers(&modifiers, &[Modifier::Pub])?;
+                let xenum = self.parse_enum(&modifiers)?;
                 elements.push(ElemEnum(xenum));
             }
 
+            TokenKind::Import => {
+                self.ban_modifiers(&modifiers)?;
+                let import = self.parse_import()?;
+                elements.push(ElemImport(import));
+            }
+
+            TokenKind::Use => {
+                self.ban_modifiers(&modifiers)?;
+                let xuse = self.parse_use()?;
+                elements.push(ElemUse(xuse));
+            }
+
             _ => {
                 let msg = ParseError::ExpectedTopLevelElement(self.token.name());
                 return Err(ParseErrorAndPos::new(self.token.position, msg));
@@ -174,13 +188,54 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn parse_enum(&mut self) -> Result<Enum, ParseErrorAndPos> {
+    fn parse_import(&mut self) -> Result<Import, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.expect_token(TokenKind::Import)?.position;
+        let path = self.parse_path()?;
+        self.expect_semicolon()?;
+        let span = self.span_from(start);
+
+        Ok(Import {
+            id: self.generate_id(),
+            pos,
+            span,
+            path,
+        })
+    }
+
+    fn parse_use(&mut self) -> Result<Use, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.expect_token(TokenKind::Use)?.position;
+        let path = self.parse_path()?;
+        self.expect_semicolon()?;
+        let span = self.span_from(start);
+
+        Ok(Use {
+            id: self.generate_id(),
+            pos,
+            span,
+            path,
+        })
+    }
+
+    fn parse_path(&mut self) -> Result<Vec<Name>, ParseErrorAndPos> {
+        let mut path = vec![self.expect_identifier()?];
+
+        while self.token.is(TokenKind::Sep) {
+            self.advance_token()?;
+            path.push(self.expect_identifier()?);
+        }
+
+        Ok(path)
+    }
+
+    fn parse_enum(&mut self, modifiers: &Modifiers) -> Result<Enum, ParseErrorAndPos> {
         let start = self.token.span.start();
         let pos = self.expect_token(TokenKind::Enum)?.position;
         let name = self.expect_identifier()?;
 
         self.expect_token(TokenKind::LBrace)?;
-        let values = self.parse_comma_list(TokenKind::RBrace, |p| p.parse_identifier())?;
+        let values = self.parse_comma_list(TokenKind::RBrace, |p| p.parse_enum_value())?;
         let span = self.span_from(start);
 
         Ok(Enum {
@@ -188,11 +243,35 @@ impl<'a> Parser<'a> {
             pos,
             span,
             name,
+            is_pub: modifiers.contains(Modifier::Pub),
             values,
         })
     }
 
-    fn parse_const(&mut self) -> Result<Const, ParseErrorAndPos> {
+    fn parse_enum_value(&mut self) -> Result<EnumValue, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.token.position;
+        let name = self.expect_identifier()?;
+
+        let types = if self.token.is(TokenKind::LParen) {
+            self.advance_token()?;
+            Some(self.parse_comma_list(TokenKind::RParen, |p| p.parse_type())?)
+        } else {
+            None
+        };
+
+        let span = self.span_from(start);
+
+        Ok(EnumValue {
+            id: self.generate_id(),
+            pos,
+            span,
+            name,
+            types,
+        })
+    }
+
+    fn parse_const(&mut self, modifiers: &Modifiers) -> Result<Const, ParseErrorAndPos> {
         let start = self.token.span.start();
         let pos = self.expect_token(TokenKind::Const)?.position;
         let name = self.expect_identifier()?;
@@ -208,6 +287,7 @@ impl<'a> Parser<'a> {
             pos,
             span,
             name,
+            is_pub: modifiers.contains(Modifier::Pub),
             data_type: ty,
             expr,
         })
@@ -255,7 +335,11 @@ impl<'a> Parser<'a> {
         })
     }
 
-    fn parse_global(&mut self, elements: &mut Vec<Elem>) -> Result<(), ParseErrorAndPos> {
+    fn parse_global(
+        &mut self,
+        modifiers: &Modifiers,
+        elements: &mut Vec<Elem>,
+    ) -> Result<(), ParseErrorAndPos> {
         let start = self.token.span.start();
         let pos = self.token.position;
         let reassignable = self.token.is(TokenKind::Var);
@@ -284,6 +368,7 @@ impl<'a> Parser<'a> {
             span,
             data_type,
             reassignable,
+            is_pub: modifiers.contains(Modifier::Pub),
             expr,
         };
 
@@ -292,7 +377,7 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn parse_trait(&mut self) -> Result<Trait, ParseErrorAndPos> {
+    fn parse_trait(&mut self, modifiers: &Modifiers) -> Result<Trait, ParseErrorAndPos> {
         let start = self.token.span.start();
         let pos = self.expect_token(TokenKind::Trait)?.position;
         let ident = self.expect_identifier()?;
@@ -317,11 +402,12 @@ impl<'a> Parser<'a> {
             name: ident,
             pos,
             span,
+            is_pub: modifiers.contains(Modifier::Pub),
             methods,
         })
     }
 
-    fn parse_struct(&mut self) -> Result<Struct, ParseErrorAndPos> {
+    fn parse_struct(&mut self, modifiers: &Modifiers) -> Result<Struct, ParseErrorAndPos> {
         let start = self.token.span.start();
         let pos = self.expect_token(TokenKind::Struct)?.position;
         let ident = self.expect_identifier()?;
@@ -335,6 +421,7 @@ impl<'a> Parser<'a> {
             name: ident,
             pos,
             span,
+            is_pub: modifiers.contains(Modifier::Pub),
             fields,
         })
     }
@@ -362,6 +449,7 @@ impl<'a> Parser<'a> {
         let has_open = modifiers.contains(Modifier::Open);
         let internal = modifiers.contains(Modifier::Internal);
         let is_abstract = modifiers.contains(Modifier::Abstract);
+        let is_pub = modifiers.contains(Modifier::Pub);
 
         let pos = self.expect_token(TokenKind::Class)?.position;
         let ident = self.expect_identifier()?;
@@ -375,6 +463,7 @@ impl<'a> Parser<'a> {
             has_open,
             internal,
             is_abstract,
+            is_pub,
             has_constructor: false,
             parent_class: None,
             constructor: None,
@@ -986,7 +1075,14 @@ impl<'a> Parser<'a> {
             TokenKind::Identifier(_) => {
                 let pos = self.token.position;
                 let start = self.token.span.start();
-                let name = self.expect_identifier()?;
+                let mut path = Vec::new();
+                let mut name = self.expect_identifier()?;
+
+                while self.token.is(TokenKind::Sep) {
+                    self.advance_token()?;
+                    path.push(name);
+                    name = self.expect_identifier()?;
+                }
 
                 let params = if self.token.is(TokenKind::LBracket) {
                     self.advance_token()?;
@@ -1000,6 +1096,7 @@ impl<'a> Parser<'a> {
                     self.generate_id(),
                     pos,
                     span,
+                    path,
                     name,
                     params,
                 ))
@@ -1311,6 +1408,118 @@ impl<'a> Parser<'a> {
         )))
     }
 
+    fn parse_match(&mut self) -> ExprResult {
+        let start = self.token.span.start();
+        let pos = self.expect_token(TokenKind::Match)?.position;
+
+        let expr = self.parse_expression_no_struct_lit()?;
+        let mut cases = Vec::new();
+
+        self.expect_token(TokenKind::LBrace)?;
+
+        while !self.token.is(TokenKind::RBrace) && !self.token.is_eof() {
+            let case = self.parse_match_case()?;
+            let needs_comma = case.value.needs_semicolon();
+            cases.push(case);
+
+            if needs_comma && !self.token.is(TokenKind::RBrace) {
+                self.expect_token(TokenKind::Comma)?;
+            } else if self.token.is(TokenKind::Comma) {
+                self.advance_token()?;
+            }
+        }
+
+        self.expect_token(TokenKind::RBrace)?;
+        let span = self.span_from(start);
+
+        Ok(Box::new(Expr::create_match(
+            self.generate_id(),
+            pos,
+            span,
+            expr,
+            cases,
+        )))
+    }
+
+    fn parse_match_case(&mut self) -> Result<MatchCaseType, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.token.position;
+        let mut patterns = vec![self.parse_match_pattern()?];
+
+        while self.token.is(TokenKind::BitOr) {
+            self.advance_token()?;
+            patterns.push(self.parse_match_pattern()?);
+        }
+
+        self.expect_token(TokenKind::DoubleArrow)?;
+        let value = self.parse_expression()?;
+        let span = self.span_from(start);
+
+        Ok(MatchCaseType {
+            id: self.generate_id(),
+            pos,
+            span,
+            patterns,
+            value,
+        })
+    }
+
+    fn parse_match_pattern(&mut self) -> Result<MatchPattern, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.token.position;
+
+        let data = if self.token.is(TokenKind::Underscore) {
+            self.advance_token()?;
+            MatchPatternData::Underscore
+        } else {
+            let mut path = vec![self.expect_identifier()?];
+
+            while self.token.is(TokenKind::Sep) {
+                self.advance_token()?;
+                path.push(self.expect_identifier()?);
+            }
+
+            let params = if self.token.is(TokenKind::LParen) {
+                self.advance_token()?;
+                Some(self.parse_comma_list(TokenKind::RParen, |p| p.parse_match_pattern_param())?)
+            } else {
+                None
+            };
+
+            MatchPatternData::Ident(MatchPatternIdent { path, params })
+        };
+
+        let span = self.span_from(start);
+
+        Ok(MatchPattern {
+            id: self.generate_id(),
+            pos,
+            span,
+            data,
+        })
+    }
+
+    fn parse_match_pattern_param(&mut self) -> Result<MatchPatternParam, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.token.position;
+
+        let name = if self.token.is(TokenKind::Underscore) {
+            self.advance_token()?;
+            None
+        } else {
+            Some(self.expect_identifier()?)
+        };
+
+        let span = self.span_from(start);
+
+        Ok(MatchPatternParam {
+            id: self.generate_id(),
+            pos,
+            span,
+            name,
+        })
+    }
+
     fn parse_for(&mut self) -> StmtResult {
         let start = self.token.span.start();
         let pos = self.expect_token(TokenKind::For)?.position;
@@ -1625,6 +1834,7 @@ impl<'a> Parser<'a> {
             TokenKind::LParen => self.parse_parentheses(),
             TokenKind::LBrace => self.parse_block(),
             TokenKind::If => self.parse_if(),
+            TokenKind::Match => self.parse_match(),
             TokenKind::LitChar(_) => self.parse_lit_char(),
             TokenKind::LitInt(_, _, _) => self.parse_lit_int(),
             TokenKind::LitFloat(_, _) => self.parse_lit_float(),
@@ -1650,6 +1860,10 @@ impl<'a> Parser<'a> {
         let span = self.token.span;
         let name = self.expect_identifier()?;
 
+        if self.parse_struct_lit && self.token.is(TokenKind::LBrace) {
+            return self.parse_lit_struct(pos, span.start(), name);
+        }
+
         Ok(Box::new(Expr::create_ident(
             self.generate_id(),
             pos,
@@ -1659,6 +1873,37 @@ impl<'a> Parser<'a> {
         )))
     }
 
+    fn parse_lit_struct(&mut self, pos: Position, start: u32, path: Name) -> ExprResult {
+        self.expect_token(TokenKind::LBrace)?;
+        let args = self.parse_comma_list(TokenKind::RBrace, |p| p.parse_struct_arg())?;
+        let span = self.span_from(start);
+
+        Ok(Box::new(Expr::create_lit_struct(
+            self.generate_id(),
+            pos,
+            span,
+            path,
+            args,
+        )))
+    }
+
+    fn parse_struct_arg(&mut self) -> Result<StructArg, ParseErrorAndPos> {
+        let start = self.token.span.start();
+        let pos = self.token.position;
+        let name = self.expect_identifier()?;
+        self.expect_token(TokenKind::Colon)?;
+        let expr = self.parse_expression()?;
+        let span = self.span_from(start);
+
+        Ok(StructArg {
+            id: self.generate_id(),
+            pos,
+            span,
+            name,
+            expr,
+        })
+    }
+
     fn parse_parentheses(&mut self) -> ExprResult {
         let pos = self.token.position;
         let start = self.token.span.start();
@@ -1951,23 +2196,51 @@ impl<'a> Parser<'a> {
             })?
         };
 
-        let ret = if self.token.is(TokenKind::Arrow) {
+        let return_type = if self.token.is(TokenKind::Arrow) {
             self.advance_token()?;
-            Some(Box::new(self.parse_type()?))
+            Some(self.parse_type()?)
         } else {
             None
         };
 
-        let block = self.parse_block_stmt()?;
+        let block = self.parse_block()?;
+        let block = if let Expr::ExprBlock(block_type) = *block {
+            Box::new(block_type)
+        } else {
+            unreachable!()
+        };
         let span = self.span_from(start);
 
+        let fct = Function {
+            id: self.generate_id(),
+            name: self.interner.intern("<lambda>"),
+            pos: tok.position,
+            span,
+            method: false,
+            has_open: false,
+            has_override: false,
+            has_final: false,
+            has_optimize: false,
+            has_optimize_immediately: false,
+            is_pub: false,
+            is_static: false,
+            internal: false,
+            is_abstract: false,
+            is_constructor: false,
+            is_test: false,
+            use_cannon: false,
+            params,
+            throws: false,
+            return_type,
+            block: Some(block),
+            type_params: None,
+        };
+
         Ok(Box::new(Expr::create_lambda(
             self.generate_id(),
             tok.position,
             span,
-            params,
-            ret,
-            block,
+            Box::new(fct),
         )))
     }
 
@@ -2840,6 +3113,77 @@ mod tests {
         assert!(ifexpr.else_block.is_some());
     }
 
+    #[test]
+    fn parse_match() {
+        let (expr, interner) = parse_expr("match x { Foo::A | Foo::B => 1, _ => { 2 } }");
+        let expr = expr.to_match().unwrap();
+
+        assert!(expr.expr.is_ident());
+        assert_eq!(2, expr.cases.len());
+
+        let case = &expr.cases[0];
+        assert_eq!(2, case.patterns.len());
+        assert!(case.value.is_lit_int());
+
+        match case.patterns[1].data {
+            MatchPatternData::Ident(ref ident) => {
+                assert_eq!(2, ident.path.len());
+                assert_eq!("Foo", *interner.str(ident.path[0]));
+                assert_eq!("B", *interner.str(ident.path[1]));
+                assert!(ident.params.is_none());
+            }
+
+            _ => unreachable!(),
+        }
+
+        let case = &expr.cases[1];
+        assert_eq!(1, case.patterns.len());
+        assert!(case.value.is_block());
+
+        match case.patterns[0].data {
+            MatchPatternData::Underscore => {}
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parse_match_with_params() {
+        let (expr, interner) = parse_expr("match x { Foo::A(a, _) => a, Foo::B() => 1 }");
+        let expr = expr.to_match().unwrap();
+
+        match expr.cases[0].patterns[0].data {
+            MatchPatternData::Ident(ref ident) => {
+                let params = ident.params.as_ref().unwrap();
+                assert_eq!(2, params.len());
+                assert_eq!("a", *interner.str(params[0].name.unwrap()));
+                assert!(params[1].name.is_none());
+            }
+
+            _ => unreachable!(),
+        }
+
+        match expr.cases[1].patterns[0].data {
+            MatchPatternData::Ident(ref ident) => {
+                assert_eq!(0, ident.params.as_ref().unwrap().len());
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parse_match_without_comma() {
+        let (expr, _) = parse_expr("match x { Foo::A => { 1 } Foo::B => 2 }");
+        assert_eq!(2, expr.to_match().unwrap().cases.len());
+
+        err_expr(
+            "match x { Foo::A => 1 Foo::B => 2 }",
+            ParseError::ExpectedToken(",".into(), "Foo".into()),
+            1,
+            23,
+        );
+    }
+
     #[test]
     fn parse_if_without_else() {
         let (expr, _) = parse_expr("if true { 2; }");
@@ -2958,6 +3302,19 @@ mod tests {
         assert_eq!("B", *interner.str(basic.params[1].to_basic().unwrap().name));
     }
 
+    #[test]
+    fn parse_type_with_path() {
+        let (ty, interner) = parse_type("a::b::Foo[A]");
+        let basic = ty.to_basic().unwrap();
+
+        assert_eq!(2, basic.path.len());
+        assert_eq!("a", *interner.str(basic.path[0]));
+        assert_eq!("b", *interner.str(basic.path[1]));
+        assert_eq!("Foo", *interner.str(basic.name));
+        assert_eq!(1, basic.params.len());
+        assert_eq!("a::b::Foo", ty.to_string(&interner));
+    }
+
     #[test]
     fn parse_type_fct_no_params() {
         let (ty, _) = parse_type("() -> ()");
@@ -3488,6 +3845,30 @@ mod tests {
         assert_eq!("fb", *interner.str(f2.name));
     }
 
+    #[test]
+    fn parse_lit_struct() {
+        let (expr, interner) = parse_expr("Foo { a: 1, b: x }");
+        let lit = expr.to_lit_struct().unwrap();
+
+        assert_eq!("Foo", *interner.str(lit.path));
+        assert_eq!(2, lit.args.len());
+        assert_eq!("a", *interner.str(lit.args[0].name));
+        assert!(lit.args[0].expr.is_lit_int());
+        assert_eq!("b", *interner.str(lit.args[1].name));
+        assert!(lit.args[1].expr.is_ident());
+
+        let (expr, _) = parse_expr("Foo {}");
+        assert_eq!(0, expr.to_lit_struct().unwrap().args.len());
+    }
+
+    #[test]
+    fn parse_lit_struct_in_cond() {
+        let (expr, _) = parse_expr("if (Foo { a: 1 }).a == 1 { }");
+        let ifexpr = expr.to_if().unwrap();
+        assert!(ifexpr.cond.is_bin());
+        assert!(ifexpr.then_block.is_block());
+    }
+
     #[test]
     fn parse_struct_lit_while() {
         let stmt = parse_stmt("while i < n { }");
@@ -3649,6 +4030,57 @@ mod tests {
         assert_eq!(false, global.reassignable);
     }
 
+    #[test]
+    fn parse_pub_items() {
+        let (prog, _) = parse("@pub let a: int = 0;");
+        assert!(prog.global0().is_pub);
+
+        let (prog, _) = parse("@pub class Foo");
+        assert!(prog.cls0().is_pub);
+
+        let (prog, _) = parse("@pub struct Foo {}");
+        assert!(prog.struct0().is_pub);
+
+        let (prog, _) = parse("@pub trait Foo {}");
+        assert!(prog.trait0().is_pub);
+
+        let (prog, _) = parse("@pub const x: int = 0;");
+        assert!(prog.const0().is_pub);
+
+        let (prog, _) = parse("@pub fun f() {}");
+        assert!(prog.fct0().is_pub);
+
+        let (prog, _) = parse("struct Foo {}");
+        assert!(!prog.struct0().is_pub);
+    }
+
+    #[test]
+    fn parse_import() {
+        let (prog, interner) = parse("import foo::bar;");
+        let import = prog.import0();
+
+        assert_eq!(2, import.path.len());
+        assert_eq!("foo", *interner.str(import.path[0]));
+        assert_eq!("bar", *interner.str(import.path[1]));
+
+        parse_err(
+            "import foo",
+            ParseError::ExpectedToken(";".into(), "<<EOF>>".into()),
+            1,
+            11,
+        );
+    }
+
+    #[test]
+    fn parse_use() {
+        let (prog, interner) = parse("use foo::Bar;");
+        let xuse = prog.use0();
+
+        assert_eq!(2, xuse.path.len());
+        assert_eq!("foo", *interner.str(xuse.path[0]));
+        assert_eq!("Bar", *interner.str(xuse.path[1]));
+    }
+
     #[test]
     fn parse_lit_char() {
         let (expr, _) = parse_expr("'a'");
@@ -3763,14 +4195,14 @@ mod tests {
         let (expr, _) = parse_expr("|| {}");
         let lambda = expr.to_lambda().unwrap();
 
-        assert!(lambda.ret.is_none());
+        assert!(lambda.fct.return_type.is_none());
     }
 
     #[test]
     fn parse_lambda_no_params_unit_as_return_value() {
         let (expr, _) = parse_expr("|| -> () {}");
         let lambda = expr.to_lambda().unwrap();
-        let ret = lambda.ret.as_ref().unwrap();
+        let ret = lambda.fct.return_type.as_ref().unwrap();
 
         assert!(ret.is_unit());
     }
@@ -3779,7 +4211,7 @@ mod tests {
     fn parse_lambda_no_params_with_return_value() {
         let (expr, interner) = parse_expr("|| -> A {}");
         let lambda = expr.to_lambda().unwrap();
-        let ret = lambda.ret.as_ref().unwrap();
+        let ret = lambda.fct.return_type.as_ref().unwrap();
         let basic = ret.to_basic().unwrap();
 
         assert_eq!("A", *interner.str(basic.name));
@@ -3790,14 +4222,14 @@ mod tests {
         let (expr, interner) = parse_expr("|a: A| -> B {}");
         let lambda = expr.to_lambda().unwrap();
 
-        assert_eq!(1, lambda.params.len());
+        assert_eq!(1, lambda.fct.params.len());
 
-        let param = &lambda.params[0];
+        let param = &lambda.fct.params[0];
         assert_eq!("a", *interner.str(param.name));
         let basic = param.data_type.to_basic().unwrap();
         assert_eq!("A", *interner.str(basic.name));
 
-        let ret = lambda.ret.as_ref().unwrap();
+        let ret = lambda.fct.return_type.as_ref().unwrap();
         let basic = ret.to_basic().unwrap();
 
         assert_eq!("B", *interner.str(basic.name));
@@ -3808,19 +4240,19 @@ mod tests {
         let (expr, interner) = parse_expr("|a: A, b: B| -> C {}");
         let lambda = expr.to_lambda().unwrap();
 
-        assert_eq!(2, lambda.params.len());
+        assert_eq!(2, lambda.fct.params.len());
 
-        let param = &lambda.params[0];
+        let param = &lambda.fct.params[0];
         assert_eq!("a", *interner.str(param.name));
         let basic = param.data_type.to_basic().unwrap();
         assert_eq!("A", *interner.str(basic.name));
 
-        let param = &lambda.params[1];
+        let param = &lambda.fct.params[1];
         assert_eq!("b", *interner.str(param.name));
         let basic = param.data_type.to_basic().unwrap();
         assert_eq!("B", *interner.str(basic.name));
 
-        let ret = lambda.ret.as_ref().unwrap();
+        let ret = lambda.fct.return_type.as_ref().unwrap();
         let basic = ret.to_basic().unwrap();
 
         assert_eq!("C", *interner.str(basic.name));
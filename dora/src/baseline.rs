@@ -6,6 +6,7 @@ pub mod cannon;
 pub mod codegen;
 pub mod dora_compile;
 pub mod dora_entry;
+pub mod dora_interpreter;
 pub mod dora_native;
 pub mod dora_throw;
 pub mod fct;
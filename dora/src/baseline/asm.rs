@@ -5,7 +5,9 @@ use dora_parser::lexer::position::Position;
 use crate::baseline::codegen::{ensure_native_stub, AllocationSize, CondCode, ExprStore};
 use crate::baseline::dora_native::{InternalFct, InternalFctDescriptor};
 use crate::baseline::fct::{CatchType, Comment, GcPoint, JitBaselineFct, JitDescriptor};
-use crate::cpu::{FReg, Mem, Reg, FREG_RESULT, REG_PARAMS, REG_RESULT, REG_THREAD, REG_TMP1};
+use crate::cpu::{
+    FReg, Mem, Reg, FREG_RESULT, REG_PARAMS, REG_RESULT, REG_THREAD, REG_TMP1, REG_TMP2,
+};
 use crate::gc::tlab::TLAB_OBJECT_SIZE;
 use crate::gc::Address;
 use crate::masm::{Label, MacroAssembler, ScratchReg};
@@ -116,6 +118,10 @@ where
         self.masm.jump(label);
     }
 
+    pub fn jump_table(&mut self, index: Reg, targets: &[Label]) {
+        self.masm.jump_table(index, targets);
+    }
+
     pub fn jump_if(&mut self, cond: CondCode, label: Label) {
         self.masm.jump_if(cond, label);
     }
@@ -455,11 +461,59 @@ where
     }
 
     pub fn var_store(&mut self, offset: i32, ty: BuiltinType, src: ExprStore) {
-        self.masm.store_mem(ty.mode(), Mem::Local(offset), src);
+        if ty.is_struct() {
+            self.copy_struct(ty, Mem::Local(offset), Mem::Base(src.reg(), 0));
+        } else {
+            self.masm.store_mem(ty.mode(), Mem::Local(offset), src);
+        }
     }
 
     pub fn var_load(&mut self, offset: i32, ty: BuiltinType, dest: ExprStore) {
-        self.masm.load_mem(ty.mode(), dest, Mem::Local(offset));
+        if ty.is_struct() {
+            self.masm.lea(dest.reg(), Mem::Local(offset));
+        } else {
+            self.masm.load_mem(ty.mode(), dest, Mem::Local(offset));
+        }
+    }
+
+    pub fn lea(&mut self, dest: Reg, mem: Mem) {
+        self.masm.lea(dest, mem);
+    }
+
+    pub fn emit_barrier(&mut self, src: Reg, card_table_offset: usize) {
+        self.masm.emit_barrier(src, card_table_offset);
+    }
+
+    // copies the struct value at `src` to `dest`, clobbers REG_TMP1 or REG_TMP2
+    pub fn copy_struct(&mut self, ty: BuiltinType, dest: Mem, src: Mem) {
+        let tmp = struct_tmp_reg(dest, src);
+        let size = ty.size(self.vm);
+        let mut offset = 0;
+
+        while offset < size {
+            let mode = struct_chunk_mode(size - offset);
+            self.masm
+                .load_mem(mode, tmp.into(), mem_with_offset(src, offset));
+            self.masm
+                .store_mem(mode, mem_with_offset(dest, offset), tmp.into());
+            offset += mode.size();
+        }
+    }
+
+    // fills the struct value at `dest` with zeroes
+    pub fn zero_struct(&mut self, ty: BuiltinType, dest: Mem) {
+        let tmp = struct_tmp_reg(dest, dest);
+        let size = ty.size(self.vm);
+        let mut offset = 0;
+
+        self.masm.load_int_const(MachineMode::Int64, tmp, 0);
+
+        while offset < size {
+            let mode = struct_chunk_mode(size - offset);
+            self.masm
+                .store_mem(mode, mem_with_offset(dest, offset), tmp.into());
+            offset += mode.size();
+        }
     }
 
     pub fn jit(mut self, stacksize: i32, desc: JitDescriptor, throws: bool) -> JitBaselineFct {
@@ -746,3 +800,36 @@ enum SlowPathKind {
     TlabAllocationFailure(Label, Label, Reg, AllocationSize, Position, bool, GcPoint),
     StackOverflow(Label, Position),
 }
+
+fn struct_tmp_reg(dest: Mem, src: Mem) -> Reg {
+    let uses = |mem: Mem, reg: Reg| match mem {
+        Mem::Local(_) => false,
+        Mem::Base(base, _) => base == reg,
+        _ => unimplemented!(),
+    };
+
+    if uses(dest, REG_TMP1) || uses(src, REG_TMP1) {
+        assert!(!uses(dest, REG_TMP2) && !uses(src, REG_TMP2));
+        REG_TMP2
+    } else {
+        REG_TMP1
+    }
+}
+
+fn struct_chunk_mode(remaining: i32) -> MachineMode {
+    if remaining >= 8 {
+        MachineMode::Int64
+    } else if remaining >= 4 {
+        MachineMode::Int32
+    } else {
+        MachineMode::Int8
+    }
+}
+
+fn mem_with_offset(mem: Mem, offset: i32) -> Mem {
+    match mem {
+        Mem::Local(disp) => Mem::Local(disp + offset),
+        Mem::Base(base, disp) => Mem::Base(base, disp + offset),
+        _ => unimplemented!(),
+    }
+}
@@ -24,14 +24,18 @@ use crate::field::FieldId;
 use crate::gc::Address;
 use crate::masm::*;
 use crate::mem;
-use crate::object::{Header, Str};
+use crate::object::{offset_of_array_data, Header, Str};
 use crate::os::signal::Trap;
-use crate::semck::always_returns;
-use crate::semck::specialize::specialize_class_ty;
+use crate::semck::specialize::{
+    enum_tag_offset, specialize_class_ty, specialize_enum_value, specialize_lambda,
+    specialize_struct_id_params, specialize_type,
+};
+use crate::semck::{always_returns, match_case_table};
 use crate::size::InstanceSize;
 use crate::ty::{BuiltinType, MachineMode, TypeList};
 use crate::vm::{
-    Arg, CallSite, ConstId, Fct, FctId, FctKind, FctParent, FctSrc, IdentType, Intrinsic, VarId, VM,
+    Arg, CallSite, CallType, ConstId, EnumId, Fct, FctId, FctKind, FctParent, FctSrc, GlobalId,
+    IdentType, Intrinsic, StructFieldDef, StructFieldId, VarId, VM,
 };
 use crate::vtable::{VTable, DISPLAY_SIZE};
 
@@ -104,7 +108,7 @@ where
             let offset = self.var_offset(var.id);
             self.asm.store_mem(mode, Mem::Local(offset), dest);
 
-            self.stack.add_var(var.ty, offset);
+            self.stack.add_var(var.ty, offset, self.vm);
 
             if mode.is_float() {
                 freg_idx += 1;
@@ -122,7 +126,7 @@ where
             assert!(self.var_to_slot.insert(varid, slot_param).is_none());
 
             let offset = self.var_offset(varid);
-            self.stack.add_var(ty, offset);
+            self.stack.add_var(ty, offset, self.vm);
 
             if is_float && freg_idx < FREG_PARAMS.len() {
                 let reg = FREG_PARAMS[freg_idx];
@@ -140,10 +144,45 @@ where
                     .var_store(self.var_offset(varid), self.jit_info.ty(varid), reg.into());
 
                 reg_idx += 1;
+            } else if let Some(&ptr_offset) = self.jit_info.map_struct_params.get(&varid) {
+                self.asm.emit_comment(Comment::StoreParam(varid));
+                self.asm
+                    .load_mem(MachineMode::Ptr, REG_TMP2.into(), Mem::Local(ptr_offset));
+                self.asm
+                    .copy_struct(ty, Mem::Local(offset), Mem::Base(REG_TMP2, 0));
             } else {
                 // ignore params not stored in register
             }
         }
+
+        if let Some(struct_return) = self.jit_info.struct_return {
+            if reg_idx < REG_PARAMS.len() {
+                let reg = REG_PARAMS[reg_idx];
+                self.asm
+                    .store_mem(MachineMode::Ptr, Mem::Local(struct_return), reg.into());
+            } else {
+                let param_offset = self.jit_info.struct_return_param.unwrap();
+                self.asm
+                    .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(param_offset));
+                self.asm
+                    .store_mem(MachineMode::Ptr, Mem::Local(struct_return), REG_TMP1.into());
+            }
+        }
+    }
+
+    // copies struct return value into storage provided by caller
+    fn emit_struct_return(&mut self) {
+        let return_type = self.specialize_type(self.fct.return_type);
+        let struct_return = self.jit_info.struct_return.unwrap();
+
+        self.asm
+            .load_mem(MachineMode::Ptr, REG_TMP2.into(), Mem::Local(struct_return));
+        self.asm.copy_struct(
+            return_type,
+            Mem::Base(REG_TMP2, 0),
+            Mem::Base(REG_RESULT, 0),
+        );
+        self.asm.copy_reg(MachineMode::Ptr, REG_RESULT, REG_TMP2);
     }
 
     fn emit_prolog(&mut self) {
@@ -168,6 +207,10 @@ where
         if let Some(ref expr) = s.expr {
             self.emit_expr_result_reg(expr);
 
+            if return_type.is_struct() {
+                self.emit_struct_return();
+            }
+
             if len > 0 {
                 let offset = self.jit_info.eh_return_value.unwrap();
                 let rmode = return_type.mode();
@@ -403,7 +446,7 @@ where
         }
 
         let offset = self.var_offset(var);
-        self.stack.add_var(ty, offset);
+        self.stack.add_var(ty, offset, self.vm);
 
         // uninitialized variables which reference objects need to be initialized to null
         // otherwise the GC can't know if the stored value is a valid pointer
@@ -414,6 +457,8 @@ where
                 self.jit_info.ty(var),
                 REG_RESULT.into(),
             );
+        } else if ty.is_struct() && !initialized {
+            self.asm.zero_struct(ty, Mem::Local(offset));
         }
     }
 
@@ -468,11 +513,11 @@ where
             self.stack.push_scope();
             self.managed_stack.push_scope();
 
-            self.stack.add_var(BuiltinType::Ptr, offset);
+            self.stack.add_var(BuiltinType::Ptr, offset, self.vm);
             let catch_span = self.stmt_with_finally(s, &catch.block, lbl_after);
 
             self.managed_stack.pop_scope(self.vm);
-            self.stack.pop_scope();
+            self.stack.pop_scope(self.vm);
 
             let ty = self.src.ty(catch.data_type.id());
             let ty = self.specialize_type(ty);
@@ -532,7 +577,7 @@ where
         self.managed_stack.push_scope();
 
         let offset = *self.jit_info.map_offsets.get(s.id).unwrap();
-        self.stack.add_var(BuiltinType::Ptr, offset);
+        self.stack.add_var(BuiltinType::Ptr, offset, self.vm);
         let _slot = self.managed_stack.add_scope(BuiltinType::Ptr, self.vm);
 
         self.visit_stmt(&finally_block.block);
@@ -542,7 +587,7 @@ where
         self.asm.throw(REG_RESULT, s.pos);
 
         self.managed_stack.pop_scope(self.vm);
-        self.stack.pop_scope();
+        self.stack.pop_scope(self.vm);
 
         Some(finally_pos)
     }
@@ -563,7 +608,7 @@ where
     }
 
     fn emit_call_site_old(&mut self, call_site: &CallSite<'ast>, pos: Position) -> ExprStore {
-        let callee = self.vm.fcts.idx(call_site.callee);
+        let callee = self.vm.fcts.idx(call_site.callee.unwrap());
         let callee = callee.read();
         let return_type = self.specialize_type(callee.return_type);
 
@@ -589,16 +634,19 @@ where
             ExprPath(ref expr) => self.emit_path(expr, dest),
             ExprDelegation(ref expr) => self.emit_delegation(expr, dest),
             ExprDot(ref expr) => self.emit_dot(expr, dest),
+            ExprSelf(ref expr) if self.fct.is_lambda() => self.emit_captured_self(expr, dest),
             ExprSelf(_) => self.emit_self(dest),
             ExprSuper(_) => self.emit_self(dest),
             ExprNil(_) => self.emit_nil(dest.reg()),
             ExprConv(ref expr) => self.emit_conv(expr, dest.reg()),
             ExprTemplate(ref expr) => self.emit_template(expr, dest.reg()),
             ExprTry(ref expr) => self.emit_try(expr, dest),
-            ExprLambda(_) => unimplemented!(),
+            ExprLambda(ref expr) => self.emit_lambda(expr, dest),
             ExprBlock(ref expr) => self.emit_block(expr, dest),
             ExprIf(ref expr) => self.emit_if(expr, dest),
+            ExprLitStruct(ref expr) => self.emit_lit_struct(expr, dest),
             ExprTuple(_) => unimplemented!(),
+            ExprMatch(ref expr) => self.emit_match(expr, dest),
         }
     }
 
@@ -626,6 +674,122 @@ where
         self.asm.bind_label(lbl_end);
     }
 
+    fn emit_match(&mut self, e: &'ast ExprMatchType, dest: ExprStore) {
+        let lbl_end = self.asm.create_label();
+        let lbl_cases = e
+            .cases
+            .iter()
+            .map(|_| self.asm.create_label())
+            .collect::<Vec<_>>();
+
+        let targets = match_case_table(self.vm, self.src, e)
+            .into_iter()
+            .map(|idx| lbl_cases[idx])
+            .collect::<Vec<_>>();
+
+        self.emit_expr_result_reg(&e.expr);
+
+        // values of enums with payload data are objects, dispatch on their tag
+        let object = if self.ty(e.expr.id()).is_enum_object() {
+            let offset = self.jit_info.get_store(e.expr.id()).offset();
+            self.asm.test_if_nil_bailout(e.pos, REG_RESULT, Trap::NIL);
+            self.asm
+                .store_mem(MachineMode::Ptr, Mem::Local(offset), REG_RESULT.into());
+            self.stack.add_temp(BuiltinType::Ptr, offset, self.vm);
+            let slot = self.managed_stack.add_temp(BuiltinType::Ptr, self.vm);
+
+            self.asm.load_mem(
+                MachineMode::Int32,
+                REG_RESULT.into(),
+                Mem::Base(REG_RESULT, enum_tag_offset()),
+            );
+
+            Some((slot, offset))
+        } else {
+            None
+        };
+
+        self.asm.jump_table(REG_RESULT, &targets);
+
+        for (case, &lbl_case) in e.cases.iter().zip(&lbl_cases) {
+            self.asm.bind_label(lbl_case);
+
+            self.stack.push_scope();
+            self.managed_stack.push_scope();
+
+            if let Some((_, offset)) = object {
+                for pattern in &case.patterns {
+                    self.emit_match_bindings(pattern, offset);
+                }
+            }
+
+            self.emit_expr(&case.value, dest);
+
+            self.managed_stack.pop_scope(self.vm);
+            self.stack.pop_scope(self.vm);
+
+            self.asm.jump(lbl_end);
+        }
+
+        self.asm.bind_label(lbl_end);
+
+        if let Some((slot, offset)) = object {
+            self.stack.free_temp(BuiltinType::Ptr, offset, self.vm);
+            self.managed_stack.free_temp(slot, self.vm);
+        }
+    }
+
+    // copies the payload of the matched object into the pattern's variables
+    fn emit_match_bindings(&mut self, pattern: &'ast MatchPattern, object_offset: i32) {
+        let params = match pattern.data {
+            MatchPatternData::Ident(ref ident) => match ident.params {
+                Some(ref params) => params,
+                None => return,
+            },
+            MatchPatternData::Underscore => return,
+        };
+
+        let cls_id = match self.src.map_idents.get(pattern.id) {
+            Some(&IdentType::EnumValue(enum_id, value)) => {
+                specialize_enum_value(self.vm, enum_id, value)
+            }
+            _ => unreachable!(),
+        };
+        let cls = self.vm.class_defs.idx(cls_id);
+        let cls = cls.read();
+
+        for (param, field) in params.iter().zip(&cls.fields) {
+            let var_id = match self.src.map_vars.get(param.id) {
+                Some(&var_id) => var_id,
+                None => continue,
+            };
+
+            let ty = self.jit_info.ty(var_id);
+            let slot_var = self.managed_stack.add_scope(ty, self.vm);
+            assert!(self.var_to_slot.insert(var_id, slot_var).is_none());
+
+            let offset = self.var_offset(var_id);
+            self.stack.add_var(ty, offset, self.vm);
+
+            if ty.is_unit() {
+                continue;
+            }
+
+            let reg = result_reg(ty.mode());
+            self.asm
+                .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(object_offset));
+
+            if ty.is_struct() {
+                self.asm.lea(reg.reg(), Mem::Base(REG_TMP1, field.offset));
+            } else {
+                self.asm
+                    .load_mem(field.ty.mode(), reg, Mem::Base(REG_TMP1, field.offset));
+            }
+
+            self.asm.var_store(offset, ty, reg);
+        }
+    }
+
     fn emit_block(&mut self, block: &'ast ExprBlockType, dest: ExprStore) {
         self.stack.push_scope();
         self.managed_stack.push_scope();
@@ -639,7 +803,7 @@ where
         }
 
         self.managed_stack.pop_scope(self.vm);
-        self.stack.pop_scope();
+        self.stack.pop_scope(self.vm);
     }
 
     fn emit_try(&mut self, e: &'ast ExprTryType, dest: ExprStore) {
@@ -717,8 +881,11 @@ where
             REG_RESULT.into(),
         );
 
-        self.stack
-            .add_temp(BuiltinType::Ptr, template_info.string_buffer_offset);
+        self.stack.add_temp(
+            BuiltinType::Ptr,
+            template_info.string_buffer_offset,
+            self.vm,
+        );
         let slot_string_buffer_offset = self.managed_stack.add_temp(BuiltinType::Ptr, self.vm);
 
         for (idx, part) in e.parts.iter().enumerate() {
@@ -754,8 +921,11 @@ where
         }
 
         self.emit_call_site(&template_info.string_buffer_to_string, e.pos, dest.into());
-        self.stack
-            .free_temp(BuiltinType::Ptr, template_info.string_buffer_offset);
+        self.stack.free_temp(
+            BuiltinType::Ptr,
+            template_info.string_buffer_offset,
+            self.vm,
+        );
         self.managed_stack
             .free_temp(slot_string_buffer_offset, self.vm);
     }
@@ -909,7 +1079,7 @@ where
         let ty = arg.ty();
         let offset = arg.offset();
 
-        self.stack.add_temp(ty, offset);
+        self.stack.add_temp(ty, offset, self.vm);
         let slot = self.managed_stack.add_temp(ty, self.vm);
 
         (slot, offset)
@@ -920,7 +1090,7 @@ where
         let ty = self.ty(id);
         let offset = self.jit_info.get_store(id).offset();
 
-        self.stack.add_temp(ty, offset);
+        self.stack.add_temp(ty, offset, self.vm);
         let slot = self.managed_stack.add_temp(ty, self.vm);
 
         (slot, offset)
@@ -928,7 +1098,7 @@ where
 
     fn free_temp_node(&mut self, expr: &Expr, slot: ManagedStackSlot, offset: i32) {
         let ty = self.ty(expr.id());
-        self.stack.free_temp(ty, offset);
+        self.stack.free_temp(ty, offset, self.vm);
         self.managed_stack.free_temp(slot, self.vm);
     }
 
@@ -946,24 +1116,184 @@ where
             .load_mem(var.ty.mode(), dest.into(), Mem::Local(offset));
     }
 
+    fn emit_captured_self(&mut self, e: &'ast ExprSelfType, dest: ExprStore) {
+        let var_id = self.src.map_idents.get(e.id).unwrap().var_id();
+
+        self.asm.emit_comment(Comment::LoadVar(var_id));
+        self.asm
+            .var_load(self.var_offset(var_id), self.jit_info.ty(var_id), dest);
+    }
+
+    fn emit_lambda(&mut self, e: &'ast ExprLambdaType, dest: ExprStore) {
+        let lambda_id = *self.src.map_lambdas.get(e.id).unwrap();
+        let lambda = self.vm.fcts.idx(lambda_id);
+        let lambda = lambda.read();
+        let lambda_src = lambda.src();
+        let lambda_src = lambda_src.read();
+
+        let cls_id = specialize_lambda(
+            self.vm,
+            &*lambda,
+            &*lambda_src,
+            self.cls_type_params,
+            self.fct_type_params,
+        );
+        let offset = *self.jit_info.map_offsets.get(e.id).unwrap();
+        self.emit_allocation(e.pos, &[], cls_id, offset, REG_RESULT);
+
+        let cls = self.vm.class_defs.idx(cls_id);
+        let cls = cls.read();
+
+        // copy values of captured variables into closure object
+        for (capture, field) in lambda_src.captures.iter().zip(&cls.fields) {
+            if field.ty.is_unit() {
+                continue;
+            }
+
+            let reg = result_reg(field.ty.mode());
+            self.asm.emit_comment(Comment::LoadVar(capture.outer));
+            self.asm.var_load(
+                self.var_offset(capture.outer),
+                self.jit_info.ty(capture.outer),
+                reg,
+            );
+
+            self.asm
+                .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(offset));
+            self.store_field_value(e.pos, field.ty, REG_TMP1, field.offset, reg);
+        }
+
+        self.asm
+            .load_mem(MachineMode::Ptr, dest, Mem::Local(offset));
+    }
+
+    // lambdas copy the captured variables from the closure object
+    // into local variables on entry
+    fn load_captured_vars(&mut self) {
+        let cls_id = specialize_lambda(
+            self.vm,
+            self.fct,
+            &*self.src,
+            self.cls_type_params,
+            self.fct_type_params,
+        );
+        let cls = self.vm.class_defs.idx(cls_id);
+        let cls = cls.read();
+
+        let closure_offset = self.var_offset(self.src.var_self().id);
+        let captures = self.src.captures.clone();
+
+        for (capture, field) in captures.iter().zip(&cls.fields) {
+            let var_id = capture.inner;
+            let ty = self.jit_info.ty(var_id);
+
+            let slot_var = self.managed_stack.add_scope(ty, self.vm);
+            assert!(self.var_to_slot.insert(var_id, slot_var).is_none());
+
+            let offset = self.var_offset(var_id);
+            self.stack.add_var(ty, offset, self.vm);
+
+            if ty.is_unit() {
+                continue;
+            }
+
+            let reg = result_reg(ty.mode());
+            self.asm.emit_comment(Comment::StoreParam(var_id));
+            self.asm.load_mem(
+                MachineMode::Ptr,
+                REG_TMP1.into(),
+                Mem::Local(closure_offset),
+            );
+            if ty.is_struct() {
+                self.asm.lea(reg.reg(), Mem::Base(REG_TMP1, field.offset));
+            } else {
+                self.asm
+                    .load_mem(field.ty.mode(), reg, Mem::Base(REG_TMP1, field.offset));
+            }
+            self.asm.var_store(offset, ty, reg);
+        }
+    }
+
     fn emit_nil(&mut self, dest: Reg) {
         self.asm.load_nil(dest);
     }
 
     fn emit_dot(&mut self, expr: &'ast ExprDotType, dest: ExprStore) {
-        let (ty, field) = {
-            let ident_type = self.src.map_idents.get(expr.id).unwrap();
+        let ident_type = self.src.map_idents.get(expr.id).unwrap();
 
-            match ident_type {
-                &IdentType::Field(ty, field) => (ty, field),
-                _ => unreachable!(),
+        match ident_type {
+            &IdentType::Field(ty, field) => {
+                let ty = self.specialize_type(ty);
+
+                self.emit_expr(&expr.lhs, REG_RESULT.into());
+                self.emit_field_access(expr.pos, ty, field, REG_RESULT, dest);
+            }
+
+            &IdentType::StructField(ty, field_id) => {
+                let field = self.struct_field(ty, field_id);
+
+                // lhs evaluates to address of struct value
+                self.emit_expr(&expr.lhs, REG_RESULT.into());
+
+                if field.ty.is_struct() {
+                    self.asm
+                        .lea(dest.reg(), Mem::Base(REG_RESULT, field.offset));
+                } else {
+                    self.asm
+                        .load_mem(field.ty.mode(), dest, Mem::Base(REG_RESULT, field.offset));
+                }
             }
+
+            _ => unreachable!(),
+        }
+    }
+
+    fn struct_field(&self, ty: BuiltinType, field_id: StructFieldId) -> StructFieldDef {
+        let (struct_id, list_id) = match self.specialize_type(ty) {
+            BuiltinType::Struct(struct_id, list_id) => (struct_id, list_id),
+            _ => unreachable!(),
         };
 
-        let ty = self.specialize_type(ty);
+        let params = self.vm.lists.lock().get(list_id);
+        let struct_def_id = specialize_struct_id_params(self.vm, struct_id, params);
+        let struct_def = self.vm.struct_defs.idx(struct_def_id);
+        let struct_def = struct_def.lock();
+
+        struct_def.fields[field_id].clone()
+    }
+
+    // stores value into field of object, struct values are copied
+    fn store_field_value(
+        &mut self,
+        pos: Position,
+        ty: BuiltinType,
+        base: Reg,
+        offset: i32,
+        src: ExprStore,
+    ) {
+        let card_table_offset = self.vm.gc.card_table_offset();
 
-        self.emit_expr(&expr.lhs, REG_RESULT.into());
-        self.emit_field_access(expr.pos, ty, field, REG_RESULT, dest);
+        if ty.is_struct() {
+            self.asm.test_if_nil_bailout(pos, base, Trap::NIL);
+            self.asm
+                .copy_struct(ty, Mem::Base(base, offset), Mem::Base(src.reg(), 0));
+
+            if self.vm.gc.needs_write_barrier() && !ty.ref_offsets(self.vm).is_empty() {
+                self.asm.emit_barrier(base, card_table_offset);
+            }
+        } else {
+            let write_barrier = self.vm.gc.needs_write_barrier() && ty.reference_type();
+
+            self.asm.store_field(
+                ty.mode(),
+                base,
+                offset,
+                src,
+                pos.line as i32,
+                write_barrier,
+                card_table_offset,
+            );
+        }
     }
 
     fn emit_field_access(
@@ -980,8 +1310,56 @@ where
         let field = &cls.fields[fieldid.idx()];
 
         self.asm.emit_comment(Comment::LoadField(cls_id, fieldid));
-        self.asm
-            .load_field(field.ty.mode(), dest, src, field.offset, pos.line as i32);
+
+        if field.ty.is_struct() {
+            // address of struct value stays valid until next gc point
+            self.asm.test_if_nil_bailout(pos, src, Trap::NIL);
+            self.asm.lea(dest.reg(), Mem::Base(src, field.offset));
+        } else {
+            self.asm
+                .load_field(field.ty.mode(), dest, src, field.offset, pos.line as i32);
+        }
+    }
+
+    fn emit_lit_struct(&mut self, e: &'ast ExprLitStructType, dest: ExprStore) {
+        let ty = self.ty(e.id);
+        let offset = self.jit_info.get_store(e.id).offset();
+
+        let struct_id = match ty {
+            BuiltinType::Struct(struct_id, _) => struct_id,
+            _ => unreachable!(),
+        };
+
+        // clear struct first, such that the gc only sees valid references
+        self.asm.zero_struct(ty, Mem::Local(offset));
+        self.stack.add_temp(ty, offset, self.vm);
+        let slot = self.managed_stack.add_temp(ty, self.vm);
+
+        for arg in &e.args {
+            let field_id = {
+                let struc = self.vm.structs.idx(struct_id);
+                let struc = struc.lock();
+                struc.find_field(arg.name).unwrap()
+            };
+
+            let field = self.struct_field(ty, field_id);
+            let res = result_reg_ty(field.ty);
+            self.emit_expr(&arg.expr, res);
+
+            let field_mem = Mem::Local(offset + field.offset);
+
+            if field.ty.is_struct() {
+                self.asm
+                    .copy_struct(field.ty, field_mem, Mem::Base(res.reg(), 0));
+            } else {
+                self.asm.store_mem(field.ty.mode(), field_mem, res);
+            }
+        }
+
+        self.stack.free_temp(ty, offset, self.vm);
+        self.managed_stack.free_temp(slot, self.vm);
+
+        self.asm.lea(dest.reg(), Mem::Local(offset));
     }
 
     fn emit_lit_char(&mut self, lit: &'ast ExprLitCharType, dest: Reg) {
@@ -1031,7 +1409,12 @@ where
     }
 
     fn emit_ident(&mut self, e: &'ast ExprIdentType, dest: ExprStore) {
-        let ident = self.src.map_idents.get(e.id).unwrap();
+        self.emit_ident_type(e.id, e.pos, dest);
+    }
+
+    // also used for items of other modules, e.g. `a::b`
+    fn emit_ident_type(&mut self, id: NodeId, pos: Position, dest: ExprStore) {
+        let ident = self.src.map_idents.get(id).unwrap();
 
         match ident {
             &IdentType::Var(varid) => {
@@ -1050,24 +1433,36 @@ where
                 self.asm.emit_comment(Comment::LoadGlobal(gid));
                 self.asm.load_constpool(REG_TMP1, disp + pos);
 
-                self.asm
-                    .load_mem(glob.ty.mode(), dest, Mem::Base(REG_TMP1, 0));
+                if glob.ty.is_struct() {
+                    self.asm.copy_reg(MachineMode::Ptr, dest.reg(), REG_TMP1);
+                } else {
+                    self.asm
+                        .load_mem(glob.ty.mode(), dest, Mem::Base(REG_TMP1, 0));
+                }
             }
 
             &IdentType::Field(cls, field) => {
                 self.emit_self(REG_RESULT.into());
-                self.emit_field_access(e.pos, cls, field, REG_RESULT, dest);
+                self.emit_field_access(pos, cls, field, REG_RESULT, dest);
             }
 
             &IdentType::Struct(_) => {
-                unimplemented!();
+                // struct without fields
+                let ty = self.ty(id);
+                let offset = self.jit_info.get_store(id).offset();
+
+                self.asm.zero_struct(ty, Mem::Local(offset));
+                self.asm.lea(dest.reg(), Mem::Local(offset));
             }
 
+            &IdentType::StructField(_, _) => unreachable!(),
+
             &IdentType::Const(const_id) => {
                 self.emit_const(const_id, dest);
             }
 
             &IdentType::Enum(_) | &IdentType::EnumValue(_, _) => unreachable!(),
+            &IdentType::Module(_) => unreachable!(),
             &IdentType::Fct(_) | &IdentType::FctType(_, _) => unreachable!(),
             &IdentType::Class(_) | &IdentType::ClassType(_, _) => unreachable!(),
             &IdentType::Method(_, _) | &IdentType::MethodType(_, _, _) => unreachable!(),
@@ -1252,7 +1647,12 @@ where
                 self.asm.emit_comment(Comment::StoreGlobal(gid));
                 self.asm.load_constpool(REG_TMP1, disp + pos);
 
-                self.asm.store_mem(ty.mode(), Mem::Base(REG_TMP1, 0), dest);
+                if ty.is_struct() {
+                    self.asm
+                        .copy_struct(ty, Mem::Base(REG_TMP1, 0), Mem::Base(dest.reg(), 0));
+                } else {
+                    self.asm.store_mem(ty.mode(), Mem::Base(REG_TMP1, 0), dest);
+                }
             }
 
             &IdentType::Field(ty, fieldid) => {
@@ -1297,19 +1697,7 @@ where
                     .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(temp_offset));
 
                 self.asm.emit_comment(Comment::StoreField(cls_id, fieldid));
-
-                let write_barrier = self.vm.gc.needs_write_barrier() && field.ty.reference_type();
-                let card_table_offset = self.vm.gc.card_table_offset();
-
-                self.asm.store_field(
-                    field.ty.mode(),
-                    REG_TMP1,
-                    field.offset,
-                    reg,
-                    e.pos.line as i32,
-                    write_barrier,
-                    card_table_offset,
-                );
+                self.store_field_value(e.pos, field.ty, REG_TMP1, field.offset, reg);
 
                 if verify_refs {
                     let gcpoint = self.stack.gcpoint();
@@ -1335,11 +1723,15 @@ where
                 self.free_temp_node(temp, slot, temp_offset);
             }
 
-            &IdentType::Struct(_) => {
-                unimplemented!();
+            &IdentType::StructField(ty, field_id) => {
+                self.emit_assign_struct_field(e, ty, field_id);
             }
 
-            &IdentType::Const(_) | &IdentType::Enum(_) | &IdentType::EnumValue(_, _) => {
+            &IdentType::Struct(_)
+            | &IdentType::Const(_)
+            | &IdentType::Enum(_)
+            | &IdentType::EnumValue(_, _)
+            | &IdentType::Module(_) => {
                 unreachable!();
             }
 
@@ -1353,6 +1745,111 @@ where
         }
     }
 
+    fn emit_assign_struct_field(
+        &mut self,
+        e: &'ast ExprBinType,
+        ty: BuiltinType,
+        field_id: StructFieldId,
+    ) {
+        let field = self.struct_field(ty, field_id);
+        let (root, offset) = self.struct_field_storage(&e.lhs);
+        let offset = offset + field.offset;
+        let res = result_reg_ty(field.ty);
+
+        match root {
+            StructStorage::Var(var_id) => {
+                self.emit_expr(&e.rhs, res);
+
+                self.asm.emit_comment(Comment::StoreVar(var_id));
+                let dest = Mem::Local(self.var_offset(var_id) + offset);
+
+                if field.ty.is_struct() {
+                    self.asm
+                        .copy_struct(field.ty, dest, Mem::Base(res.reg(), 0));
+                } else {
+                    self.asm.store_mem(field.ty.mode(), dest, res);
+                }
+            }
+
+            StructStorage::Global(global_id) => {
+                self.emit_expr(&e.rhs, res);
+
+                let address_value = self.vm.globals.idx(global_id).lock().address_value;
+                let disp = self.asm.add_addr(address_value.to_ptr());
+                let pos = self.asm.pos() as i32;
+
+                self.asm.emit_comment(Comment::StoreGlobal(global_id));
+                self.asm.load_constpool(REG_TMP1, disp + pos);
+                let dest = Mem::Base(REG_TMP1, offset);
+
+                if field.ty.is_struct() {
+                    self.asm
+                        .copy_struct(field.ty, dest, Mem::Base(res.reg(), 0));
+                } else {
+                    self.asm.store_mem(field.ty.mode(), dest, res);
+                }
+            }
+
+            StructStorage::Object(object) => {
+                if let Some(object) = object {
+                    self.emit_expr(object, REG_RESULT.into());
+                } else {
+                    self.emit_self(REG_RESULT.into());
+                }
+
+                // keep object alive while evaluating rhs
+                let temp_offset = self.jit_info.get_store(e.lhs.id()).offset();
+                self.stack.add_temp(BuiltinType::Ptr, temp_offset, self.vm);
+                let slot = self.managed_stack.add_temp(BuiltinType::Ptr, self.vm);
+                self.asm
+                    .store_mem(MachineMode::Ptr, Mem::Local(temp_offset), REG_RESULT.into());
+
+                self.emit_expr(&e.rhs, res);
+                self.asm
+                    .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(temp_offset));
+                self.store_field_value(e.pos, field.ty, REG_TMP1, offset, res);
+
+                self.stack.free_temp(BuiltinType::Ptr, temp_offset, self.vm);
+                self.managed_stack.free_temp(slot, self.vm);
+            }
+        }
+    }
+
+    // determines where the struct value of `e` is stored and the offset of
+    // the value within this storage
+    fn struct_field_storage(&self, e: &'ast Expr) -> (StructStorage<'ast>, i32) {
+        let dot = e.to_dot().unwrap();
+        let mut expr: &'ast Expr = &dot.lhs;
+        let mut offset = 0;
+
+        loop {
+            match *self.src.map_idents.get(expr.id()).unwrap() {
+                IdentType::Var(var_id) => return (StructStorage::Var(var_id), offset),
+                IdentType::Global(global_id) => return (StructStorage::Global(global_id), offset),
+
+                IdentType::Field(ty, field_id) => {
+                    let ty = self.specialize_type(ty);
+                    let cls_id = specialize_class_ty(self.vm, ty);
+                    let cls = self.vm.class_defs.idx(cls_id);
+                    let cls = cls.read();
+                    let object = expr.to_dot().map(|dot| &*dot.lhs);
+
+                    return (
+                        StructStorage::Object(object),
+                        offset + cls.fields[field_id.idx()].offset,
+                    );
+                }
+
+                IdentType::StructField(ty, field_id) => {
+                    offset += self.struct_field(ty, field_id).offset;
+                    expr = &expr.to_dot().unwrap().lhs;
+                }
+
+                _ => unreachable!(),
+            }
+        }
+    }
+
     fn emit_bin(&mut self, e: &'ast ExprBinType, dest: ExprStore) {
         if e.op.is_any_assign() {
             self.emit_assign(e);
@@ -1527,17 +2024,23 @@ where
         let ident_type = self.src.map_idents.get(e.id).unwrap();
 
         match ident_type {
+            &IdentType::EnumValue(enum_id, value) if self.ty(e.id).is_enum_object() => {
+                self.emit_enum_value(e.id, e.pos, &[], enum_id, value, dest);
+            }
+
             &IdentType::EnumValue(_, value) => {
                 self.asm
                     .load_int_const(MachineMode::Int32, dest.reg(), value as i64);
             }
 
-            _ => unreachable!(),
+            _ => self.emit_ident_type(e.id, e.pos, dest),
         }
     }
 
     fn emit_call(&mut self, e: &'ast ExprCallType, dest: ExprStore) {
-        if let Some(intrinsic) = self.intrinsic(e.id) {
+        if let CallType::EnumValue(enum_id, value) = **self.src.map_calls.get(e.id).unwrap() {
+            self.emit_enum_value(e.id, e.pos, &e.args, enum_id, value, dest);
+        } else if let Some(intrinsic) = self.intrinsic(e.id) {
             let mut args: Vec<&'ast Expr> = Vec::with_capacity(3);
             let call_type = self.src.map_calls.get(e.id).unwrap();
 
@@ -1557,6 +2060,72 @@ where
         }
     }
 
+    fn emit_enum_value(
+        &mut self,
+        id: NodeId,
+        pos: Position,
+        args: &'ast [Box<Expr>],
+        enum_id: EnumId,
+        value: u32,
+        dest: ExprStore,
+    ) {
+        let cls_id = specialize_enum_value(self.vm, enum_id, value);
+        let offset = *self.jit_info.map_offsets.get(id).unwrap();
+
+        // evaluate payload first, the allocation must not be interrupted
+        let mut temps = Vec::with_capacity(args.len());
+
+        for arg in args {
+            let ty = self.ty(arg.id());
+            let reg = result_reg_ty(ty);
+            self.emit_expr(arg, reg);
+
+            if ty.is_unit() {
+                temps.push(None);
+                continue;
+            }
+
+            let (slot, temp_offset) = self.add_temp_node(arg);
+            self.asm.var_store(temp_offset, ty, reg);
+            temps.push(Some((slot, temp_offset)));
+        }
+
+        self.emit_allocation(pos, &[], cls_id, offset, REG_RESULT);
+
+        self.asm
+            .load_int_const(MachineMode::Int32, REG_TMP1, value as i64);
+        self.asm.store_mem(
+            MachineMode::Int32,
+            Mem::Base(REG_RESULT, enum_tag_offset()),
+            REG_TMP1.into(),
+        );
+
+        let cls = self.vm.class_defs.idx(cls_id);
+        let cls = cls.read();
+
+        for (temp, field) in temps.iter().zip(&cls.fields) {
+            let temp_offset = match *temp {
+                Some((_, temp_offset)) => temp_offset,
+                None => continue,
+            };
+
+            let reg = result_reg_ty(field.ty);
+            self.asm.var_load(temp_offset, field.ty, reg);
+            self.asm
+                .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(offset));
+            self.store_field_value(pos, field.ty, REG_TMP1, field.offset, reg);
+        }
+
+        for (arg, temp) in args.iter().zip(temps).rev() {
+            if let Some((slot, temp_offset)) = temp {
+                self.free_temp_node(arg, slot, temp_offset);
+            }
+        }
+
+        self.asm
+            .load_mem(MachineMode::Ptr, dest, Mem::Local(offset));
+    }
+
     fn emit_call_intrinsic(
         &mut self,
         id: NodeId,
@@ -1569,7 +2138,12 @@ where
             Intrinsic::GenericArrayLen => self.emit_intrinsic_len(pos, args[0], dest.reg()),
             Intrinsic::GenericArrayGet => {
                 let element_type = self.ty(args[0].id()).type_params(self.vm)[0];
-                self.emit_array_get(pos, element_type.mode(), args[0], args[1], dest)
+
+                if element_type.is_struct() {
+                    self.emit_struct_array_get(pos, element_type, args[0], args[1], dest)
+                } else {
+                    self.emit_array_get(pos, element_type.mode(), args[0], args[1], dest)
+                }
             }
             Intrinsic::GenericArraySet => {
                 let element_type = self.ty(args[0].id()).type_params(self.vm)[0];
@@ -1784,7 +2358,16 @@ where
 
         self.emit_expr(rhs, res);
         let (slot_value, offset_value) = self.add_temp_node(rhs);
-        self.asm.store_mem(mode, Mem::Local(offset_value), res);
+
+        if element_type.is_struct() {
+            self.asm.copy_struct(
+                element_type,
+                Mem::Local(offset_value),
+                Mem::Base(res.reg(), 0),
+            );
+        } else {
+            self.asm.store_mem(mode, Mem::Local(offset_value), res);
+        }
 
         self.asm
             .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(offset_object));
@@ -1800,19 +2383,37 @@ where
             self.asm.check_index_out_of_bounds(pos, REG_TMP1, REG_TMP2);
         }
 
-        self.asm.load_mem(mode, res, Mem::Local(offset_value));
-
-        let write_barrier = self.vm.gc.needs_write_barrier() && element_type.reference_type();
         let card_table_offset = self.vm.gc.card_table_offset();
 
-        self.asm.store_array_elem(
-            mode,
-            REG_TMP1,
-            REG_TMP2,
-            res,
-            write_barrier,
-            card_table_offset,
-        );
+        if element_type.is_struct() {
+            self.emit_struct_array_elem_address(element_type, REG_TMP1, REG_TMP2);
+
+            // struct value was copied into temporary
+            self.asm.copy_struct(
+                element_type,
+                Mem::Base(REG_TMP1, offset_of_array_data()),
+                Mem::Local(offset_value),
+            );
+
+            if self.vm.gc.needs_write_barrier() {
+                self.asm
+                    .load_mem(MachineMode::Ptr, REG_TMP1.into(), Mem::Local(offset_object));
+                self.asm.emit_barrier(REG_TMP1, card_table_offset);
+            }
+        } else {
+            self.asm.load_mem(mode, res, Mem::Local(offset_value));
+
+            let write_barrier = self.vm.gc.needs_write_barrier() && element_type.reference_type();
+
+            self.asm.store_array_elem(
+                mode,
+                REG_TMP1,
+                REG_TMP2,
+                res,
+                write_barrier,
+                card_table_offset,
+            );
+        }
 
         self.free_temp_node(object, slot_object, offset_object);
         self.free_temp_node(index, slot_index, offset_index);
@@ -1854,6 +2455,58 @@ where
         }
     }
 
+    fn emit_struct_array_get(
+        &mut self,
+        pos: Position,
+        element_type: BuiltinType,
+        object: &'ast Expr,
+        index: &'ast Expr,
+        dest: ExprStore,
+    ) {
+        self.emit_expr(object, REG_RESULT.into());
+        let (slot, offset) = self.add_temp_node(object);
+        self.asm
+            .store_mem(MachineMode::Ptr, Mem::Local(offset), REG_RESULT.into());
+
+        self.emit_expr(index, REG_TMP1.into());
+        self.asm
+            .load_mem(MachineMode::Ptr, REG_RESULT.into(), Mem::Local(offset));
+
+        self.asm.test_if_nil_bailout(pos, REG_RESULT, Trap::NIL);
+
+        if !self.vm.args.flag_omit_bounds_check {
+            self.asm
+                .check_index_out_of_bounds(pos, REG_RESULT, REG_TMP1);
+        }
+
+        // address of element stays valid until next gc point
+        self.emit_struct_array_elem_address(element_type, REG_RESULT, REG_TMP1);
+        self.asm
+            .lea(dest.reg(), Mem::Base(REG_RESULT, offset_of_array_data()));
+
+        self.free_temp_node(object, slot, offset);
+    }
+
+    // computes `array + index * element_size` into `array`, clobbers `index`
+    fn emit_struct_array_elem_address(
+        &mut self,
+        element_type: BuiltinType,
+        array: Reg,
+        index: Reg,
+    ) {
+        let element_size = element_type.size(self.vm);
+        let scratch = if array == REG_TMP2 || index == REG_TMP2 {
+            REG_RESULT
+        } else {
+            REG_TMP2
+        };
+
+        self.asm
+            .load_int_const(MachineMode::Int64, scratch, element_size as i64);
+        self.asm.int_mul(MachineMode::Int64, index, index, scratch);
+        self.asm.int_add(MachineMode::Ptr, array, array, index);
+    }
+
     fn emit_intrinsic_is_nan(&mut self, e: &'ast Expr, dest: Reg, intrinsic: Intrinsic) {
         self.emit_expr(e, FREG_RESULT.into());
 
@@ -2226,9 +2879,8 @@ where
             Option<ClassDefId>,
         )> = Vec::new();
 
-        let fid = csite.callee;
-        let fct = self.vm.fcts.idx(fid);
-        let fct = fct.read();
+        let fct = csite.callee.map(|fid| self.vm.fcts.idx(fid));
+        let fct = fct.as_ref().map(|fct| fct.read());
 
         for (idx, arg) in csite.args.iter().enumerate() {
             let mode = arg.ty().mode();
@@ -2244,17 +2896,18 @@ where
                     //   super calls (guaranteed to not be nil) and
                     //   dynamic dispatch (implicit check when loading fctptr from vtable)
                     if idx == 0
-                        && fct.has_self()
+                        && fct
+                            .as_ref()
+                            .map_or(false, |fct| fct.has_self() && !fct.is_virtual())
                         && check_for_nil(ty)
                         && !csite.super_call
-                        && !fct.is_virtual()
                     {
                         self.asm.test_if_nil_bailout(pos, dest.reg(), Trap::NIL);
                     }
                 }
 
                 Arg::Stack(soffset, ty, _) => {
-                    self.asm.load_mem(ty.mode(), dest, Mem::Local(soffset));
+                    self.asm.var_load(soffset, ty, dest);
                 }
 
                 Arg::Selfie(_, _) => {
@@ -2273,8 +2926,7 @@ where
             }
 
             let (slot, offset) = self.add_temp_arg(arg);
-            self.asm
-                .store_mem(arg.ty().mode(), Mem::Local(offset), dest);
+            self.asm.var_store(offset, arg.ty(), dest);
             temps.push((arg.ty(), Some(slot), offset, None));
         }
 
@@ -2299,7 +2951,7 @@ where
                     // after the allocation `offset` is initialized,
                     // add it to the set of temporaries such that it is part
                     // of the gc point
-                    self.stack.add_temp(BuiltinType::Ptr, offset);
+                    self.stack.add_temp(BuiltinType::Ptr, offset, self.vm);
                     let slot = self.managed_stack.add_temp(BuiltinType::Ptr, self.vm);
                     temps[idx].1 = Some(slot);
 
@@ -2324,13 +2976,14 @@ where
                     sp_offset += 8;
                 }
             } else {
+                // structs are passed by address
                 if reg_idx < REG_PARAMS.len() {
                     let reg = REG_PARAMS[reg_idx];
-                    self.asm.load_mem(mode, reg.into(), Mem::Local(offset));
+                    self.asm.var_load(offset, ty, reg.into());
 
                     reg_idx += 1;
                 } else {
-                    self.asm.load_mem(mode, REG_TMP1.into(), Mem::Local(offset));
+                    self.asm.var_load(offset, ty, REG_TMP1.into());
                     self.asm
                         .store_mem(mode, Mem::Base(REG_SP, sp_offset), REG_TMP1.into());
 
@@ -2341,6 +2994,20 @@ where
             idx += 1;
         }
 
+        // pass address of storage for struct return value as last argument
+        if let Some(return_slot) = csite.return_slot {
+            if reg_idx < REG_PARAMS.len() {
+                self.asm.lea(REG_PARAMS[reg_idx], Mem::Local(return_slot));
+            } else {
+                self.asm.lea(REG_TMP1, Mem::Local(return_slot));
+                self.asm.store_mem(
+                    MachineMode::Ptr,
+                    Mem::Base(REG_SP, sp_offset),
+                    REG_TMP1.into(),
+                );
+            }
+        }
+
         let return_type = self.specialize_type(csite.return_type);
         let cls_type_params = TypeList::with(
             csite
@@ -2364,7 +3031,14 @@ where
             .iter()
             .all(|ty| !ty.contains_type_param(self.vm)));
 
-        if csite.super_call {
+        if fct.is_none() {
+            // closure object stores code of lambda in the first vtable entry
+            self.asm.emit_comment(Comment::CallClosure);
+            let gcpoint = self.stack.gcpoint();
+            self.asm
+                .indirect_call(0, pos, gcpoint, return_type, TypeList::empty(), dest);
+        } else if csite.super_call {
+            let fid = csite.callee.unwrap();
             let ptr = self.ptr_for_fct_id(fid, cls_type_params.clone(), fct_type_params.clone());
             self.asm.emit_comment(Comment::CallSuper(fid));
             let gcpoint = self.stack.gcpoint();
@@ -2378,8 +3052,9 @@ where
                 return_type,
                 dest,
             );
-        } else if fct.is_virtual() {
-            let vtable_index = fct.vtable_index.unwrap();
+        } else if fct.as_ref().unwrap().is_virtual() {
+            let fid = csite.callee.unwrap();
+            let vtable_index = fct.as_ref().unwrap().vtable_index.unwrap();
             self.asm.emit_comment(Comment::CallVirtual(fid));
             let gcpoint = self.stack.gcpoint();
             let cls_type_params = temps[0].0.type_params(self.vm);
@@ -2392,6 +3067,7 @@ where
                 dest,
             );
         } else {
+            let fid = csite.callee.unwrap();
             let ptr = self.ptr_for_fct_id(fid, cls_type_params.clone(), fct_type_params.clone());
             self.asm.emit_comment(Comment::CallDirect(fid));
             let gcpoint = self.stack.gcpoint();
@@ -2415,7 +3091,7 @@ where
         }
 
         for temp in temps.into_iter() {
-            self.stack.free_temp(temp.0, temp.2);
+            self.stack.free_temp(temp.0, temp.2, self.vm);
 
             if let Some(slot) = temp.1 {
                 self.managed_stack.free_temp(slot, self.vm);
@@ -2454,7 +3130,7 @@ where
                 alloc_size = AllocationSize::Fixed(size as usize);
             }
 
-            InstanceSize::Array(esize) if temps.len() > 1 => {
+            InstanceSize::Array(esize) | InstanceSize::StructArray(esize) if temps.len() > 1 => {
                 self.asm
                     .load_mem(MachineMode::Int32, REG_TMP1.into(), Mem::Local(temps[1].2));
 
@@ -2487,7 +3163,10 @@ where
                 alloc_size = AllocationSize::Dynamic(REG_PARAMS[0]);
             }
 
-            InstanceSize::Array(_) | InstanceSize::ObjArray | InstanceSize::Str => {
+            InstanceSize::Array(_)
+            | InstanceSize::StructArray(_)
+            | InstanceSize::ObjArray
+            | InstanceSize::Str => {
                 let size = Header::size() as usize + mem::ptr_width_usize();
                 self.asm
                     .load_int_const(MachineMode::Int32, REG_PARAMS[0], size as i64);
@@ -2562,7 +3241,7 @@ where
                 );
 
                 let element_size = match cls.size {
-                    InstanceSize::Array(esize) => esize,
+                    InstanceSize::Array(esize) | InstanceSize::StructArray(esize) => esize,
                     InstanceSize::ObjArray => mem::ptr_width(),
                     InstanceSize::Str => 1,
                     InstanceSize::Fixed(_) => unreachable!(),
@@ -2586,12 +3265,12 @@ where
     fn specialize_type(&self, ty: BuiltinType) -> BuiltinType {
         match ty {
             BuiltinType::ClassTypeParam(cls_id, id) => {
-                assert!(self.fct.parent == FctParent::Class(cls_id));
+                assert!(self.fct.is_lambda() || self.fct.parent == FctParent::Class(cls_id));
                 self.cls_type_params[id.idx()]
             }
 
             BuiltinType::FctTypeParam(fct_id, id) => {
-                assert!(self.fct.id == fct_id);
+                assert!(self.fct.is_lambda() || self.fct.id == fct_id);
                 self.fct_type_params[id.idx()]
             }
 
@@ -2605,7 +3284,9 @@ where
                 BuiltinType::Class(cls_id, list_id)
             }
 
-            BuiltinType::Lambda(_) => unimplemented!(),
+            BuiltinType::Lambda(_) => {
+                specialize_type(self.vm, ty, self.cls_type_params, self.fct_type_params)
+            }
 
             _ => ty,
         }
@@ -2628,6 +3309,10 @@ impl<'a, 'ast> CodeGen<'ast> for AstCodeGen<'a, 'ast> {
         self.emit_prolog();
         self.store_register_params_on_stack();
 
+        if self.fct.is_lambda() {
+            self.load_captured_vars();
+        }
+
         let always_returns = self.src.always_returns;
 
         {
@@ -2642,6 +3327,10 @@ impl<'a, 'ast> CodeGen<'ast> for AstCodeGen<'a, 'ast> {
                 let reg = result_reg_ty(return_type);
                 self.emit_expr(value, reg);
 
+                if return_type.is_struct() {
+                    self.emit_struct_return();
+                }
+
                 if !always_returns {
                     self.emit_epilog();
                 }
@@ -2649,7 +3338,7 @@ impl<'a, 'ast> CodeGen<'ast> for AstCodeGen<'a, 'ast> {
         }
 
         self.managed_stack.pop_scope(self.vm);
-        self.stack.pop_scope();
+        self.stack.pop_scope(self.vm);
         assert!(self.stack.is_empty());
         assert!(self.managed_stack.is_empty());
 
@@ -2692,6 +3381,14 @@ impl<'a, 'ast> visit::Visitor<'ast> for AstCodeGen<'a, 'ast> {
     }
 }
 
+enum StructStorage<'ast> {
+    Var(VarId),
+    Global(GlobalId),
+
+    // field of object, `None` for self
+    Object(Option<&'ast Expr>),
+}
+
 fn result_reg(mode: MachineMode) -> ExprStore {
     if mode.is_float() {
         FREG_RESULT.into()
@@ -2721,7 +3418,7 @@ fn check_for_nil(ty: BuiltinType) -> bool {
         | BuiltinType::Bool
         | BuiltinType::Enum(_) => false,
         BuiltinType::Nil | BuiltinType::Ptr => true,
-        BuiltinType::Class(_, _) => true,
+        BuiltinType::Class(_, _) | BuiltinType::EnumObject(_) => true,
         BuiltinType::Struct(_, _) => false,
         BuiltinType::Trait(_) => false,
         BuiltinType::This => unreachable!(),
@@ -9,10 +9,10 @@ use dora_parser::ast::*;
 use crate::cpu::*;
 use crate::mem;
 use crate::semck::specialize::{specialize_for_call_type, specialize_type};
-use crate::ty::{BuiltinType, TypeList, TypeParamId};
+use crate::ty::{BuiltinType, LambdaId, TypeList, TypeParamId};
 use crate::vm::{
-    Arg, CallSite, CallType, Fct, FctId, FctKind, FctParent, FctSrc, Intrinsic, NodeMap, Store,
-    TraitId, VarId, VM,
+    Arg, CallSite, CallType, Fct, FctId, FctKind, FctParent, FctSrc, IdentType, Intrinsic, NodeMap,
+    Store, TraitId, VarId, VM,
 };
 
 pub fn generate<'a, 'ast: 'a>(
@@ -25,7 +25,17 @@ pub fn generate<'a, 'ast: 'a>(
 ) {
     let start = if fct.has_self() { 1 } else { 0 };
 
-    if let FctParent::Class(cls_id) = fct.parent {
+    // lambdas are compiled with the type params of their enclosing function
+    let owner_id = vm.lambda_owner(fct);
+    let (parent, type_params_len) = if owner_id == fct.id {
+        (fct.parent.clone(), fct.type_params.len())
+    } else {
+        let owner = vm.fcts.idx(owner_id);
+        let owner = owner.read();
+        (owner.parent.clone(), owner.type_params.len())
+    };
+
+    if let FctParent::Class(cls_id) = parent {
         let cls = vm.classes.idx(cls_id);
         let cls = cls.read();
         assert_eq!(cls_type_params.len(), cls.type_params.len());
@@ -33,7 +43,7 @@ pub fn generate<'a, 'ast: 'a>(
         assert_eq!(cls_type_params.len(), 0);
     }
 
-    assert_eq!(fct.type_params.len(), fct_type_params.len());
+    assert_eq!(type_params_len, fct_type_params.len());
 
     for ty in cls_type_params.iter() {
         assert!(ty.is_concrete_type(vm));
@@ -68,15 +78,18 @@ pub fn generate<'a, 'ast: 'a>(
 }
 
 pub struct JitInfo<'ast> {
-    pub stacksize: i32,               // size of local variables on stack
-    pub leaf: bool,                   // false if fct calls other functions
-    pub eh_return_value: Option<i32>, // stack slot for return value storage
+    pub stacksize: i32,                   // size of local variables on stack
+    pub leaf: bool,                       // false if fct calls other functions
+    pub eh_return_value: Option<i32>,     // stack slot for return value storage
+    pub struct_return: Option<i32>,       // stack slot for address of struct return value
+    pub struct_return_param: Option<i32>, // stack offset of that address if not passed in register
 
     pub map_stores: NodeMap<Store>,
     pub map_csites: NodeMap<CallSite<'ast>>,
     pub map_offsets: NodeMap<i32>,
     pub map_var_offsets: HashMap<VarId, i32>,
     pub map_var_types: HashMap<VarId, BuiltinType>,
+    pub map_struct_params: HashMap<VarId, i32>,
     pub map_intrinsics: NodeMap<Intrinsic>,
     pub map_fors: NodeMap<ForInfo<'ast>>,
     pub map_templates: NodeMap<TemplateJitInfo<'ast>>,
@@ -113,12 +126,15 @@ impl<'ast> JitInfo<'ast> {
             stacksize: 0,
             leaf: false,
             eh_return_value: None,
+            struct_return: None,
+            struct_return_param: None,
 
             map_stores: NodeMap::new(),
             map_csites: NodeMap::new(),
             map_offsets: NodeMap::new(),
             map_var_offsets: HashMap::new(),
             map_var_types: HashMap::new(),
+            map_struct_params: HashMap::new(),
             map_intrinsics: NodeMap::new(),
             map_fors: NodeMap::new(),
             map_templates: NodeMap::new(),
@@ -165,6 +181,16 @@ impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
             self.reserve_stack_for_var(var);
             self.param_reg_idx += 1;
 
+        // structs are passed by address, their value is copied
+        // into a local variable
+        } else if ty.is_struct() {
+            self.jit_info
+                .map_struct_params
+                .insert(var, self.param_offset);
+            self.reserve_stack_for_var(var);
+
+            self.param_offset = next_param_offset(self.param_offset, ty);
+
         // the rest of the parameters are already stored on the stack
         // just use the current offset
         } else {
@@ -208,6 +234,11 @@ impl<'a, 'ast> Visitor<'ast> for InfoGenerator<'a, 'ast> {
             ExprConv(ref expr) => self.expr_conv(expr),
             ExprTypeParam(_) => unreachable!(),
             ExprTemplate(ref expr) => self.expr_template(expr),
+            ExprLambda(ref expr) => self.expr_lambda(expr),
+            ExprLitStruct(ref expr) => self.expr_lit_struct(expr),
+            ExprIdent(ref expr) => self.expr_ident(expr),
+            ExprMatch(ref expr) => self.expr_match(expr),
+            ExprPath(ref expr) => self.expr_path(expr),
 
             _ => visit::walk_expr(self, e),
         }
@@ -220,8 +251,23 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
             self.reserve_stack_for_self();
         }
 
+        for capture in &self.src.captures {
+            self.reserve_stack_for_var(capture.inner);
+        }
+
         self.visit_fct(self.ast);
 
+        // address of struct return value is passed after all other parameters
+        let return_type = self.specialize_type(self.fct.return_type);
+
+        if return_type.is_struct() {
+            self.jit_info.struct_return = Some(self.reserve_stack_slot(BuiltinType::Ptr));
+
+            if self.param_reg_idx >= REG_PARAMS.len() {
+                self.jit_info.struct_return_param = Some(self.param_offset);
+            }
+        }
+
         self.jit_info.stacksize = mem::align_i32(self.stacksize, 16);
         self.jit_info.leaf = self.leaf;
         self.jit_info.eh_return_value = self.eh_return_value;
@@ -312,6 +358,8 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
                 cls.ty
             }
 
+            FctParent::Function(_) => BuiltinType::Ptr,
+
             _ => unreachable!(),
         };
 
@@ -319,6 +367,7 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
 
         let id = self.src.var_self().id;
         self.jit_info.map_var_offsets.insert(id, offset);
+        self.jit_info.map_var_types.insert(id, ty);
     }
 
     fn reserve_stack_for_var(&mut self, id: VarId) -> i32 {
@@ -364,7 +413,135 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
         }
     }
 
+    fn expr_lit_struct(&mut self, expr: &'ast ExprLitStructType) {
+        // struct value is built in temporary
+        self.reserve_temp_for_node_id(expr.id);
+
+        for arg in &expr.args {
+            self.visit_expr(&arg.expr);
+        }
+    }
+
+    fn expr_ident(&mut self, expr: &'ast ExprIdentType) {
+        if let Some(&IdentType::Struct(_)) = self.src.map_idents.get(expr.id) {
+            self.reserve_temp_for_node_id(expr.id);
+        }
+    }
+
+    fn expr_lambda(&mut self, expr: &'ast ExprLambdaType) {
+        // body of lambda is compiled separately, only reserve
+        // slot for closure object
+        let offset = self.reserve_stack_slot(BuiltinType::Ptr);
+        self.jit_info.map_offsets.insert(expr.id, offset);
+        self.leaf = false;
+    }
+
+    fn expr_call_enum_value(&mut self, expr: &'ast ExprCallType) {
+        // arguments are evaluated into temporaries before the allocation
+        for arg in &expr.args {
+            self.visit_expr(arg);
+            self.reserve_temp_for_node(arg);
+        }
+
+        let offset = self.reserve_stack_slot(BuiltinType::Ptr);
+        self.jit_info.map_offsets.insert(expr.id, offset);
+        self.leaf = false;
+    }
+
+    fn expr_path(&mut self, expr: &'ast ExprPathType) {
+        // values of enums with payload data are allocated on the heap
+        if self.ty(expr.id).is_enum_object() {
+            let offset = self.reserve_stack_slot(BuiltinType::Ptr);
+            self.jit_info.map_offsets.insert(expr.id, offset);
+            self.leaf = false;
+        }
+
+        // struct of another module
+        if let Some(&IdentType::Struct(_)) = self.src.map_idents.get(expr.id) {
+            self.reserve_temp_for_node_id(expr.id);
+        }
+    }
+
+    fn expr_match(&mut self, expr: &'ast ExprMatchType) {
+        self.visit_expr(&expr.expr);
+
+        if self.ty(expr.expr.id()).is_enum_object() {
+            // keep matched object alive while payload is copied into bindings
+            self.reserve_temp_for_node(&expr.expr);
+
+            for case in &expr.cases {
+                for pattern in &case.patterns {
+                    if let MatchPatternData::Ident(ref ident) = pattern.data {
+                        for param in ident.params.iter().flatten() {
+                            if let Some(&var_id) = self.src.map_vars.get(param.id) {
+                                self.reserve_stack_for_var(var_id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for case in &expr.cases {
+            self.visit_expr(&case.value);
+        }
+    }
+
+    fn expr_call_lambda(&mut self, expr: &'ast ExprCallType, lambda_id: LambdaId) {
+        // function invokes another function
+        self.leaf = false;
+
+        let lambda = self.vm.lambda_types.lock().get(lambda_id);
+
+        // closure object is passed as first argument
+        let closure_type = self.specialize_type(BuiltinType::Lambda(lambda_id));
+        let mut args = vec![(&*expr.callee, closure_type)];
+
+        for (arg, &ty) in expr.args.iter().zip(&lambda.params) {
+            args.push((&**arg, self.specialize_type(ty)));
+        }
+
+        let args = args
+            .into_iter()
+            .map(|(arg, ty)| {
+                let offset = self.reserve_stack_slot(ty);
+                Arg::Expr(arg, ty, offset)
+            })
+            .collect::<Vec<_>>();
+
+        let return_type = self.specialize_type(lambda.ret);
+        let argsize = self.determine_call_stack(&args, return_type);
+        let return_slot = self.reserve_return_slot(return_type);
+
+        let csite = CallSite {
+            callee: None,
+            args,
+            argsize,
+            cls_type_params: TypeList::empty(),
+            fct_type_params: TypeList::empty(),
+            super_call: false,
+            return_type,
+            return_slot,
+        };
+
+        self.jit_info.map_csites.insert(expr.id, csite);
+    }
+
     fn expr_call(&mut self, expr: &'ast ExprCallType) {
+        match **self.src.map_calls.get(expr.id).unwrap() {
+            CallType::Lambda(lambda_id) => {
+                self.expr_call_lambda(expr, lambda_id);
+                return;
+            }
+
+            CallType::EnumValue(_, _) => {
+                self.expr_call_enum_value(expr);
+                return;
+            }
+
+            _ => {}
+        }
+
         if let Some(intrinsic) = self.get_intrinsic(expr.id) {
             self.reserve_args_call(expr);
             self.jit_info.map_intrinsics.insert(expr.id, intrinsic);
@@ -466,7 +643,9 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
             }
 
             CallType::Trait(_, _) => unimplemented!(),
-            CallType::Intrinsic(_) => unreachable!(),
+            CallType::Intrinsic(_) | CallType::Lambda(_) | CallType::EnumValue(_, _) => {
+                unreachable!()
+            }
         }
 
         let fct = self.vm.fcts.idx(fct_id);
@@ -585,16 +764,26 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
             self.determine_call_args_and_types(&*call_type, &*callee, args);
         let (cls_type_params, fct_type_params) = self.determine_call_type_params(&*call_type);
 
-        let argsize = self.determine_call_stack(&args);
+        let argsize = self.determine_call_stack(&args, return_type);
+        let return_slot = self.reserve_return_slot(return_type);
 
         CallSite {
-            callee: callee_id,
+            callee: Some(callee_id),
             args,
             argsize,
             cls_type_params,
             fct_type_params,
             super_call,
             return_type,
+            return_slot,
+        }
+    }
+
+    fn reserve_return_slot(&mut self, return_type: BuiltinType) -> Option<i32> {
+        if return_type.is_struct() {
+            Some(self.reserve_stack_slot(return_type))
+        } else {
+            None
         }
     }
 
@@ -677,16 +866,23 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
                 fct_type_params = TypeList::empty();
             }
 
-            CallType::Intrinsic(_) => unreachable!(),
+            CallType::Intrinsic(_) | CallType::Lambda(_) | CallType::EnumValue(_, _) => {
+                unreachable!()
+            }
         }
 
         (cls_type_params, fct_type_params)
     }
 
-    fn determine_call_stack(&mut self, args: &[Arg<'ast>]) -> i32 {
+    fn determine_call_stack(&mut self, args: &[Arg<'ast>], return_type: BuiltinType) -> i32 {
         let mut reg_args: i32 = 0;
         let mut freg_args: i32 = 0;
 
+        // address for struct return value
+        if return_type.is_struct() {
+            reg_args += 1;
+        }
+
         for arg in args {
             match *arg {
                 Arg::Expr(ast, ty, _) => {
@@ -765,6 +961,11 @@ impl<'a, 'ast> InfoGenerator<'a, 'ast> {
 
             self.reserve_temp_for_node(&lhs.lhs);
             self.reserve_temp_for_node(&e.rhs);
+
+            // object containing the assigned struct field
+            if let Some(&IdentType::StructField(_, _)) = self.src.map_idents.get(lhs.id) {
+                self.reserve_temp_for_node_with_type(lhs.id, BuiltinType::Ptr);
+            }
         }
     }
 
@@ -1,17 +1,16 @@
 use crate::baseline::asm::BaselineAssembler;
-use crate::baseline::codegen::fct_pattern_match;
 use crate::bytecode::generate::BytecodeIdx;
-use crate::class::ClassDefId;
+use crate::class::{ClassDef, ClassDefId};
 use crate::cpu::{Mem, FREG_PARAMS, FREG_RESULT, FREG_TMP1, REG_PARAMS, REG_RESULT, REG_TMP1};
 use crate::field::FieldId;
 use dora_parser::ast::*;
 use std::collections::hash_map::HashMap;
 
-use crate::baseline::codegen::{should_emit_debug, CodeGen, CondCode};
-use crate::baseline::fct::{Comment, JitBaselineFct, JitDescriptor};
+use crate::baseline::codegen::{should_emit_bytecode, should_emit_debug, CodeGen, CondCode};
+use crate::baseline::fct::{CatchType, Comment, JitBaselineFct, JitDescriptor};
 use crate::masm::*;
 use crate::object::Str;
-use crate::ty::TypeList;
+use crate::ty::{MachineMode, TypeList};
 use crate::vm::VM;
 use crate::vm::{Fct, FctSrc, GlobalId};
 
@@ -749,6 +748,33 @@ where
             .store_mem(bytecode_type.mode(), Mem::Local(offset), REG_RESULT.into());
     }
 
+    fn emit_test_float(
+        &mut self,
+        bytecode: &BytecodeFunction,
+        dest: Register,
+        lhs: Register,
+        rhs: Register,
+        op: CondCode,
+    ) {
+        assert_eq!(bytecode.register(lhs), bytecode.register(rhs));
+        assert_eq!(bytecode.register(dest), BytecodeType::Bool);
+
+        let bytecode_type = bytecode.register(lhs);
+        let offset = bytecode.offset(lhs);
+        self.asm
+            .load_mem(bytecode_type.mode(), FREG_RESULT.into(), Mem::Local(offset));
+        let offset = bytecode.offset(rhs);
+        self.asm
+            .load_mem(bytecode_type.mode(), FREG_TMP1.into(), Mem::Local(offset));
+
+        self.asm
+            .float_cmp(bytecode_type.mode(), REG_RESULT, FREG_RESULT, FREG_TMP1, op);
+
+        let offset = bytecode.offset(dest);
+        self.asm
+            .store_mem(MachineMode::Int8, Mem::Local(offset), REG_RESULT.into());
+    }
+
     fn emit_jump_if(
         &mut self,
         bytecode: &BytecodeFunction,
@@ -781,6 +807,31 @@ where
         self.resolve_label(bytecode_idx, lbl);
     }
 
+    fn emit_jump_table(
+        &mut self,
+        bytecode: &BytecodeFunction,
+        opnd: Register,
+        targets: &[BytecodeIdx],
+    ) {
+        assert_eq!(bytecode.register(opnd), BytecodeType::Int);
+
+        let bytecode_type = bytecode.register(opnd);
+        let offset = bytecode.offset(opnd);
+        self.asm
+            .load_mem(bytecode_type.mode(), REG_RESULT.into(), Mem::Local(offset));
+
+        let lbls = targets
+            .iter()
+            .map(|&target| {
+                let lbl = self.asm.create_label();
+                self.resolve_label(target, lbl);
+                lbl
+            })
+            .collect::<Vec<_>>();
+
+        self.asm.jump_table(REG_RESULT, &lbls);
+    }
+
     fn resolve_label(&mut self, bytecode_idx: BytecodeIdx, lbl: Label) {
         let BytecodeIdx(current_position) = self.pos();
         let BytecodeIdx(target) = bytecode_idx;
@@ -834,6 +885,34 @@ where
         }
     }
 
+    fn emit_exception_handlers(&mut self, bytecode: &BytecodeFunction) {
+        for handler in bytecode.exception_handlers() {
+            let address = |idx: BytecodeIdx| {
+                *self
+                    .bytecode_to_address
+                    .get(&idx)
+                    .expect("address for bytecode index not found")
+            };
+
+            let span = (address(handler.start), address(handler.end));
+            let catch = address(handler.catch);
+            let offset = handler.exception.map(|reg| bytecode.offset(reg));
+
+            let catch_type = match handler.catch_type {
+                Some(cls_def_id) => {
+                    let cls_def = self.vm.class_defs.idx(cls_def_id);
+                    let cls_def = cls_def.read();
+                    CatchType::Class(&*cls_def as *const ClassDef)
+                }
+
+                None => CatchType::Any,
+            };
+
+            self.asm
+                .emit_exception_handler(span, catch, offset, catch_type);
+        }
+    }
+
     fn pos(&self) -> BytecodeIdx {
         self.current_pos.expect("current position is not set")
     }
@@ -960,10 +1039,10 @@ impl<'a, 'ast> CodeGen<'ast> for CannonCodeGen<'a, 'ast> {
                     self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::NotEqual);
                 }
 
-                Bytecode::TestEqInt(dest, lhs, rhs) => {
+                Bytecode::TestEqInt(dest, lhs, rhs) | Bytecode::TestEqLong(dest, lhs, rhs) => {
                     self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::Equal)
                 }
-                Bytecode::TestNeInt(dest, lhs, rhs) => {
+                Bytecode::TestNeInt(dest, lhs, rhs) | Bytecode::TestNeLong(dest, lhs, rhs) => {
                     self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::NotEqual)
                 }
                 Bytecode::TestGtInt(dest, lhs, rhs) => {
@@ -979,6 +1058,47 @@ impl<'a, 'ast> CodeGen<'ast> for CannonCodeGen<'a, 'ast> {
                     self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::LessEq)
                 }
 
+                Bytecode::TestEqBool(dest, lhs, rhs)
+                | Bytecode::TestEqByte(dest, lhs, rhs)
+                | Bytecode::TestEqChar(dest, lhs, rhs) => {
+                    self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::Equal)
+                }
+                Bytecode::TestNeBool(dest, lhs, rhs)
+                | Bytecode::TestNeByte(dest, lhs, rhs)
+                | Bytecode::TestNeChar(dest, lhs, rhs) => {
+                    self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::NotEqual)
+                }
+                Bytecode::TestGtByte(dest, lhs, rhs) | Bytecode::TestGtChar(dest, lhs, rhs) => {
+                    self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::Greater)
+                }
+                Bytecode::TestGeByte(dest, lhs, rhs) | Bytecode::TestGeChar(dest, lhs, rhs) => {
+                    self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::GreaterEq)
+                }
+                Bytecode::TestLtByte(dest, lhs, rhs) | Bytecode::TestLtChar(dest, lhs, rhs) => {
+                    self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::Less)
+                }
+                Bytecode::TestLeByte(dest, lhs, rhs) | Bytecode::TestLeChar(dest, lhs, rhs) => {
+                    self.emit_test_generic(&bytecode, *dest, *lhs, *rhs, CondCode::LessEq)
+                }
+                Bytecode::TestEqFloat(dest, lhs, rhs) | Bytecode::TestEqDouble(dest, lhs, rhs) => {
+                    self.emit_test_float(&bytecode, *dest, *lhs, *rhs, CondCode::Equal)
+                }
+                Bytecode::TestNeFloat(dest, lhs, rhs) | Bytecode::TestNeDouble(dest, lhs, rhs) => {
+                    self.emit_test_float(&bytecode, *dest, *lhs, *rhs, CondCode::NotEqual)
+                }
+                Bytecode::TestGtFloat(dest, lhs, rhs) | Bytecode::TestGtDouble(dest, lhs, rhs) => {
+                    self.emit_test_float(&bytecode, *dest, *lhs, *rhs, CondCode::Greater)
+                }
+                Bytecode::TestGeFloat(dest, lhs, rhs) | Bytecode::TestGeDouble(dest, lhs, rhs) => {
+                    self.emit_test_float(&bytecode, *dest, *lhs, *rhs, CondCode::GreaterEq)
+                }
+                Bytecode::TestLtFloat(dest, lhs, rhs) | Bytecode::TestLtDouble(dest, lhs, rhs) => {
+                    self.emit_test_float(&bytecode, *dest, *lhs, *rhs, CondCode::Less)
+                }
+                Bytecode::TestLeFloat(dest, lhs, rhs) | Bytecode::TestLeDouble(dest, lhs, rhs) => {
+                    self.emit_test_float(&bytecode, *dest, *lhs, *rhs, CondCode::LessEq)
+                }
+
                 Bytecode::JumpIfFalse(src, bytecode_idx) => {
                     self.emit_jump_if(&bytecode, *src, *bytecode_idx, false)
                 }
@@ -986,6 +1106,9 @@ impl<'a, 'ast> CodeGen<'ast> for CannonCodeGen<'a, 'ast> {
                     self.emit_jump_if(&bytecode, *src, *bytecode_idx, true)
                 }
                 Bytecode::Jump(bytecode_idx) => self.emit_jump(&bytecode, *bytecode_idx),
+                Bytecode::JumpTable(opnd, targets) => {
+                    self.emit_jump_table(&bytecode, *opnd, targets)
+                }
 
                 Bytecode::RetBool(src)
                 | Bytecode::RetByte(src)
@@ -1002,6 +1125,7 @@ impl<'a, 'ast> CodeGen<'ast> for CannonCodeGen<'a, 'ast> {
         }
 
         self.resolve_forward_jumps();
+        self.emit_exception_handlers(&bytecode);
 
         let jit_fct = self.asm.jit(
             bytecode.stacksize(),
@@ -1012,11 +1136,3 @@ impl<'a, 'ast> CodeGen<'ast> for CannonCodeGen<'a, 'ast> {
         jit_fct
     }
 }
-
-fn should_emit_bytecode(vm: &VM, fct: &Fct) -> bool {
-    if let Some(ref dbg_names) = vm.args.flag_emit_bytecode {
-        fct_pattern_match(vm, fct, dbg_names)
-    } else {
-        false
-    }
-}
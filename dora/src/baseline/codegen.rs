@@ -12,9 +12,12 @@ use capstone::prelude::*;
 use crate::baseline::asm::BaselineAssembler;
 use crate::baseline::ast::{generate_info, AstCodeGen, JitInfo};
 use crate::baseline::cannon::CannonCodeGen;
+use crate::baseline::dora_interpreter;
 use crate::baseline::dora_native::{self, InternalFct};
-use crate::baseline::fct::{CommentFormat, GcPoint, JitBaselineFct, JitFct};
+use crate::baseline::fct::{CommentFormat, GcPoint, JitBaselineFct, JitDescriptor, JitFct};
 use crate::baseline::map::CodeDescriptor;
+use crate::bytecode::astgen;
+use crate::bytecode::interpreter::{self, InterpretedFct};
 use crate::cpu::x64::reg::{FREG_RESULT, REG_RESULT};
 use crate::cpu::{FReg, Reg};
 use crate::driver::cmd::{AsmSyntax, BaselineName};
@@ -74,6 +77,13 @@ pub fn generate_fct<'ast>(
         unimplemented!();
     }
 
+    // fall back to the compiler for functions the interpreter can't run
+    if let BaselineName::Interpreter = bc {
+        if !interpreter::is_supported(vm, fct, src, cls_type_params, fct_type_params) {
+            bc = BaselineName::AstCompiler;
+        }
+    }
+
     let jit_fct = match bc {
         BaselineName::Cannon => CannonCodeGen::new(
             vm,
@@ -126,6 +136,20 @@ pub fn generate_fct<'ast>(
             }
             .generate()
         }
+        BaselineName::Interpreter => {
+            let bytecode = astgen::generate_fct(vm, fct, src, cls_type_params, fct_type_params);
+
+            if should_emit_bytecode(vm, fct) {
+                bytecode.dump();
+            }
+
+            let idx = vm
+                .interpreted_fcts
+                .push(InterpretedFct::new(vm, fct, &bytecode));
+            let interpreted = vm.interpreted_fcts.idx_usize(idx);
+
+            dora_interpreter::generate(vm, fct, &interpreted, should_emit_debug(vm, fct))
+        }
     };
 
     if vm.args.flag_enable_perf {
@@ -145,6 +169,10 @@ pub fn generate_fct<'ast>(
     let fct_ptr = jit_fct.fct_ptr();
     let ptr_start = jit_fct.ptr_start();
     let ptr_end = jit_fct.ptr_end();
+    let interpreted = match jit_fct.desc {
+        JitDescriptor::InterpreterEntry(_) => true,
+        _ => false,
+    };
 
     debug_assert!(mem::is_aligned(ptr_start.to_usize(), 16));
     debug_assert!(mem::is_aligned(fct_ptr.to_usize(), 16));
@@ -165,7 +193,11 @@ pub fn generate_fct<'ast>(
 
     {
         let mut code_map = vm.code_map.lock();
-        let cdata = CodeDescriptor::DoraFct(jit_fct_id);
+        let cdata = if interpreted {
+            CodeDescriptor::InterpreterEntry(jit_fct_id)
+        } else {
+            CodeDescriptor::DoraFct(jit_fct_id)
+        };
         code_map.insert(ptr_start, ptr_end, cdata);
     }
 
@@ -339,43 +371,44 @@ impl StackFrame {
         self.scopes.push(StackScope::new());
     }
 
-    pub fn add_var(&mut self, ty: BuiltinType, offset: i32) {
-        if ty.reference_type() {
-            assert!(self.references.insert(offset));
-        }
-
+    pub fn add_var(&mut self, ty: BuiltinType, offset: i32, vm: &VM) {
+        self.add_references(ty, offset, vm);
         assert!(self.all.insert(offset));
 
         let scope = self.scopes.last_mut().expect("no active scope");
         scope.add_var(ty, offset);
     }
 
-    pub fn pop_scope(&mut self) {
+    pub fn pop_scope(&mut self, vm: &VM) {
         let scope = self.scopes.pop().expect("no active scope");
 
         for (offset, ty) in scope.vars.into_iter() {
-            if ty.reference_type() {
-                assert!(self.references.remove(&offset));
-            }
-
+            self.remove_references(ty, offset, vm);
             assert!(self.all.remove(&offset));
         }
     }
 
-    pub fn add_temp(&mut self, ty: BuiltinType, offset: i32) {
-        if ty.reference_type() {
-            assert!(self.references.insert(offset));
-        }
-
+    pub fn add_temp(&mut self, ty: BuiltinType, offset: i32, vm: &VM) {
+        self.add_references(ty, offset, vm);
         assert!(self.all.insert(offset));
     }
 
-    pub fn free_temp(&mut self, ty: BuiltinType, offset: i32) {
-        if ty.reference_type() {
-            assert!(self.references.remove(&offset));
+    pub fn free_temp(&mut self, ty: BuiltinType, offset: i32, vm: &VM) {
+        self.remove_references(ty, offset, vm);
+        assert!(self.all.remove(&offset));
+    }
+
+    // structs can embed multiple references
+    fn add_references(&mut self, ty: BuiltinType, offset: i32, vm: &VM) {
+        for ref_offset in ty.ref_offsets(vm) {
+            assert!(self.references.insert(offset + ref_offset));
         }
+    }
 
-        assert!(self.all.remove(&offset));
+    fn remove_references(&mut self, ty: BuiltinType, offset: i32, vm: &VM) {
+        for ref_offset in ty.ref_offsets(vm) {
+            assert!(self.references.remove(&(offset + ref_offset)));
+        }
     }
 
     pub fn gcpoint(&self) -> GcPoint {
@@ -702,6 +735,14 @@ pub fn should_emit_debug(vm: &VM, fct: &Fct) -> bool {
     }
 }
 
+pub fn should_emit_bytecode(vm: &VM, fct: &Fct) -> bool {
+    if let Some(ref dbg_names) = vm.args.flag_emit_bytecode {
+        fct_pattern_match(vm, fct, dbg_names)
+    } else {
+        false
+    }
+}
+
 pub fn should_emit_asm(vm: &VM, fct: &Fct) -> bool {
     if let Some(ref dbg_names) = vm.args.flag_emit_asm {
         fct_pattern_match(vm, fct, dbg_names)
@@ -219,6 +219,18 @@ fn patch_vtable_call(
 ) -> Address {
     let obj = unsafe { &mut *receiver.to_mut_ptr::<Obj>() };
     let vtable = obj.header().vtbl();
+
+    if let Some((fct_id, ref lambda_fct_tps)) = vtable.class().lambda {
+        // closure objects know the type params the lambda was created with
+        let lambda_cls_tps = vtable.class().type_params.clone();
+        let fct_ptr = baseline::generate(vm, fct_id, &lambda_cls_tps, lambda_fct_tps);
+
+        let methodtable = vtable.table_mut();
+        methodtable[vtable_index as usize] = fct_ptr.to_usize();
+
+        return fct_ptr;
+    }
+
     let cls_id = vtable.class().cls_id.expect("no corresponding class");
     let cls = vm.classes.idx(cls_id);
     let cls = cls.read();
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use crate::baseline::fct::{CatchType, JitBaselineFct, JitDescriptor, JitFct, JitFctId};
+use crate::baseline::map::CodeDescriptor;
+use crate::bytecode::generate::BytecodeType;
+use crate::bytecode::interpreter::{interpret, InterpretedFct};
+use crate::cpu::{
+    Mem, FREG_PARAMS, FREG_RESULT, REG_FP, REG_PARAMS, REG_RESULT, REG_SP, REG_THREAD, REG_TMP1,
+    REG_TMP2,
+};
+use crate::exception::DoraToNativeInfo;
+use crate::gc::Address;
+use crate::masm::MacroAssembler;
+use crate::mem;
+use crate::os::signal::Trap;
+use crate::threads::ThreadLocalData;
+use crate::ty::MachineMode;
+use crate::vm::{Fct, VM};
+
+// Stubs used by the interpreter to call compiled code, one stub per signature.
+pub struct InterpreterCallStubs {
+    map: HashMap<(Vec<BytecodeType>, Option<BytecodeType>), Address>,
+}
+
+impl InterpreterCallStubs {
+    pub fn new() -> InterpreterCallStubs {
+        InterpreterCallStubs {
+            map: HashMap::new(),
+        }
+    }
+}
+
+// Generates the entry of an interpreted function: compiled code calls it
+// like any other function, the arguments are stored on the stack and passed
+// to the interpreter together with the function.
+pub fn generate<'a, 'ast: 'a>(
+    vm: &'a VM<'ast>,
+    fct: &Fct<'ast>,
+    interpreted: &InterpretedFct,
+    dbg: bool,
+) -> JitBaselineFct {
+    let mut masm = MacroAssembler::new();
+    let params = interpreted.params();
+
+    let offset_dtn = 0;
+    let offset_args = offset_dtn + size_of::<DoraToNativeInfo>() as i32;
+    let framesize = mem::align_i32(offset_args + params.len().max(1) as i32 * 8, 16);
+
+    if dbg {
+        masm.debug();
+    }
+
+    masm.prolog_size(framesize);
+
+    let lbl_stack_overflow = masm.create_label();
+    masm.check_stack_pointer(lbl_stack_overflow);
+    masm.emit_bailout(lbl_stack_overflow, Trap::STACK_OVERFLOW, fct.pos);
+
+    let mut reg_idx = 0;
+    let mut freg_idx = 0;
+
+    for (idx, ty) in params.iter().enumerate() {
+        let mode = ty.mode();
+        let offset = offset_args + idx as i32 * 8;
+
+        if mode.is_float() {
+            let freg = FREG_PARAMS[freg_idx];
+            masm.store_mem(mode, Mem::Base(REG_SP, offset), freg.into());
+            freg_idx += 1;
+        } else {
+            let reg = REG_PARAMS[reg_idx];
+            masm.store_mem(mode, Mem::Base(REG_SP, offset), reg.into());
+            reg_idx += 1;
+        }
+    }
+
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_TMP1.into(),
+        Mem::Base(REG_THREAD, ThreadLocalData::dtn_offset()),
+    );
+
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_SP, offset_dtn + DoraToNativeInfo::last_offset()),
+        REG_TMP1.into(),
+    );
+
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_SP, offset_dtn + DoraToNativeInfo::fp_offset()),
+        REG_FP.into(),
+    );
+
+    masm.copy_pc(REG_TMP1);
+
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_SP, offset_dtn + DoraToNativeInfo::pc_offset()),
+        REG_TMP1.into(),
+    );
+
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_THREAD, ThreadLocalData::dtn_offset()),
+        REG_SP.into(),
+    );
+
+    masm.load_int_const(
+        MachineMode::Ptr,
+        REG_PARAMS[0],
+        interpreted as *const _ as i64,
+    );
+    masm.copy_sp(REG_PARAMS[1]);
+    masm.int_add_imm(
+        MachineMode::Ptr,
+        REG_PARAMS[1],
+        REG_PARAMS[1],
+        offset_args as i64,
+    );
+    masm.raw_call(interpret as *const u8);
+
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_TMP1.into(),
+        Mem::Base(REG_SP, offset_dtn + DoraToNativeInfo::last_offset()),
+    );
+
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_THREAD, ThreadLocalData::dtn_offset()),
+        REG_TMP1.into(),
+    );
+
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_TMP1.into(),
+        Mem::Base(REG_THREAD, ThreadLocalData::exception_object_offset()),
+    );
+
+    let lbl_exception = masm.test_if_not_nil(REG_TMP1);
+
+    if let Some(ty) = interpreted.return_type() {
+        let mode = ty.mode();
+        let dest = if mode.is_float() {
+            FREG_RESULT.into()
+        } else {
+            REG_RESULT.into()
+        };
+
+        masm.load_mem(mode, dest, Mem::Base(REG_SP, offset_args));
+    }
+
+    masm.epilog();
+
+    masm.bind_label(lbl_exception);
+    masm.throw(REG_TMP1, fct.pos);
+    masm.nop();
+
+    masm.jit(
+        vm,
+        framesize,
+        JitDescriptor::InterpreterEntry(fct.id),
+        fct.ast.throws,
+    )
+}
+
+// Returns the stub the interpreter uses to call compiled code with the given
+// signature: `stub(tld, fct_ptr, args)` loads the arguments from `args` and
+// stores the result into the first slot. An exception thrown by the callee
+// is caught and stored in the thread local data.
+pub fn call_stub(vm: &VM, params: &[BytecodeType], return_type: Option<BytecodeType>) -> Address {
+    let mut stubs = vm.interpreter_call_stubs.lock();
+    let key = (params.to_vec(), return_type);
+
+    if let Some(&ptr) = stubs.map.get(&key) {
+        return ptr;
+    }
+
+    let jit_fct = generate_call_stub(vm, params, return_type);
+    let ptr = jit_fct.fct_ptr();
+    let ptr_start = jit_fct.ptr_start();
+    let ptr_end = jit_fct.ptr_end();
+
+    let jit_fct_id: JitFctId = vm.jit_fcts.push(JitFct::Base(jit_fct)).into();
+    vm.insert_code_map(
+        ptr_start,
+        ptr_end,
+        CodeDescriptor::InterpreterCall(jit_fct_id),
+    );
+
+    stubs.map.insert(key, ptr);
+
+    ptr
+}
+
+fn generate_call_stub(
+    vm: &VM,
+    params: &[BytecodeType],
+    return_type: Option<BytecodeType>,
+) -> JitBaselineFct {
+    let mut masm = MacroAssembler::new();
+
+    let offset_thread = 0;
+    let offset_args = offset_thread + mem::ptr_width();
+    let offset_exception = offset_args + mem::ptr_width();
+    let framesize = mem::align_i32(offset_exception + mem::ptr_width(), 16);
+
+    masm.prolog_size(framesize);
+
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_SP, offset_thread),
+        REG_THREAD.into(),
+    );
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_SP, offset_args),
+        REG_PARAMS[2].into(),
+    );
+
+    masm.copy_reg(MachineMode::Ptr, REG_THREAD, REG_PARAMS[0]);
+    masm.copy_reg(MachineMode::Ptr, REG_TMP1, REG_PARAMS[1]);
+    masm.copy_reg(MachineMode::Ptr, REG_TMP2, REG_PARAMS[2]);
+
+    let mut reg_idx = 0;
+    let mut freg_idx = 0;
+
+    for (idx, ty) in params.iter().enumerate() {
+        let mode = ty.mode();
+        let offset = idx as i32 * 8;
+
+        if mode.is_float() {
+            let freg = FREG_PARAMS[freg_idx];
+            masm.load_mem(mode, freg.into(), Mem::Base(REG_TMP2, offset));
+            freg_idx += 1;
+        } else {
+            let reg = REG_PARAMS[reg_idx];
+            masm.load_mem(mode, reg.into(), Mem::Base(REG_TMP2, offset));
+            reg_idx += 1;
+        }
+    }
+
+    let try_start = masm.pos();
+    masm.call_reg(REG_TMP1);
+    let try_end = masm.pos();
+
+    if let Some(ty) = return_type {
+        let mode = ty.mode();
+        let src = if mode.is_float() {
+            FREG_RESULT.into()
+        } else {
+            REG_RESULT.into()
+        };
+
+        masm.load_mem(
+            MachineMode::Ptr,
+            REG_TMP1.into(),
+            Mem::Base(REG_SP, offset_args),
+        );
+        masm.store_mem(mode, Mem::Base(REG_TMP1, 0), src);
+    }
+
+    let lbl_end = masm.create_label();
+    masm.bind_label(lbl_end);
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_THREAD.into(),
+        Mem::Base(REG_SP, offset_thread),
+    );
+    masm.epilog();
+
+    let catch = masm.pos();
+    masm.load_mem(
+        MachineMode::Ptr,
+        REG_TMP1.into(),
+        Mem::Base(REG_SP, offset_exception),
+    );
+    masm.store_mem(
+        MachineMode::Ptr,
+        Mem::Base(REG_THREAD, ThreadLocalData::exception_object_offset()),
+        REG_TMP1.into(),
+    );
+    masm.jump(lbl_end);
+
+    masm.emit_exception_handler(
+        (try_start, try_end),
+        catch,
+        Some(offset_exception - framesize),
+        CatchType::Any,
+    );
+
+    masm.jit(vm, framesize, JitDescriptor::InterpreterCall, false)
+}
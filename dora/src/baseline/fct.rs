@@ -78,6 +78,8 @@ pub enum JitDescriptor {
     VerifyThunk,
     NativeThunk(FctId),
     DoraEntry,
+    InterpreterEntry(FctId),
+    InterpreterCall,
 }
 
 pub struct JitBaselineFct {
@@ -181,6 +183,7 @@ impl JitBaselineFct {
         match self.desc {
             JitDescriptor::NativeThunk(fct_id) => fct_id,
             JitDescriptor::DoraFct(fct_id) => fct_id,
+            JitDescriptor::InterpreterEntry(fct_id) => fct_id,
             _ => panic!("no fctid found"),
         }
     }
@@ -280,6 +283,7 @@ pub enum Comment {
     CallSuper(FctId),
     CallVirtual(FctId),
     CallDirect(FctId),
+    CallClosure,
     StoreParam(VarId),
     Newline,
     StoreField(ClassDefId, FieldId),
@@ -350,6 +354,8 @@ impl<'a, 'ast> fmt::Display for CommentFormat<'a, 'ast> {
                 write!(f, "call direct {}", &name)
             }
 
+            &Comment::CallClosure => write!(f, "call closure"),
+
             &Comment::StoreParam(vid) => {
                 let var = &self.fct_src.unwrap().vars[vid];
                 let name = self.vm.interner.str(var.name);
@@ -43,6 +43,14 @@ impl CodeMap {
                     println!("native {}", fct.full_name(vm));
                 }
                 &CodeDescriptor::DoraEntry => println!("dora_entry"),
+                &CodeDescriptor::InterpreterEntry(jit_fct_id) => {
+                    let jit_fct = vm.jit_fcts.idx(jit_fct_id);
+                    let fct = vm.fcts.idx(jit_fct.fct_id());
+                    let fct = fct.read();
+
+                    println!("interpreter {}", fct.full_name(vm));
+                }
+                &CodeDescriptor::InterpreterCall(_) => println!("interpreter_call"),
             }
         }
 
@@ -71,6 +79,8 @@ pub enum CodeDescriptor {
     VerifyThunk,
     NativeThunk(JitFctId),
     DoraEntry,
+    InterpreterEntry(JitFctId),
+    InterpreterCall(JitFctId),
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -1,4 +1,5 @@
 pub mod astgen;
 pub mod encoding;
 pub mod generate;
+pub mod interpreter;
 pub mod opcode;
@@ -3,24 +3,31 @@ use std::collections::HashMap;
 use dora_parser::ast::Expr::*;
 use dora_parser::ast::Stmt::*;
 use dora_parser::ast::*;
+use dora_parser::lexer::position::Position;
 use dora_parser::lexer::token::{FloatSuffix, IntSuffix};
 
 use crate::bytecode::generate::{
     BytecodeFunction, BytecodeGenerator, BytecodeType, Label, Register,
 };
-use crate::semck::expr_block_always_returns;
 use crate::semck::specialize::{specialize_class_id_params, specialize_class_ty, specialize_type};
+use crate::semck::{always_returns, expr_block_always_returns, match_case_table};
 use crate::ty::{BuiltinType, TypeList};
 use crate::vm::{CallType, Fct, FctId, FctKind, FctSrc, IdentType, Intrinsic, VarId, VM};
 
 pub struct LoopLabels {
     cond: Label,
     end: Label,
+    // number of active finally blocks when entering the loop
+    finallys: usize,
 }
 
 impl LoopLabels {
-    fn new(cond: Label, end: Label) -> LoopLabels {
-        LoopLabels { cond, end }
+    fn new(cond: Label, end: Label, finallys: usize) -> LoopLabels {
+        LoopLabels {
+            cond,
+            end,
+            finallys,
+        }
     }
 }
 
@@ -56,6 +63,7 @@ pub fn generate_fct<'ast>(
 
         gen: BytecodeGenerator::new(),
         loops: Vec::new(),
+        finallys: Vec::new(),
         var_registers: HashMap::new(),
     };
     ast_bytecode_generator.generate()
@@ -72,14 +80,18 @@ pub struct AstBytecodeGen<'a, 'ast: 'a> {
 
     gen: BytecodeGenerator,
     loops: Vec<LoopLabels>,
+    // finally blocks of the enclosing do statements, innermost last
+    finallys: Vec<&'ast Stmt>,
     var_registers: HashMap<VarId, Register>,
 }
 
 impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
     pub fn generate(mut self) -> BytecodeFunction {
         if self.fct.has_self() {
-            let var_id = self.src.var_self().id;
-            let reg = self.gen.add_register(BytecodeType::Ptr);
+            let var_self = self.src.var_self();
+            let var_id = var_self.id;
+            let ty: BytecodeType = self.specialize_type(var_self.ty).into();
+            let reg = self.gen.add_register(ty);
             self.var_registers.insert(var_id, reg);
         }
 
@@ -115,7 +127,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
     }
 
     // TODO - implement other statements
-    fn visit_stmt(&mut self, stmt: &Stmt) {
+    fn visit_stmt(&mut self, stmt: &'ast Stmt) {
         match *stmt {
             StmtReturn(ref ret) => self.visit_stmt_return(ret),
             StmtBreak(ref stmt) => self.visit_stmt_break(stmt),
@@ -125,15 +137,15 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             StmtWhile(ref stmt) => self.visit_stmt_while(stmt),
             StmtLoop(ref stmt) => self.visit_stmt_loop(stmt),
             StmtThrow(ref stmt) => self.visit_stmt_throw(stmt),
+            StmtDo(ref stmt) => self.visit_stmt_do(stmt),
             // StmtDefer(ref stmt) => {},
-            // StmtDo(ref stmt) => {},
             // StmtSpawn(ref stmt) => {},
             // StmtFor(ref stmt) => {},
             _ => unimplemented!(),
         }
     }
 
-    fn visit_stmt_var(&mut self, stmt: &StmtVarType) {
+    fn visit_stmt_var(&mut self, stmt: &'ast StmtVarType) {
         let var_id = *self.src.map_vars.get(stmt.id).unwrap();
         let ty: BytecodeType = self.specialize_type(self.src.vars[var_id].ty).into();
         let var_reg = self.gen.add_register(ty);
@@ -145,42 +157,143 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
     }
 
-    fn visit_stmt_while(&mut self, stmt: &StmtWhileType) {
+    fn visit_stmt_while(&mut self, stmt: &'ast StmtWhileType) {
         let cond_lbl = self.gen.define_label();
         let end_lbl = self.gen.create_label();
         let cond_reg = self.visit_expr(&stmt.cond, DataDest::Alloc);
         self.gen.emit_jump_if_false(cond_reg, end_lbl);
-        self.loops.push(LoopLabels::new(cond_lbl, end_lbl));
+        self.loops
+            .push(LoopLabels::new(cond_lbl, end_lbl, self.finallys.len()));
         self.visit_stmt(&stmt.block);
         self.loops.pop();
         self.gen.emit_jump(cond_lbl);
         self.gen.bind_label(end_lbl);
     }
 
-    fn visit_stmt_loop(&mut self, stmt: &StmtLoopType) {
+    fn visit_stmt_loop(&mut self, stmt: &'ast StmtLoopType) {
         let start_lbl = self.gen.define_label();
         let end_lbl = self.gen.create_label();
-        self.loops.push(LoopLabels::new(start_lbl, end_lbl));
+        self.loops
+            .push(LoopLabels::new(start_lbl, end_lbl, self.finallys.len()));
         self.visit_stmt(&stmt.block);
         self.loops.pop();
         self.gen.emit_jump(start_lbl);
         self.gen.bind_label(end_lbl);
     }
 
-    fn visit_stmt_throw(&mut self, stmt: &StmtThrowType) {
+    fn visit_stmt_throw(&mut self, stmt: &'ast StmtThrowType) {
         let exception_reg = self.visit_expr(&stmt.expr, DataDest::Alloc);
         self.gen.emit_throw(exception_reg);
     }
 
-    fn visit_stmt_expr(&mut self, stmt: &StmtExprType) {
+    fn visit_stmt_do(&mut self, stmt: &'ast StmtDoType) {
+        let end_lbl = self.gen.create_label();
+
+        let do_span = self.visit_stmt_with_finally(stmt, &stmt.do_block, end_lbl);
+        let mut spans = vec![do_span];
+
+        for catch in &stmt.catch_blocks {
+            let var_id = *self.src.map_vars.get(catch.id).unwrap();
+            let exception_reg = self.gen.add_register(BytecodeType::Ptr);
+            self.var_registers.insert(var_id, exception_reg);
+
+            let ty = self.ty(catch.data_type.id());
+            let cls_def_id = specialize_class_ty(self.vm, ty);
+
+            let catch_span = self.visit_stmt_with_finally(stmt, &catch.block, end_lbl);
+            self.gen.add_exception_handler(
+                do_span.0,
+                do_span.1,
+                catch_span.0,
+                Some(cls_def_id),
+                Some(exception_reg),
+            );
+
+            spans.push(catch_span);
+        }
+
+        // the finally block runs for exceptions thrown in the do or catch
+        // blocks and rethrows them afterwards
+        if let Some(ref finally_block) = stmt.finally_block {
+            let exception_reg = self.gen.add_register(BytecodeType::Ptr);
+            let finally_lbl = self.gen.define_label();
+
+            self.visit_stmt(&finally_block.block);
+            self.gen.set_position(stmt.pos);
+            self.gen.emit_throw(exception_reg);
+
+            for (start, end) in spans {
+                self.gen
+                    .add_exception_handler(start, end, finally_lbl, None, Some(exception_reg));
+            }
+        }
+
+        self.gen.bind_label(end_lbl);
+    }
+
+    // Returns the labels around the block, the finally block is inlined
+    // when the block completes normally.
+    fn visit_stmt_with_finally(
+        &mut self,
+        stmt: &'ast StmtDoType,
+        block: &'ast Stmt,
+        end_lbl: Label,
+    ) -> (Label, Label) {
+        if let Some(ref finally_block) = stmt.finally_block {
+            self.finallys.push(&finally_block.block);
+        }
+
+        let start = self.gen.define_label();
+        self.visit_stmt(block);
+        let end = self.gen.define_label();
+
+        if stmt.finally_block.is_some() {
+            self.finallys.pop();
+        }
+
+        if !always_returns(block) {
+            if let Some(ref finally_block) = stmt.finally_block {
+                self.visit_stmt(&finally_block.block);
+            }
+
+            self.gen.emit_jump(end_lbl);
+        }
+
+        (start, end)
+    }
+
+    // Inlines the finally blocks entered since `start`, innermost first. A
+    // return in one of them only runs the finally blocks outside of it.
+    fn emit_finallys(&mut self, start: usize) {
+        let finallys = self.finallys.clone();
+
+        for idx in (start..finallys.len()).rev() {
+            self.finallys.truncate(idx);
+            self.visit_stmt(finallys[idx]);
+        }
+
+        self.finallys = finallys;
+    }
+
+    fn visit_stmt_expr(&mut self, stmt: &'ast StmtExprType) {
         self.visit_expr(&stmt.expr, DataDest::Effect);
     }
 
-    fn visit_stmt_return(&mut self, ret: &StmtReturnType) {
+    fn visit_stmt_return(&mut self, ret: &'ast StmtReturnType) {
         if let Some(ref expr) = ret.expr {
-            let result_reg = self.visit_expr(expr, DataDest::Alloc);
+            let dest = if self.finallys.is_empty() || self.fct.return_type.is_unit() {
+                DataDest::Alloc
+            } else {
+                // finally blocks must not change the returned value
+                let ty = self.specialize_type(self.fct.return_type).into();
+                DataDest::Reg(self.gen.add_register(ty))
+            };
+
+            let result_reg = self.visit_expr(expr, dest);
+            self.emit_finallys(0);
             self.emit_ret_value(result_reg);
         } else {
+            self.emit_finallys(0);
             self.gen.emit_ret_void();
         }
     }
@@ -206,23 +319,32 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
     }
 
     fn visit_stmt_break(&mut self, _stmt: &StmtBreakType) {
-        let end = self.loops.pop().unwrap().end;
+        let (end, finallys) = {
+            let current = self.loops.last().unwrap();
+            (current.end, current.finallys)
+        };
+        self.emit_finallys(finallys);
         self.gen.emit_jump(end);
     }
 
     fn visit_stmt_continue(&mut self, _stmt: &StmtContinueType) {
-        let cond = self.loops.last().unwrap().cond;
+        let (cond, finallys) = {
+            let current = self.loops.last().unwrap();
+            (current.cond, current.finallys)
+        };
+        self.emit_finallys(finallys);
         self.gen.emit_jump(cond);
     }
 
     // TODO - implement other expressions
-    fn visit_expr(&mut self, expr: &Expr, dest: DataDest) -> Register {
+    fn visit_expr(&mut self, expr: &'ast Expr, dest: DataDest) -> Register {
         match *expr {
             ExprUn(ref un) => self.visit_expr_un(un, dest),
             ExprBin(ref bin) => self.visit_expr_bin(bin, dest),
             ExprDot(ref field) => self.visit_expr_dot(field, dest),
             ExprBlock(ref block) => self.visit_expr_block(block, dest),
             ExprIf(ref expr) => self.visit_expr_if(expr, dest),
+            ExprMatch(ref expr) => self.visit_expr_match(expr, dest),
             // ExprArray(ref array) => {},
             ExprLitChar(ref lit) => self.visit_expr_lit_char(lit, dest),
             ExprLitInt(ref lit) => self.visit_expr_lit_int(lit, dest),
@@ -231,24 +353,26 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             // ExprLitStruct(ref lit) => {},
             ExprLitBool(ref lit) => self.visit_expr_lit_bool(lit, dest),
             ExprIdent(ref ident) => self.visit_expr_ident(ident, dest),
+            ExprPath(ref path) => self.visit_expr_path(path, dest),
             ExprCall(ref call) => self.visit_expr_call(call, dest),
-            // ExprDelegation(ref call) => {},
+            ExprDelegation(ref call) => self.visit_expr_delegation(call, dest),
             ExprSelf(ref selfie) => self.visit_expr_self(selfie, dest),
             // ExprSuper(ref expr) => {},
             ExprNil(ref nil) => self.visit_expr_nil(nil, dest),
             // ExprConv(ref expr) => {},
-            // ExprTry(ref expr) => {},
+            ExprTry(ref expr) => self.visit_expr_try(expr, dest),
             // ExprLambda(ref expr) => {},
             _ => unimplemented!(),
         }
     }
 
-    fn visit_expr_if(&mut self, expr: &ExprIfType, dest: DataDest) -> Register {
+    fn visit_expr_if(&mut self, expr: &'ast ExprIfType, dest: DataDest) -> Register {
         let ty = self.ty(expr.id);
-        let dest = if ty.is_unit() {
-            Register::invalid()
+        let (dest, result) = if ty.is_unit() {
+            (DataDest::Effect, Register::invalid())
         } else {
-            self.ensure_register(dest, ty.into())
+            let reg = self.ensure_register(dest, ty.into());
+            (DataDest::Reg(reg), reg)
         };
 
         if let Some(ref else_block) = expr.else_block {
@@ -258,24 +382,73 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             let cond_reg = self.visit_expr(&expr.cond, DataDest::Alloc);
             self.gen.emit_jump_if_false(cond_reg, else_lbl);
 
-            self.visit_expr(&expr.then_block, DataDest::Reg(dest));
+            self.visit_expr(&expr.then_block, dest);
             self.gen.emit_jump(end_lbl);
 
             self.gen.bind_label(else_lbl);
-            self.visit_expr(else_block, DataDest::Reg(dest));
+            self.visit_expr(else_block, dest);
             self.gen.bind_label(end_lbl);
         } else {
             let end_lbl = self.gen.create_label();
             let cond_reg = self.visit_expr(&expr.cond, DataDest::Alloc);
             self.gen.emit_jump_if_false(cond_reg, end_lbl);
-            self.visit_expr(&expr.then_block, DataDest::Reg(dest));
+            self.visit_expr(&expr.then_block, dest);
             self.gen.bind_label(end_lbl);
         }
 
-        dest
+        result
+    }
+
+    fn visit_expr_match(&mut self, expr: &'ast ExprMatchType, dest: DataDest) -> Register {
+        let ty = self.ty(expr.id);
+        let (dest, result) = if ty.is_unit() {
+            (DataDest::Effect, Register::invalid())
+        } else {
+            let reg = self.ensure_register(dest, ty.into());
+            (DataDest::Reg(reg), reg)
+        };
+
+        let end_lbl = self.gen.create_label();
+        let case_lbls = expr
+            .cases
+            .iter()
+            .map(|_| self.gen.create_label())
+            .collect::<Vec<_>>();
+
+        // enums with payload data are not supported yet
+        if self.ty(expr.expr.id()).is_enum_object() {
+            unimplemented!();
+        }
+
+        let targets = match_case_table(self.vm, self.src, expr)
+            .into_iter()
+            .map(|idx| case_lbls[idx])
+            .collect::<Vec<_>>();
+
+        let opnd = self.visit_expr(&expr.expr, DataDest::Alloc);
+        self.gen.emit_jump_table(opnd, &targets);
+
+        for (case, &case_lbl) in expr.cases.iter().zip(&case_lbls) {
+            self.gen.bind_label(case_lbl);
+            self.visit_expr(&case.value, dest);
+            self.gen.emit_jump(end_lbl);
+        }
+
+        self.gen.bind_label(end_lbl);
+
+        result
+    }
+
+    fn visit_expr_try(&mut self, expr: &'ast ExprTryType, dest: DataDest) -> Register {
+        // invoke instructions propagate exceptions of the callee
+        if expr.mode.is_normal() {
+            self.visit_expr(&expr.expr, dest)
+        } else {
+            unimplemented!()
+        }
     }
 
-    fn visit_expr_block(&mut self, block: &ExprBlockType, dest: DataDest) -> Register {
+    fn visit_expr_block(&mut self, block: &'ast ExprBlockType, dest: DataDest) -> Register {
         for stmt in &block.stmts {
             self.visit_stmt(stmt);
         }
@@ -287,7 +460,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
     }
 
-    fn visit_expr_dot(&mut self, e: &ExprDotType, dest: DataDest) -> Register {
+    fn visit_expr_dot(&mut self, e: &'ast ExprDotType, dest: DataDest) -> Register {
         let (class, field_id) = {
             let ident_type = self.src.map_idents.get(e.id).unwrap();
 
@@ -321,7 +494,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         dest
     }
 
-    fn visit_expr_assert(&mut self, expr: &ExprCallType, _dest: DataDest) {
+    fn visit_expr_assert(&mut self, expr: &'ast ExprCallType, _dest: DataDest) {
         let lbl_assert = self.gen.create_label();
 
         let assert_reg = self.visit_expr(&*expr.args[0], DataDest::Alloc);
@@ -365,7 +538,11 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         self.gen.bind_label(lbl_assert);
     }
 
-    fn visit_expr_call(&mut self, expr: &ExprCallType, dest: DataDest) -> Register {
+    fn visit_expr_call(&mut self, expr: &'ast ExprCallType, dest: DataDest) -> Register {
+        if let CallType::EnumValue(_, _) = **self.src.map_calls.get(expr.id).unwrap() {
+            unimplemented!();
+        }
+
         if let Some(intrinsic) = self.get_intrinsic(expr.id) {
             match intrinsic {
                 Intrinsic::Assert => self.visit_expr_assert(expr, dest),
@@ -375,6 +552,26 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
 
         let call_type = self.src.map_calls.get(expr.id).unwrap().clone();
+        let object = if call_type.is_method() {
+            expr.object()
+        } else {
+            None
+        };
+        let args = expr.args.iter().map(|arg| &**arg).collect::<Vec<_>>();
+
+        self.emit_call(&call_type, object, &args, expr.pos, dest)
+    }
+
+    // Calls the function of `call_type`: `object` is the receiver of method
+    // calls, other functions with `self` are called on `self`.
+    fn emit_call(
+        &mut self,
+        call_type: &CallType,
+        object: Option<&'ast Expr>,
+        args: &[&'ast Expr],
+        pos: Position,
+        dest: DataDest,
+    ) -> Register {
         let fct_id = call_type.fct_id().unwrap();
 
         let fct = self.vm.fcts.idx(fct_id);
@@ -396,12 +593,12 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         let return_type = if dest.is_effect() {
             BuiltinType::Unit
         } else {
-            self.specialize_type_for_call(&call_type, callee.return_type)
+            self.specialize_type_for_call(call_type, callee.return_type)
         };
         let arg_types = callee
             .params_with_self()
             .iter()
-            .map(|&arg| self.specialize_type_for_call(&call_type, arg).into())
+            .map(|&arg| self.specialize_type_for_call(call_type, arg).into())
             .collect::<Vec<BytecodeType>>();
         let num_args = arg_types.len();
 
@@ -417,68 +614,69 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             Register::zero()
         };
 
-        let arg_start_reg = if let CallType::CtorNew(cls_id, _, tp) = &*call_type {
+        self.gen.set_position(pos);
+
+        let arg_start_reg = if let CallType::CtorNew(cls_id, _, tp) = call_type {
             let cls_id = specialize_class_id_params(self.vm, *cls_id, tp);
             self.gen.emit_new_object(start_reg, cls_id);
             start_reg.offset(1)
         } else if callee.has_self() {
-            let self_id = self.src.var_self().id;
-            let self_reg = self.var_reg(self_id);
+            match object {
+                Some(obj) => {
+                    self.visit_expr(obj, DataDest::Reg(start_reg));
+                }
+
+                None => {
+                    let self_id = self.src.var_self().id;
+                    let self_reg = self.var_reg(self_id);
+
+                    self.gen.emit_mov_ptr(start_reg, self_reg);
+                }
+            }
 
-            self.gen.emit_mov_ptr(start_reg, self_reg);
             start_reg.offset(1)
         } else {
             start_reg
         };
 
-        for (idx, arg) in expr.args.iter().enumerate() {
+        for (idx, &arg) in args.iter().enumerate() {
             let arg_reg = arg_start_reg.offset(idx);
             self.visit_expr(arg, DataDest::Reg(arg_reg));
         }
 
+        self.gen.set_position(pos);
+
         match *call_type {
             CallType::Ctor(_, _, _) | CallType::CtorNew(_, _, _) => {
                 self.gen
                     .emit_invoke_direct_void(callee_id, start_reg, num_args);
             }
 
-            CallType::Method(_, _, _) => unimplemented!(),
+            CallType::Method(_, _, _) => {
+                if callee.is_virtual() {
+                    self.emit_invoke_virtual(
+                        return_type,
+                        return_reg,
+                        callee_id,
+                        start_reg,
+                        num_args,
+                    );
+                } else {
+                    self.emit_invoke_direct(
+                        return_type,
+                        return_reg,
+                        callee_id,
+                        start_reg,
+                        num_args,
+                    );
+                }
+            }
+
             CallType::Expr(_, _) => unimplemented!(),
+            CallType::Lambda(_) | CallType::EnumValue(_, _) => unimplemented!(),
 
             CallType::Fct(_, _, _) => {
-                if return_type.is_unit() {
-                    self.gen
-                        .emit_invoke_static_void(callee_id, start_reg, num_args);
-                } else {
-                    let return_type: BytecodeType = return_type.into();
-
-                    match return_type.into() {
-                        BytecodeType::Bool => self
-                            .gen
-                            .emit_invoke_static_bool(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Byte => self
-                            .gen
-                            .emit_invoke_static_byte(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Char => self
-                            .gen
-                            .emit_invoke_static_char(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Int => self
-                            .gen
-                            .emit_invoke_static_int(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Long => self
-                            .gen
-                            .emit_invoke_static_long(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Float => self
-                            .gen
-                            .emit_invoke_static_float(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Double => self
-                            .gen
-                            .emit_invoke_static_double(return_reg, callee_id, start_reg, num_args),
-                        BytecodeType::Ptr => self
-                            .gen
-                            .emit_invoke_static_ptr(return_reg, callee_id, start_reg, num_args),
-                    }
-                }
+                self.emit_invoke_static(return_type, return_reg, callee_id, start_reg, num_args);
             }
 
             CallType::Trait(_, _) => unimplemented!(),
@@ -487,12 +685,187 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
 
         if call_type.is_ctor_new() {
-            start_reg
+            match dest {
+                DataDest::Reg(dest) => {
+                    self.gen.emit_mov_ptr(dest, start_reg);
+                    dest
+                }
+                DataDest::Effect | DataDest::Alloc => start_reg,
+            }
         } else {
             return_reg
         }
     }
 
+    fn emit_invoke_direct(
+        &mut self,
+        return_type: BuiltinType,
+        return_reg: Register,
+        callee_id: FctId,
+        start_reg: Register,
+        num_args: usize,
+    ) {
+        if return_type.is_unit() {
+            self.gen
+                .emit_invoke_direct_void(callee_id, start_reg, num_args);
+            return;
+        }
+
+        let return_type: BytecodeType = return_type.into();
+
+        match return_type {
+            BytecodeType::Bool => self
+                .gen
+                .emit_invoke_direct_bool(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Byte => self
+                .gen
+                .emit_invoke_direct_byte(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Char => self
+                .gen
+                .emit_invoke_direct_char(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Int => self
+                .gen
+                .emit_invoke_direct_int(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Long => self
+                .gen
+                .emit_invoke_direct_long(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Float => self
+                .gen
+                .emit_invoke_direct_float(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Double => self
+                .gen
+                .emit_invoke_direct_double(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Ptr => self
+                .gen
+                .emit_invoke_direct_ptr(return_reg, callee_id, start_reg, num_args),
+        }
+    }
+
+    fn emit_invoke_virtual(
+        &mut self,
+        return_type: BuiltinType,
+        return_reg: Register,
+        callee_id: FctId,
+        start_reg: Register,
+        num_args: usize,
+    ) {
+        if return_type.is_unit() {
+            self.gen
+                .emit_invoke_virtual_void(callee_id, start_reg, num_args);
+            return;
+        }
+
+        let return_type: BytecodeType = return_type.into();
+
+        match return_type {
+            BytecodeType::Bool => self
+                .gen
+                .emit_invoke_virtual_bool(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Byte => self
+                .gen
+                .emit_invoke_virtual_byte(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Char => self
+                .gen
+                .emit_invoke_virtual_char(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Int => self
+                .gen
+                .emit_invoke_virtual_int(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Long => self
+                .gen
+                .emit_invoke_virtual_long(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Float => self
+                .gen
+                .emit_invoke_virtual_float(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Double => self
+                .gen
+                .emit_invoke_virtual_double(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Ptr => self
+                .gen
+                .emit_invoke_virtual_ptr(return_reg, callee_id, start_reg, num_args),
+        }
+    }
+
+    fn emit_invoke_static(
+        &mut self,
+        return_type: BuiltinType,
+        return_reg: Register,
+        callee_id: FctId,
+        start_reg: Register,
+        num_args: usize,
+    ) {
+        if return_type.is_unit() {
+            self.gen
+                .emit_invoke_static_void(callee_id, start_reg, num_args);
+            return;
+        }
+
+        let return_type: BytecodeType = return_type.into();
+
+        match return_type {
+            BytecodeType::Bool => self
+                .gen
+                .emit_invoke_static_bool(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Byte => self
+                .gen
+                .emit_invoke_static_byte(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Char => self
+                .gen
+                .emit_invoke_static_char(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Int => self
+                .gen
+                .emit_invoke_static_int(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Long => self
+                .gen
+                .emit_invoke_static_long(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Float => self
+                .gen
+                .emit_invoke_static_float(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Double => self
+                .gen
+                .emit_invoke_static_double(return_reg, callee_id, start_reg, num_args),
+            BytecodeType::Ptr => self
+                .gen
+                .emit_invoke_static_ptr(return_reg, callee_id, start_reg, num_args),
+        }
+    }
+
+    fn visit_expr_delegation(
+        &mut self,
+        expr: &'ast ExprDelegationType,
+        _dest: DataDest,
+    ) -> Register {
+        let call_type = self.src.map_calls.get(expr.id).unwrap().clone();
+        let callee_id = call_type.fct_id().unwrap();
+
+        let arg_types = {
+            let callee = self.vm.fcts.idx(callee_id);
+            let callee = callee.read();
+
+            callee
+                .params_with_self()
+                .iter()
+                .map(|&arg| self.specialize_type_for_call(&call_type, arg).into())
+                .collect::<Vec<BytecodeType>>()
+        };
+        let num_args = arg_types.len();
+        let start_reg = self.gen.add_register_chain(&arg_types);
+
+        let self_id = self.src.var_self().id;
+        let self_reg = self.var_reg(self_id);
+        self.gen.emit_mov_ptr(start_reg, self_reg);
+
+        for (idx, arg) in expr.args.iter().enumerate() {
+            let arg_reg = start_reg.offset(idx + 1);
+            self.visit_expr(arg, DataDest::Reg(arg_reg));
+        }
+
+        self.gen.set_position(expr.pos);
+        self.gen
+            .emit_invoke_direct_void(callee_id, start_reg, num_args);
+
+        Register::invalid()
+    }
+
     fn visit_expr_nil(&mut self, _nil: &ExprNilType, dest: DataDest) -> Register {
         if dest.is_effect() {
             return Register::invalid();
@@ -545,6 +918,26 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         dest
     }
 
+    fn visit_expr_path(&mut self, expr: &ExprPathType, dest: DataDest) -> Register {
+        let value = match self.src.map_idents.get(expr.id) {
+            Some(&IdentType::EnumValue(_, value)) => value,
+            _ => unreachable!(),
+        };
+
+        if self.ty(expr.id).is_enum_object() {
+            unimplemented!();
+        }
+
+        if dest.is_effect() {
+            return Register::invalid();
+        }
+
+        let dest = self.ensure_register(dest, BytecodeType::Int);
+        self.gen.emit_const_int(dest, value);
+
+        dest
+    }
+
     fn visit_expr_lit_int(&mut self, lit: &ExprLitIntType, dest: DataDest) -> Register {
         if dest.is_effect() {
             return Register::invalid();
@@ -636,7 +1029,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         dest
     }
 
-    fn visit_expr_un(&mut self, expr: &ExprUnType, dest: DataDest) -> Register {
+    fn visit_expr_un(&mut self, expr: &'ast ExprUnType, dest: DataDest) -> Register {
         if let Some(intrinsic) = self.get_intrinsic(expr.id) {
             if dest.is_effect() {
                 self.visit_expr(&expr.opnd, dest);
@@ -680,7 +1073,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
     }
 
-    fn visit_expr_bin(&mut self, e: &ExprBinType, dest: DataDest) -> Register {
+    fn visit_expr_bin(&mut self, e: &'ast ExprBinType, dest: DataDest) -> Register {
         if e.op.is_any_assign() {
             self.visit_expr_assign(e, dest)
         } else if e.op == BinOp::Cmp(CmpOp::Is) || e.op == BinOp::Cmp(CmpOp::IsNot) {
@@ -692,32 +1085,95 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         } else if let Some(intrinsic) = self.get_intrinsic(e.id) {
             self.emit_intrinsic_bin(&e.lhs, &e.rhs, intrinsic, e.op, dest)
         } else {
-            unimplemented!();
+            self.emit_bin_call(e, dest)
         }
     }
 
-    fn emit_bin_is(&mut self, e: &ExprBinType, dest: DataDest) -> Register {
+    // Operators without intrinsic call their method, comparisons are
+    // derived from the result of `equals` or `compareTo`.
+    fn emit_bin_call(&mut self, e: &'ast ExprBinType, dest: DataDest) -> Register {
+        let call_type = self.src.map_calls.get(e.id).unwrap().clone();
+
+        let result = match e.op {
+            BinOp::Cmp(CmpOp::Ne) => {
+                let result =
+                    self.emit_call(&call_type, Some(&e.lhs), &[&e.rhs], e.pos, DataDest::Alloc);
+                let dest = self.ensure_register(dest, BytecodeType::Bool);
+                self.gen.emit_not_bool(dest, result);
+
+                dest
+            }
+
+            BinOp::Cmp(op) if op != CmpOp::Eq => {
+                let result =
+                    self.emit_call(&call_type, Some(&e.lhs), &[&e.rhs], e.pos, DataDest::Alloc);
+                let zero = self.gen.add_register(BytecodeType::Int);
+                self.gen.emit_const_zero_int(zero);
+                let dest = self.ensure_register(dest, BytecodeType::Bool);
+
+                match op {
+                    CmpOp::Lt => self.gen.emit_test_lt_int(dest, result, zero),
+                    CmpOp::Le => self.gen.emit_test_le_int(dest, result, zero),
+                    CmpOp::Gt => self.gen.emit_test_gt_int(dest, result, zero),
+                    CmpOp::Ge => self.gen.emit_test_ge_int(dest, result, zero),
+                    _ => unreachable!(),
+                }
+
+                dest
+            }
+
+            _ => self.emit_call(&call_type, Some(&e.lhs), &[&e.rhs], e.pos, dest),
+        };
+
+        if dest.is_effect() {
+            Register::invalid()
+        } else {
+            result
+        }
+    }
+
+    fn emit_bin_is(&mut self, e: &'ast ExprBinType, dest: DataDest) -> Register {
         if dest.is_effect() {
             self.visit_expr(&e.lhs, dest);
             self.visit_expr(&e.rhs, dest);
             return Register::invalid();
         }
 
+        let ty: BytecodeType = match self.ty(e.lhs.id()) {
+            BuiltinType::Nil => BytecodeType::Ptr,
+            ty => ty.into(),
+        };
         let dest = self.ensure_register(dest, BytecodeType::Bool);
 
         let lhs_reg = self.visit_expr(&e.lhs, DataDest::Alloc);
         let rhs_reg = self.visit_expr(&e.rhs, DataDest::Alloc);
 
         if e.op == BinOp::Cmp(CmpOp::Is) {
-            self.gen.emit_test_eq_ptr(dest, lhs_reg, rhs_reg);
+            match ty {
+                BytecodeType::Bool => self.gen.emit_test_eq_bool(dest, lhs_reg, rhs_reg),
+                BytecodeType::Byte => self.gen.emit_test_eq_byte(dest, lhs_reg, rhs_reg),
+                BytecodeType::Char => self.gen.emit_test_eq_char(dest, lhs_reg, rhs_reg),
+                BytecodeType::Int => self.gen.emit_test_eq_int(dest, lhs_reg, rhs_reg),
+                BytecodeType::Long => self.gen.emit_test_eq_long(dest, lhs_reg, rhs_reg),
+                BytecodeType::Ptr => self.gen.emit_test_eq_ptr(dest, lhs_reg, rhs_reg),
+                BytecodeType::Float | BytecodeType::Double => unimplemented!(),
+            }
         } else {
-            self.gen.emit_test_ne_ptr(dest, lhs_reg, rhs_reg);
+            match ty {
+                BytecodeType::Bool => self.gen.emit_test_ne_bool(dest, lhs_reg, rhs_reg),
+                BytecodeType::Byte => self.gen.emit_test_ne_byte(dest, lhs_reg, rhs_reg),
+                BytecodeType::Char => self.gen.emit_test_ne_char(dest, lhs_reg, rhs_reg),
+                BytecodeType::Int => self.gen.emit_test_ne_int(dest, lhs_reg, rhs_reg),
+                BytecodeType::Long => self.gen.emit_test_ne_long(dest, lhs_reg, rhs_reg),
+                BytecodeType::Ptr => self.gen.emit_test_ne_ptr(dest, lhs_reg, rhs_reg),
+                BytecodeType::Float | BytecodeType::Double => unimplemented!(),
+            }
         }
 
         dest
     }
 
-    fn emit_bin_or(&mut self, e: &ExprBinType, dest: DataDest) -> Register {
+    fn emit_bin_or(&mut self, e: &'ast ExprBinType, dest: DataDest) -> Register {
         if dest.is_effect() {
             let end_lbl = self.gen.create_label();
             let dest = self.gen.add_register(BytecodeType::Bool);
@@ -741,7 +1197,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         }
     }
 
-    fn emit_bin_and(&mut self, e: &ExprBinType, dest: DataDest) -> Register {
+    fn emit_bin_and(&mut self, e: &'ast ExprBinType, dest: DataDest) -> Register {
         if dest.is_effect() {
             let end_lbl = self.gen.create_label();
             let dest = self.gen.add_register(BytecodeType::Bool);
@@ -767,8 +1223,8 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
 
     fn emit_intrinsic_bin(
         &mut self,
-        lhs: &Expr,
-        rhs: &Expr,
+        lhs: &'ast Expr,
+        rhs: &'ast Expr,
         intrinsic: Intrinsic,
         op: BinOp,
         dest: DataDest,
@@ -785,7 +1241,19 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             | Intrinsic::IntShl
             | Intrinsic::IntShr
             | Intrinsic::IntSar => BytecodeType::Int,
-            Intrinsic::IntEq | Intrinsic::IntCmp => BytecodeType::Bool,
+            Intrinsic::BoolEq
+            | Intrinsic::ByteEq
+            | Intrinsic::ByteCmp
+            | Intrinsic::CharEq
+            | Intrinsic::CharCmp
+            | Intrinsic::EnumEq
+            | Intrinsic::EnumNe
+            | Intrinsic::IntEq
+            | Intrinsic::IntCmp
+            | Intrinsic::FloatEq
+            | Intrinsic::FloatCmp
+            | Intrinsic::DoubleEq
+            | Intrinsic::DoubleCmp => BytecodeType::Bool,
             _ => unimplemented!(),
         };
 
@@ -801,6 +1269,37 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
         let rhs_reg = self.visit_expr(rhs, DataDest::Alloc);
 
         match intrinsic {
+            Intrinsic::BoolEq => match op {
+                BinOp::Cmp(CmpOp::Eq) => self.gen.emit_test_eq_bool(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ne) => self.gen.emit_test_ne_bool(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::ByteEq => match op {
+                BinOp::Cmp(CmpOp::Eq) => self.gen.emit_test_eq_byte(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ne) => self.gen.emit_test_ne_byte(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::ByteCmp => match op {
+                BinOp::Cmp(CmpOp::Lt) => self.gen.emit_test_lt_byte(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Le) => self.gen.emit_test_le_byte(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Gt) => self.gen.emit_test_gt_byte(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ge) => self.gen.emit_test_ge_byte(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::CharEq => match op {
+                BinOp::Cmp(CmpOp::Eq) => self.gen.emit_test_eq_char(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ne) => self.gen.emit_test_ne_char(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::CharCmp => match op {
+                BinOp::Cmp(CmpOp::Lt) => self.gen.emit_test_lt_char(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Le) => self.gen.emit_test_le_char(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Gt) => self.gen.emit_test_gt_char(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ge) => self.gen.emit_test_ge_char(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::EnumEq => self.gen.emit_test_eq_int(dest, lhs_reg, rhs_reg),
+            Intrinsic::EnumNe => self.gen.emit_test_ne_int(dest, lhs_reg, rhs_reg),
             Intrinsic::IntAdd => self.gen.emit_add_int(dest, lhs_reg, rhs_reg),
             Intrinsic::IntSub => self.gen.emit_sub_int(dest, lhs_reg, rhs_reg),
             Intrinsic::IntMul => self.gen.emit_mul_int(dest, lhs_reg, rhs_reg),
@@ -824,15 +1323,37 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
                 BinOp::Cmp(CmpOp::Ge) => self.gen.emit_test_ge_int(dest, lhs_reg, rhs_reg),
                 _ => unreachable!(),
             },
+            Intrinsic::FloatEq => match op {
+                BinOp::Cmp(CmpOp::Eq) => self.gen.emit_test_eq_float(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ne) => self.gen.emit_test_ne_float(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::FloatCmp => match op {
+                BinOp::Cmp(CmpOp::Lt) => self.gen.emit_test_lt_float(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Le) => self.gen.emit_test_le_float(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Gt) => self.gen.emit_test_gt_float(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ge) => self.gen.emit_test_ge_float(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::DoubleEq => match op {
+                BinOp::Cmp(CmpOp::Eq) => self.gen.emit_test_eq_double(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ne) => self.gen.emit_test_ne_double(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
+            Intrinsic::DoubleCmp => match op {
+                BinOp::Cmp(CmpOp::Lt) => self.gen.emit_test_lt_double(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Le) => self.gen.emit_test_le_double(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Gt) => self.gen.emit_test_gt_double(dest, lhs_reg, rhs_reg),
+                BinOp::Cmp(CmpOp::Ge) => self.gen.emit_test_ge_double(dest, lhs_reg, rhs_reg),
+                _ => unreachable!(),
+            },
             _ => unimplemented!(),
         }
 
         dest
     }
 
-    fn visit_expr_assign(&mut self, e: &ExprBinType, dest: DataDest) -> Register {
-        assert!(dest.is_effect());
-
+    fn visit_expr_assign(&mut self, e: &'ast ExprBinType, _dest: DataDest) -> Register {
         if e.lhs.is_ident() {
             let ident_type = self.src.map_idents.get(e.lhs.id()).unwrap();
             match ident_type {
@@ -844,8 +1365,9 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
                 &IdentType::Global(_) => unimplemented!(),
                 &IdentType::Field(_, _) => unimplemented!(),
 
-                &IdentType::Struct(_) => unimplemented!(),
+                &IdentType::Struct(_) | &IdentType::StructField(_, _) => unimplemented!(),
                 &IdentType::Enum(_) | &IdentType::EnumValue(_, _) => unreachable!(),
+                &IdentType::Module(_) => unreachable!(),
                 &IdentType::Const(_) => unreachable!(),
                 &IdentType::Fct(_) | &IdentType::FctType(_, _) => unreachable!(),
                 &IdentType::Class(_) | &IdentType::ClassType(_, _) => unimplemented!(),
@@ -924,10 +1446,11 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             }
 
             &IdentType::Field(_, _) => unimplemented!(),
-            &IdentType::Struct(_) => unimplemented!(),
+            &IdentType::Struct(_) | &IdentType::StructField(_, _) => unimplemented!(),
             &IdentType::Const(_) => unimplemented!(),
 
             &IdentType::Enum(_) | &IdentType::EnumValue(_, _) => unreachable!(),
+            &IdentType::Module(_) => unreachable!(),
             &IdentType::Fct(_) | &IdentType::FctType(_, _) => unreachable!(),
             &IdentType::Class(_) | &IdentType::ClassType(_, _) => unreachable!(),
             &IdentType::TypeParam(_) | &IdentType::TypeParamStaticMethod(_, _) => unreachable!(),
@@ -977,6 +1500,7 @@ impl<'a, 'ast> AstBytecodeGen<'a, 'ast> {
             }
 
             CallType::Trait(_, _) | CallType::TraitStatic(_, _, _) => unimplemented!(),
+            CallType::Lambda(_) | CallType::EnumValue(_, _) => unimplemented!(),
             CallType::Intrinsic(_) => unreachable!(),
         };
 
@@ -1829,6 +2353,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gen_method_call_direct() {
+        gen(
+            "
+            class Foo { fun g(a: Int) -> Int { return a; } }
+            fun f(foo: Foo) -> Int { return foo.g(1); }
+            ",
+            |vm, fct| {
+                let fct_id = vm.cls_method_by_name("Foo", "g", false).unwrap();
+                let expected = vec![
+                    MovPtr(r(2), r(0)),
+                    ConstInt(r(3), 1),
+                    InvokeDirectInt(r(1), fct_id, r(2), 2),
+                    RetInt(r(1)),
+                ];
+                assert_eq!(expected, fct.code());
+            },
+        );
+    }
+
+    #[test]
+    fn gen_method_call_virtual() {
+        gen(
+            "
+            @open class Foo { @open fun g() {} }
+            fun f(foo: Foo) { foo.g(); }
+            ",
+            |vm, fct| {
+                let fct_id = vm.cls_method_by_name("Foo", "g", false).unwrap();
+                let expected = vec![
+                    MovPtr(r(1), r(0)),
+                    InvokeVirtualVoid(fct_id, r(1), 1),
+                    RetVoid,
+                ];
+                assert_eq!(expected, fct.code());
+            },
+        );
+    }
+
+    #[test]
+    fn gen_delegation() {
+        test::parse(
+            "@open class Foo(let a: Int) class Bar(b: Int): Foo(b)",
+            |vm| {
+                let ctor_id = vm.ctor_by_name("Bar");
+                let tp = TypeList::empty();
+                let fct = astgen::generate(vm, ctor_id, &tp, &tp);
+
+                let parent_id = vm.ctor_by_name("Foo");
+                let expected = vec![
+                    MovPtr(r(2), r(0)),
+                    MovInt(r(3), r(1)),
+                    InvokeDirectVoid(parent_id, r(2), 2),
+                    RetVoid,
+                ];
+                assert_eq!(expected, fct.code());
+            },
+        );
+    }
+
     #[test]
     fn gen_new_object() {
         gen("fun f() -> Object { return Object(); }", |vm, fct| {
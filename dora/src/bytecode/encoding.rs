@@ -1,11 +1,21 @@
+use std::collections::HashMap;
+use std::mem;
+
 use crate::class::ClassDefId;
 use crate::field::FieldId;
 use crate::vm::{FctId, GlobalId};
 
-use crate::bytecode::generate::Register;
+use crate::bytecode::generate::{BytecodeIdx, Register, StrConstPoolIdx};
 use crate::bytecode::opcode::Bytecode;
 
-#[derive(Copy, Clone)]
+// Every instruction starts with its opcode followed by its operands.
+// Registers, ids and counts take a single byte each, unless one of them
+// does not fit: then the instruction is prefixed with `Wide` and opcode
+// and these operands take four bytes each. Immediates and jump offsets
+// always have a fixed size, jump offsets are relative to the start of
+// the instruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
 pub enum BytecodeInst {
     Wide,
 
@@ -82,9 +92,44 @@ pub enum BytecodeInst {
     TestLtInt,
     TestLeInt,
 
+    TestEqLong,
+    TestNeLong,
+
+    TestEqBool,
+    TestNeBool,
+
+    TestEqByte,
+    TestNeByte,
+    TestGtByte,
+    TestGeByte,
+    TestLtByte,
+    TestLeByte,
+
+    TestEqChar,
+    TestNeChar,
+    TestGtChar,
+    TestGeChar,
+    TestLtChar,
+    TestLeChar,
+
+    TestEqFloat,
+    TestNeFloat,
+    TestGtFloat,
+    TestGeFloat,
+    TestLtFloat,
+    TestLeFloat,
+
+    TestEqDouble,
+    TestNeDouble,
+    TestGtDouble,
+    TestGeDouble,
+    TestLtDouble,
+    TestLeDouble,
+
     JumpIfFalse,
     JumpIfTrue,
     Jump,
+    JumpTable,
 
     InvokeDirectVoid,
     InvokeDirectBool,
@@ -132,17 +177,53 @@ pub enum BytecodeInst {
     RetVoid,
 }
 
+impl BytecodeInst {
+    pub fn from_u32(value: u32) -> Option<BytecodeInst> {
+        if value <= BytecodeInst::RetVoid as u32 {
+            Some(unsafe { mem::transmute(value as u8) })
+        } else {
+            None
+        }
+    }
+}
+
+pub fn encode(code: &[Bytecode]) -> Vec<u8> {
+    encode_with_offsets(code).0
+}
+
+// Also returns the byte offset of every instruction.
+pub fn encode_with_offsets(code: &[Bytecode]) -> (Vec<u8>, Vec<usize>) {
+    let mut gen = BytecodeStreamGenerator::new();
+
+    for inst in code {
+        gen.emit(inst);
+    }
+
+    let offsets = gen.offsets.clone();
+    (gen.finish(), offsets)
+}
+
 pub struct BytecodeStreamGenerator {
     data: Vec<u8>,
+    // byte offset of every emitted instruction
+    offsets: Vec<usize>,
+    // (instruction start, offset position, target) for every jump
+    jumps: Vec<(usize, usize, BytecodeIdx)>,
 }
 
 impl BytecodeStreamGenerator {
     pub fn new() -> BytecodeStreamGenerator {
-        BytecodeStreamGenerator { data: Vec::new() }
+        BytecodeStreamGenerator {
+            data: Vec::new(),
+            offsets: Vec::new(),
+            jumps: Vec::new(),
+        }
     }
 
-    pub fn emit(&mut self, bytecode: Bytecode) {
-        match bytecode {
+    pub fn emit(&mut self, bytecode: &Bytecode) {
+        self.offsets.push(self.data.len());
+
+        match *bytecode {
             Bytecode::AddInt(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::AddInt, dest, lhs, rhs)
             }
@@ -155,7 +236,6 @@ impl BytecodeStreamGenerator {
             Bytecode::AddDouble(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::AddDouble, dest, lhs, rhs)
             }
-
             Bytecode::SubInt(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::SubInt, dest, lhs, rhs)
             }
@@ -178,7 +258,6 @@ impl BytecodeStreamGenerator {
                 self.emit_reg3(BytecodeInst::XorInt, dest, lhs, rhs)
             }
             Bytecode::NotBool(dest, src) => self.emit_reg2(BytecodeInst::NotBool, dest, src),
-
             Bytecode::ShlInt(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::ShlInt, dest, lhs, rhs)
             }
@@ -188,16 +267,14 @@ impl BytecodeStreamGenerator {
             Bytecode::SarInt(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::SarInt, dest, lhs, rhs)
             }
-
             Bytecode::MovBool(dest, src) => self.emit_reg2(BytecodeInst::MovBool, dest, src),
             Bytecode::MovByte(dest, src) => self.emit_reg2(BytecodeInst::MovByte, dest, src),
             Bytecode::MovChar(dest, src) => self.emit_reg2(BytecodeInst::MovChar, dest, src),
             Bytecode::MovInt(dest, src) => self.emit_reg2(BytecodeInst::MovInt, dest, src),
             Bytecode::MovLong(dest, src) => self.emit_reg2(BytecodeInst::MovLong, dest, src),
-            Bytecode::MovFloat(dest, src) => self.emit_reg2(BytecodeInst::MovDouble, dest, src),
+            Bytecode::MovFloat(dest, src) => self.emit_reg2(BytecodeInst::MovFloat, dest, src),
             Bytecode::MovDouble(dest, src) => self.emit_reg2(BytecodeInst::MovDouble, dest, src),
             Bytecode::MovPtr(dest, src) => self.emit_reg2(BytecodeInst::MovPtr, dest, src),
-
             Bytecode::LoadFieldBool(dest, obj, cid, fid) => {
                 self.emit_load_field(BytecodeInst::LoadFieldBool, dest, obj, cid, fid)
             }
@@ -222,7 +299,6 @@ impl BytecodeStreamGenerator {
             Bytecode::LoadFieldPtr(dest, obj, cid, fid) => {
                 self.emit_load_field(BytecodeInst::LoadFieldPtr, dest, obj, cid, fid)
             }
-
             Bytecode::LoadGlobalBool(dest, gid) => {
                 self.emit_load_global(BytecodeInst::LoadGlobalBool, dest, gid)
             }
@@ -247,7 +323,6 @@ impl BytecodeStreamGenerator {
             Bytecode::LoadGlobalPtr(dest, gid) => {
                 self.emit_load_global(BytecodeInst::LoadGlobalPtr, dest, gid)
             }
-
             Bytecode::ConstNil(dest) => self.emit_reg1(BytecodeInst::ConstNil, dest),
             Bytecode::ConstTrue(dest) => self.emit_reg1(BytecodeInst::ConstTrue, dest),
             Bytecode::ConstFalse(dest) => self.emit_reg1(BytecodeInst::ConstFalse, dest),
@@ -256,21 +331,39 @@ impl BytecodeStreamGenerator {
             Bytecode::ConstZeroLong(dest) => self.emit_reg1(BytecodeInst::ConstZeroLong, dest),
             Bytecode::ConstZeroFloat(dest) => self.emit_reg1(BytecodeInst::ConstZeroFloat, dest),
             Bytecode::ConstZeroDouble(dest) => self.emit_reg1(BytecodeInst::ConstZeroDouble, dest),
-            Bytecode::ConstChar(_, _) => unimplemented!(),
-            Bytecode::ConstByte(_, _) => unimplemented!(),
-            Bytecode::ConstInt(_, _) => unimplemented!(),
-            Bytecode::ConstLong(_, _) => unimplemented!(),
-            Bytecode::ConstFloat(_, _) => unimplemented!(),
-            Bytecode::ConstDouble(_, _) => unimplemented!(),
-            Bytecode::ConstString(_, _) => unimplemented!(),
-
+            Bytecode::ConstChar(dest, value) => {
+                self.emit_const(BytecodeInst::ConstChar, dest, &(value as u32).to_le_bytes())
+            }
+            Bytecode::ConstByte(dest, value) => {
+                self.emit_const(BytecodeInst::ConstByte, dest, &[value])
+            }
+            Bytecode::ConstInt(dest, value) => {
+                self.emit_const(BytecodeInst::ConstInt, dest, &value.to_le_bytes())
+            }
+            Bytecode::ConstLong(dest, value) => {
+                self.emit_const(BytecodeInst::ConstLong, dest, &value.to_le_bytes())
+            }
+            Bytecode::ConstFloat(dest, value) => self.emit_const(
+                BytecodeInst::ConstFloat,
+                dest,
+                &value.to_bits().to_le_bytes(),
+            ),
+            Bytecode::ConstDouble(dest, value) => self.emit_const(
+                BytecodeInst::ConstDouble,
+                dest,
+                &value.to_bits().to_le_bytes(),
+            ),
+            Bytecode::ConstString(dest, idx) => self.emit_values(&[
+                BytecodeInst::ConstString as u32,
+                dest.to_usize() as u32,
+                idx.0 as u32,
+            ]),
             Bytecode::TestEqPtr(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::TestEqPtr, dest, lhs, rhs)
             }
             Bytecode::TestNePtr(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::TestNePtr, dest, lhs, rhs)
             }
-
             Bytecode::TestEqInt(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::TestEqInt, dest, lhs, rhs)
             }
@@ -289,101 +382,183 @@ impl BytecodeStreamGenerator {
             Bytecode::TestLeInt(dest, lhs, rhs) => {
                 self.emit_reg3(BytecodeInst::TestLeInt, dest, lhs, rhs)
             }
-
-            Bytecode::JumpIfFalse(cond, _) => {
-                self.emit_cond_jmp(BytecodeInst::JumpIfFalse, cond, 0)
+            Bytecode::TestEqLong(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestEqLong, dest, lhs, rhs)
             }
-            Bytecode::JumpIfTrue(cond, _) => self.emit_cond_jmp(BytecodeInst::JumpIfFalse, cond, 0),
-            Bytecode::Jump(_) => self.emit_jmp(BytecodeInst::Jump, 0),
-
-            Bytecode::InvokeDirectVoid(fid, opnd, cnt) => {
-                self.emit_fct_void(BytecodeInst::InvokeDirectVoid, fid, opnd, cnt)
+            Bytecode::TestNeLong(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestNeLong, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectBool(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectBool, dest, fid, opnd, cnt)
+            Bytecode::TestEqBool(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestEqBool, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectByte(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectByte, dest, fid, opnd, cnt)
+            Bytecode::TestNeBool(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestNeBool, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectChar(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectChar, dest, fid, opnd, cnt)
+            Bytecode::TestEqByte(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestEqByte, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectInt(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectInt, dest, fid, opnd, cnt)
+            Bytecode::TestNeByte(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestNeByte, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectLong(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectLong, dest, fid, opnd, cnt)
+            Bytecode::TestGtByte(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGtByte, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectFloat(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectFloat, dest, fid, opnd, cnt)
+            Bytecode::TestGeByte(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGeByte, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectDouble(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectDouble, dest, fid, opnd, cnt)
+            Bytecode::TestLtByte(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLtByte, dest, lhs, rhs)
             }
-            Bytecode::InvokeDirectPtr(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeDirectPtr, dest, fid, opnd, cnt)
+            Bytecode::TestLeByte(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLeByte, dest, lhs, rhs)
             }
-
-            Bytecode::InvokeVirtualVoid(fid, opnd, cnt) => {
-                self.emit_fct_void(BytecodeInst::InvokeVirtualVoid, fid, opnd, cnt)
+            Bytecode::TestEqChar(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestEqChar, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualBool(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualBool, dest, fid, opnd, cnt)
+            Bytecode::TestNeChar(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestNeChar, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualByte(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualByte, dest, fid, opnd, cnt)
+            Bytecode::TestGtChar(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGtChar, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualChar(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualChar, dest, fid, opnd, cnt)
+            Bytecode::TestGeChar(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGeChar, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualInt(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualInt, dest, fid, opnd, cnt)
+            Bytecode::TestLtChar(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLtChar, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualLong(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualLong, dest, fid, opnd, cnt)
+            Bytecode::TestLeChar(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLeChar, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualFloat(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualFloat, dest, fid, opnd, cnt)
+            Bytecode::TestEqFloat(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestEqFloat, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualDouble(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualDouble, dest, fid, opnd, cnt)
+            Bytecode::TestNeFloat(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestNeFloat, dest, lhs, rhs)
             }
-            Bytecode::InvokeVirtualPtr(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeVirtualPtr, dest, fid, opnd, cnt)
+            Bytecode::TestGtFloat(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGtFloat, dest, lhs, rhs)
             }
-
-            Bytecode::InvokeStaticVoid(fid, opnd, cnt) => {
-                self.emit_fct_void(BytecodeInst::InvokeStaticVoid, fid, opnd, cnt)
+            Bytecode::TestGeFloat(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGeFloat, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticBool(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestLtFloat(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLtFloat, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticByte(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestLeFloat(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLeFloat, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticChar(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestEqDouble(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestEqDouble, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticInt(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestNeDouble(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestNeDouble, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticLong(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestGtDouble(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGtDouble, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticFloat(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestGeDouble(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestGeDouble, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticDouble(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestLtDouble(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLtDouble, dest, lhs, rhs)
             }
-            Bytecode::InvokeStaticPtr(dest, fid, opnd, cnt) => {
-                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, opnd, cnt)
+            Bytecode::TestLeDouble(dest, lhs, rhs) => {
+                self.emit_reg3(BytecodeInst::TestLeDouble, dest, lhs, rhs)
+            }
+            Bytecode::JumpIfFalse(cond, target) => {
+                self.emit_cond_jmp(BytecodeInst::JumpIfFalse, cond, target)
+            }
+            Bytecode::JumpIfTrue(cond, target) => {
+                self.emit_cond_jmp(BytecodeInst::JumpIfTrue, cond, target)
+            }
+            Bytecode::Jump(target) => self.emit_jmp(BytecodeInst::Jump, target),
+            Bytecode::JumpTable(opnd, ref targets) => {
+                self.emit_jump_table(BytecodeInst::JumpTable, opnd, targets)
+            }
+            Bytecode::InvokeDirectVoid(fid, start, cnt) => {
+                self.emit_fct_void(BytecodeInst::InvokeDirectVoid, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectBool(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectBool, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectByte(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectByte, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectChar(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectChar, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectInt(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectInt, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectLong(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectLong, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectFloat(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectFloat, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectDouble(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectDouble, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeDirectPtr(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeDirectPtr, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualVoid(fid, start, cnt) => {
+                self.emit_fct_void(BytecodeInst::InvokeVirtualVoid, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualBool(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualBool, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualByte(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualByte, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualChar(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualChar, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualInt(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualInt, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualLong(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualLong, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualFloat(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualFloat, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualDouble(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualDouble, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeVirtualPtr(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeVirtualPtr, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticVoid(fid, start, cnt) => {
+                self.emit_fct_void(BytecodeInst::InvokeStaticVoid, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticBool(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticBool, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticByte(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticByte, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticChar(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticChar, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticInt(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticInt, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticLong(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticLong, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticFloat(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticFloat, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticDouble(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticDouble, dest, fid, start, cnt)
+            }
+            Bytecode::InvokeStaticPtr(dest, fid, start, cnt) => {
+                self.emit_fct(BytecodeInst::InvokeStaticPtr, dest, fid, start, cnt)
             }
-
             Bytecode::NewObject(dest, cid) => self.emit_new(BytecodeInst::NewObject, dest, cid),
-
             Bytecode::Throw(src) => self.emit_reg1(BytecodeInst::Throw, src),
-
             Bytecode::RetBool(src) => self.emit_reg1(BytecodeInst::RetBool, src),
             Bytecode::RetByte(src) => self.emit_reg1(BytecodeInst::RetByte, src),
             Bytecode::RetChar(src) => self.emit_reg1(BytecodeInst::RetChar, src),
@@ -392,7 +567,6 @@ impl BytecodeStreamGenerator {
             Bytecode::RetFloat(src) => self.emit_reg1(BytecodeInst::RetFloat, src),
             Bytecode::RetDouble(src) => self.emit_reg1(BytecodeInst::RetDouble, src),
             Bytecode::RetPtr(src) => self.emit_reg1(BytecodeInst::RetPtr, src),
-
             Bytecode::RetVoid => self.emit_op(BytecodeInst::RetVoid),
         }
     }
@@ -417,6 +591,12 @@ impl BytecodeStreamGenerator {
         self.emit_values(&values);
     }
 
+    fn emit_const(&mut self, inst: BytecodeInst, r1: Register, value: &[u8]) {
+        let values = [inst as u32, r1.to_usize() as u32];
+        self.emit_values(&values);
+        self.data.extend_from_slice(value);
+    }
+
     fn emit_new(&mut self, inst: BytecodeInst, r1: Register, cid: ClassDefId) {
         let values = [inst as u32, r1.to_usize() as u32, cid.to_usize() as u32];
         self.emit_values(&values);
@@ -471,6 +651,33 @@ impl BytecodeStreamGenerator {
         self.emit_values(&values);
     }
 
+    fn emit_cond_jmp(&mut self, inst: BytecodeInst, cond: Register, target: BytecodeIdx) {
+        let values = [inst as u32, cond.to_usize() as u32];
+        self.emit_values(&values);
+        self.emit_offset(target);
+    }
+
+    fn emit_jmp(&mut self, inst: BytecodeInst, target: BytecodeIdx) {
+        let values = [inst as u32];
+        self.emit_values(&values);
+        self.emit_offset(target);
+    }
+
+    fn emit_jump_table(&mut self, inst: BytecodeInst, opnd: Register, targets: &[BytecodeIdx]) {
+        let values = [inst as u32, opnd.to_usize() as u32, targets.len() as u32];
+        self.emit_values(&values);
+
+        for &target in targets {
+            self.emit_offset(target);
+        }
+    }
+
+    fn emit_offset(&mut self, target: BytecodeIdx) {
+        let start = *self.offsets.last().unwrap();
+        self.jumps.push((start, self.data.len(), target));
+        self.emit_u32(0);
+    }
+
     fn emit_values(&mut self, values: &[u32]) {
         if is_wide(values) {
             self.emit_wide();
@@ -492,50 +699,835 @@ impl BytecodeStreamGenerator {
         self.data.push(value);
     }
 
-    fn emit_cond_jmp(&mut self, inst: BytecodeInst, cond: Register, offset: i32) {
-        if is_wide_u32(cond.to_usize() as u32) || is_wide_i32(offset) {
-            self.emit_wide();
-            self.emit_u32(inst as u32);
-            self.emit_u32(cond.to_usize() as u32);
-            self.emit_u32(offset as u32);
-        } else {
-            self.emit_u8(inst as u8);
-            self.emit_u8(cond.to_usize() as u8);
-            self.emit_u8(offset as u8);
+    fn emit_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        // jumps may target the end of the function
+        self.offsets.push(self.data.len());
+
+        for &(start, pos, target) in &self.jumps {
+            let offset = (self.offsets[target.0] as isize - start as isize) as i32;
+            self.data[pos..pos + 4].copy_from_slice(&offset.to_le_bytes());
         }
+
+        self.data
     }
+}
 
-    fn emit_jmp(&mut self, inst: BytecodeInst, offset: i32) {
-        if is_wide_i32(offset) {
-            self.emit_wide();
-            self.emit_u32(inst as u32);
-            self.emit_u32(offset as u32);
+fn is_wide(values: &[u32]) -> bool {
+    values.iter().any(|&val| val > u8::max_value() as u32)
+}
+
+pub struct BytecodeReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    wide: bool,
+}
+
+impl<'a> BytecodeReader<'a> {
+    pub fn new(data: &'a [u8]) -> BytecodeReader<'a> {
+        BytecodeReader {
+            data,
+            pos: 0,
+            wide: false,
+        }
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn set_pos(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    pub fn has_more(&self) -> bool {
+        self.pos < self.data.len()
+    }
+
+    pub fn read_opcode(&mut self) -> BytecodeInst {
+        let mut opcode = self.read_u8() as u32;
+        self.wide = opcode == BytecodeInst::Wide as u32;
+
+        if self.wide {
+            opcode = self.read_u32();
+        }
+
+        BytecodeInst::from_u32(opcode).expect("invalid opcode")
+    }
+
+    pub fn read_register(&mut self) -> Register {
+        Register(self.read_index())
+    }
+
+    pub fn read_fct_id(&mut self) -> FctId {
+        self.read_index().into()
+    }
+
+    pub fn read_class_def_id(&mut self) -> ClassDefId {
+        self.read_index().into()
+    }
+
+    pub fn read_field_id(&mut self) -> FieldId {
+        self.read_index().into()
+    }
+
+    pub fn read_global_id(&mut self) -> GlobalId {
+        (self.read_index() as u32).into()
+    }
+
+    pub fn read_index(&mut self) -> usize {
+        if self.wide {
+            self.read_u32() as usize
         } else {
-            self.emit_u8(inst as u8);
-            self.emit_u8(offset as u8);
+            self.read_u8() as usize
         }
     }
 
-    fn emit_u32(&mut self, value: u32) {
-        self.data.push((value & 0xFF) as u8);
-        self.data.push(((value >> 8) & 0xFF) as u8);
-        self.data.push(((value >> 16) & 0xFF) as u8);
-        self.data.push(((value >> 24) & 0xFF) as u8);
+    pub fn read_offset(&mut self) -> i32 {
+        self.read_u32() as i32
     }
 
-    pub fn finish(self) -> Vec<u8> {
-        self.data
+    pub fn read_u8(&mut self) -> u8 {
+        let value = self.data[self.pos];
+        self.pos += 1;
+        value
     }
-}
 
-fn is_wide(values: &[u32]) -> bool {
-    values.iter().any(|&val| val > u8::max_value() as u32)
+    pub fn read_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.data[self.pos..self.pos + 4]);
+        self.pos += 4;
+        u32::from_le_bytes(bytes)
+    }
+
+    pub fn read_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.data[self.pos..self.pos + 8]);
+        self.pos += 8;
+        u64::from_le_bytes(bytes)
+    }
 }
 
-fn is_wide_u32(value: u32) -> bool {
-    value > u8::max_value() as u32
+pub fn decode(data: &[u8]) -> Vec<Bytecode> {
+    let mut reader = BytecodeReader::new(data);
+    let mut code = Vec::new();
+    let mut indices = HashMap::new();
+
+    // jump targets are byte offsets until all instructions are decoded
+    let target =
+        |start: usize, offset: i32| BytecodeIdx((start as isize + offset as isize) as usize);
+
+    while reader.has_more() {
+        let start = reader.pos();
+        indices.insert(start, code.len());
+
+        let inst = match reader.read_opcode() {
+            BytecodeInst::Wide => unreachable!(),
+            BytecodeInst::AddInt => Bytecode::AddInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::AddLong => Bytecode::AddLong(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::AddFloat => Bytecode::AddFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::AddDouble => Bytecode::AddDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::SubInt => Bytecode::SubInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::NegInt => {
+                Bytecode::NegInt(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::NegLong => {
+                Bytecode::NegLong(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MulInt => Bytecode::MulInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::DivInt => Bytecode::DivInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::ModInt => Bytecode::ModInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::AndInt => Bytecode::AndInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::OrInt => Bytecode::OrInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::XorInt => Bytecode::XorInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::NotBool => {
+                Bytecode::NotBool(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::ShlInt => Bytecode::ShlInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::ShrInt => Bytecode::ShrInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::SarInt => Bytecode::SarInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::MovBool => {
+                Bytecode::MovBool(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovByte => {
+                Bytecode::MovByte(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovChar => {
+                Bytecode::MovChar(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovInt => {
+                Bytecode::MovInt(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovLong => {
+                Bytecode::MovLong(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovFloat => {
+                Bytecode::MovFloat(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovDouble => {
+                Bytecode::MovDouble(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::MovPtr => {
+                Bytecode::MovPtr(reader.read_register(), reader.read_register())
+            }
+            BytecodeInst::LoadFieldBool => Bytecode::LoadFieldBool(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldByte => Bytecode::LoadFieldByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldChar => Bytecode::LoadFieldChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldInt => Bytecode::LoadFieldInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldLong => Bytecode::LoadFieldLong(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldFloat => Bytecode::LoadFieldFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldDouble => Bytecode::LoadFieldDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadFieldPtr => Bytecode::LoadFieldPtr(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_class_def_id(),
+                reader.read_field_id(),
+            ),
+            BytecodeInst::LoadGlobalBool => {
+                Bytecode::LoadGlobalBool(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalByte => {
+                Bytecode::LoadGlobalByte(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalChar => {
+                Bytecode::LoadGlobalChar(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalInt => {
+                Bytecode::LoadGlobalInt(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalLong => {
+                Bytecode::LoadGlobalLong(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalFloat => {
+                Bytecode::LoadGlobalFloat(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalDouble => {
+                Bytecode::LoadGlobalDouble(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::LoadGlobalPtr => {
+                Bytecode::LoadGlobalPtr(reader.read_register(), reader.read_global_id())
+            }
+            BytecodeInst::ConstNil => Bytecode::ConstNil(reader.read_register()),
+            BytecodeInst::ConstTrue => Bytecode::ConstTrue(reader.read_register()),
+            BytecodeInst::ConstFalse => Bytecode::ConstFalse(reader.read_register()),
+            BytecodeInst::ConstZeroByte => Bytecode::ConstZeroByte(reader.read_register()),
+            BytecodeInst::ConstZeroInt => Bytecode::ConstZeroInt(reader.read_register()),
+            BytecodeInst::ConstZeroLong => Bytecode::ConstZeroLong(reader.read_register()),
+            BytecodeInst::ConstZeroFloat => Bytecode::ConstZeroFloat(reader.read_register()),
+            BytecodeInst::ConstZeroDouble => Bytecode::ConstZeroDouble(reader.read_register()),
+            BytecodeInst::ConstChar => {
+                let dest = reader.read_register();
+                let value = reader.read_u32();
+                Bytecode::ConstChar(dest, std::char::from_u32(value).expect("invalid char"))
+            }
+            BytecodeInst::ConstByte => {
+                Bytecode::ConstByte(reader.read_register(), reader.read_u8())
+            }
+            BytecodeInst::ConstInt => Bytecode::ConstInt(reader.read_register(), reader.read_u32()),
+            BytecodeInst::ConstLong => {
+                Bytecode::ConstLong(reader.read_register(), reader.read_u64())
+            }
+            BytecodeInst::ConstFloat => {
+                Bytecode::ConstFloat(reader.read_register(), f32::from_bits(reader.read_u32()))
+            }
+            BytecodeInst::ConstDouble => {
+                Bytecode::ConstDouble(reader.read_register(), f64::from_bits(reader.read_u64()))
+            }
+            BytecodeInst::ConstString => {
+                Bytecode::ConstString(reader.read_register(), StrConstPoolIdx(reader.read_index()))
+            }
+            BytecodeInst::TestEqPtr => Bytecode::TestEqPtr(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNePtr => Bytecode::TestNePtr(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqInt => Bytecode::TestEqInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeInt => Bytecode::TestNeInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGtInt => Bytecode::TestGtInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGeInt => Bytecode::TestGeInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLtInt => Bytecode::TestLtInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLeInt => Bytecode::TestLeInt(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqLong => Bytecode::TestEqLong(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeLong => Bytecode::TestNeLong(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqBool => Bytecode::TestEqBool(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeBool => Bytecode::TestNeBool(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqByte => Bytecode::TestEqByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeByte => Bytecode::TestNeByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGtByte => Bytecode::TestGtByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGeByte => Bytecode::TestGeByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLtByte => Bytecode::TestLtByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLeByte => Bytecode::TestLeByte(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqChar => Bytecode::TestEqChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeChar => Bytecode::TestNeChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGtChar => Bytecode::TestGtChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGeChar => Bytecode::TestGeChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLtChar => Bytecode::TestLtChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLeChar => Bytecode::TestLeChar(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqFloat => Bytecode::TestEqFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeFloat => Bytecode::TestNeFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGtFloat => Bytecode::TestGtFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGeFloat => Bytecode::TestGeFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLtFloat => Bytecode::TestLtFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLeFloat => Bytecode::TestLeFloat(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestEqDouble => Bytecode::TestEqDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestNeDouble => Bytecode::TestNeDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGtDouble => Bytecode::TestGtDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestGeDouble => Bytecode::TestGeDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLtDouble => Bytecode::TestLtDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::TestLeDouble => Bytecode::TestLeDouble(
+                reader.read_register(),
+                reader.read_register(),
+                reader.read_register(),
+            ),
+            BytecodeInst::JumpIfFalse => {
+                let cond = reader.read_register();
+                Bytecode::JumpIfFalse(cond, target(start, reader.read_offset()))
+            }
+            BytecodeInst::JumpIfTrue => {
+                let cond = reader.read_register();
+                Bytecode::JumpIfTrue(cond, target(start, reader.read_offset()))
+            }
+            BytecodeInst::Jump => Bytecode::Jump(target(start, reader.read_offset())),
+            BytecodeInst::JumpTable => {
+                let opnd = reader.read_register();
+                let len = reader.read_index();
+                let targets = (0..len)
+                    .map(|_| target(start, reader.read_offset()))
+                    .collect();
+                Bytecode::JumpTable(opnd, targets)
+            }
+            BytecodeInst::InvokeDirectVoid => Bytecode::InvokeDirectVoid(
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectBool => Bytecode::InvokeDirectBool(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectByte => Bytecode::InvokeDirectByte(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectChar => Bytecode::InvokeDirectChar(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectInt => Bytecode::InvokeDirectInt(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectLong => Bytecode::InvokeDirectLong(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectFloat => Bytecode::InvokeDirectFloat(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectDouble => Bytecode::InvokeDirectDouble(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeDirectPtr => Bytecode::InvokeDirectPtr(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualVoid => Bytecode::InvokeVirtualVoid(
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualBool => Bytecode::InvokeVirtualBool(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualByte => Bytecode::InvokeVirtualByte(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualChar => Bytecode::InvokeVirtualChar(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualInt => Bytecode::InvokeVirtualInt(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualLong => Bytecode::InvokeVirtualLong(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualFloat => Bytecode::InvokeVirtualFloat(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualDouble => Bytecode::InvokeVirtualDouble(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeVirtualPtr => Bytecode::InvokeVirtualPtr(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticVoid => Bytecode::InvokeStaticVoid(
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticBool => Bytecode::InvokeStaticBool(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticByte => Bytecode::InvokeStaticByte(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticChar => Bytecode::InvokeStaticChar(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticInt => Bytecode::InvokeStaticInt(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticLong => Bytecode::InvokeStaticLong(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticFloat => Bytecode::InvokeStaticFloat(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticDouble => Bytecode::InvokeStaticDouble(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::InvokeStaticPtr => Bytecode::InvokeStaticPtr(
+                reader.read_register(),
+                reader.read_fct_id(),
+                reader.read_register(),
+                reader.read_index(),
+            ),
+            BytecodeInst::NewObject => {
+                Bytecode::NewObject(reader.read_register(), reader.read_class_def_id())
+            }
+            BytecodeInst::Throw => Bytecode::Throw(reader.read_register()),
+            BytecodeInst::RetBool => Bytecode::RetBool(reader.read_register()),
+            BytecodeInst::RetByte => Bytecode::RetByte(reader.read_register()),
+            BytecodeInst::RetChar => Bytecode::RetChar(reader.read_register()),
+            BytecodeInst::RetInt => Bytecode::RetInt(reader.read_register()),
+            BytecodeInst::RetLong => Bytecode::RetLong(reader.read_register()),
+            BytecodeInst::RetFloat => Bytecode::RetFloat(reader.read_register()),
+            BytecodeInst::RetDouble => Bytecode::RetDouble(reader.read_register()),
+            BytecodeInst::RetPtr => Bytecode::RetPtr(reader.read_register()),
+            BytecodeInst::RetVoid => Bytecode::RetVoid,
+        };
+
+        code.push(inst);
+    }
+
+    indices.insert(reader.pos(), code.len());
+
+    for inst in &mut code {
+        match *inst {
+            Bytecode::JumpIfFalse(_, ref mut target)
+            | Bytecode::JumpIfTrue(_, ref mut target)
+            | Bytecode::Jump(ref mut target) => {
+                *target = BytecodeIdx(indices[&target.0]);
+            }
+
+            Bytecode::JumpTable(_, ref mut targets) => {
+                for target in targets.iter_mut() {
+                    *target = BytecodeIdx(indices[&target.0]);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    code
 }
 
-fn is_wide_i32(value: i32) -> bool {
-    i8::min_value() as i32 <= value && value <= i8::max_value() as i32
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, encode_with_offsets, BytecodeInst};
+    use crate::bytecode::astgen;
+    use crate::bytecode::generate::{BytecodeIdx, Register};
+    use crate::bytecode::opcode::Bytecode::*;
+    use crate::test;
+    use crate::ty::TypeList;
+
+    fn roundtrip(code: &'static str) {
+        test::parse(code, |vm| {
+            let fct_id = vm.fct_by_name("f").expect("no function `f`.");
+            let tp = TypeList::empty();
+            let fct = astgen::generate(vm, fct_id, &tp, &tp);
+
+            assert_eq!(fct.code(), &decode(&encode(fct.code()))[..]);
+        })
+    }
+
+    #[test]
+    fn roundtrip_arithmetic() {
+        roundtrip("fun f(a: Int, b: Int) -> Int { (a + b) * (a - b) / a % b << 1 >> 2 >>> 3 }");
+        roundtrip("fun f(a: Int, b: Int) -> Bool { a < b || a >= b && !(a == b) }");
+    }
+
+    #[test]
+    fn roundtrip_consts() {
+        roundtrip("fun f() { let a = 'x'; let b = 17Y; let c = 1000000; let d = 1099511627776L; }");
+        roundtrip("fun f() { let a = 1.5F; let b = 2.5; let c = 0; let d = \"abc\"; }");
+    }
+
+    #[test]
+    fn roundtrip_control_flow() {
+        roundtrip(
+            "fun f(a: Int) -> Int { var i = 0; while i < a { if i == 3 { break; } i = i + 1; } i }",
+        );
+        roundtrip("fun f(a: Int) -> Int { var i = 0; loop { i = i + 1; if i < a { continue; } return i; } }");
+        roundtrip(
+            "enum Foo { A, B, C } fun f(a: Foo) -> Int { match a { Foo::A => 1, Foo::B => 2, Foo::C => 3 } }",
+        );
+    }
+
+    #[test]
+    fn roundtrip_calls() {
+        roundtrip("class Foo(let a: Int) fun f() -> Int { let foo = Foo(1); g(foo.a) } fun g(a: Int) -> Int { a }");
+    }
+
+    #[test]
+    fn encode_wide() {
+        let code = vec![
+            AddInt(Register(300), Register(1), Register(2)),
+            AddInt(Register(3), Register(1), Register(2)),
+            RetVoid,
+        ];
+        let data = encode(&code);
+
+        assert_eq!(BytecodeInst::Wide as u8, data[0]);
+        assert_eq!(BytecodeInst::AddInt as u8, data[17]);
+        assert_eq!(17 + 4 + 1, data.len());
+        assert_eq!(code, decode(&data));
+    }
+
+    #[test]
+    fn encode_jump_offsets() {
+        let code = vec![
+            Jump(BytecodeIdx(2)),
+            JumpIfFalse(Register(0), BytecodeIdx(0)),
+            RetVoid,
+        ];
+        let data = encode(&code);
+
+        assert_eq!(
+            vec![
+                BytecodeInst::Jump as u8,
+                11,
+                0,
+                0,
+                0,
+                BytecodeInst::JumpIfFalse as u8,
+                0,
+                0xFB,
+                0xFF,
+                0xFF,
+                0xFF,
+                BytecodeInst::RetVoid as u8
+            ],
+            data
+        );
+        assert_eq!(code, decode(&data));
+    }
+
+    #[test]
+    fn encode_offsets() {
+        let code = vec![
+            ConstInt(Register(0), 1),
+            ConstInt(Register(1), 300),
+            RetInt(Register(0)),
+        ];
+        let (data, offsets) = encode_with_offsets(&code);
+
+        assert_eq!(vec![0, 6, 12], offsets);
+        assert_eq!(14, data.len());
+    }
 }
@@ -6,6 +6,8 @@ use std::mem;
 
 use crate::mem as cratemem;
 
+use dora_parser::lexer::position::Position;
+
 use crate::bytecode::opcode::Bytecode;
 use crate::class::ClassDefId;
 use crate::field::FieldId;
@@ -65,7 +67,7 @@ impl fmt::Display for BytecodeIdx {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BytecodeType {
     Bool,
     Byte,
@@ -115,7 +117,8 @@ impl From<BuiltinType> for BytecodeType {
             BuiltinType::Long => BytecodeType::Long,
             BuiltinType::Float => BytecodeType::Float,
             BuiltinType::Double => BytecodeType::Double,
-            BuiltinType::Class(_, _) => BytecodeType::Ptr,
+            BuiltinType::Enum(_) => BytecodeType::Int,
+            BuiltinType::Class(_, _) | BuiltinType::EnumObject(_) => BytecodeType::Ptr,
             _ => panic!("BuiltinType cannot converted to BytecodeType"),
         }
     }
@@ -130,12 +133,34 @@ impl fmt::Display for StrConstPoolIdx {
     }
 }
 
+// An exception thrown by an instruction in `start..end` continues at
+// `catch` when it is an instance of exactly `catch_type` (or for any
+// exception without type), the exception is stored in `exception`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExceptionHandler {
+    pub start: BytecodeIdx,
+    pub end: BytecodeIdx,
+    pub catch: BytecodeIdx,
+    pub catch_type: Option<ClassDefId>,
+    pub exception: Option<Register>,
+}
+
+struct UnresolvedHandler {
+    start: Label,
+    end: Label,
+    catch: Label,
+    catch_type: Option<ClassDefId>,
+    exception: Option<Register>,
+}
+
 pub struct BytecodeGenerator {
     code: Vec<Bytecode>,
     labels: Vec<Option<BytecodeIdx>>,
     unresolved_jumps: Vec<(BytecodeIdx, Label)>,
     registers: Vec<BytecodeType>,
     string_pool_map: HashMap<String, StrConstPoolIdx>,
+    positions: Vec<(BytecodeIdx, Position)>,
+    handlers: Vec<UnresolvedHandler>,
 }
 
 impl BytecodeGenerator {
@@ -146,6 +171,19 @@ impl BytecodeGenerator {
             unresolved_jumps: Vec::new(),
             registers: Vec::new(),
             string_pool_map: HashMap::new(),
+            positions: Vec::new(),
+            handlers: Vec::new(),
+        }
+    }
+
+    // all following instructions belong to the source position `pos`
+    pub fn set_position(&mut self, pos: Position) {
+        let idx = BytecodeIdx(self.code.len());
+
+        match self.positions.last_mut() {
+            Some(&mut (last_idx, ref mut last_pos)) if last_idx == idx => *last_pos = pos,
+            Some(&mut (_, last_pos)) if last_pos == pos => {}
+            _ => self.positions.push((idx, pos)),
         }
     }
 
@@ -182,6 +220,25 @@ impl BytecodeGenerator {
         self.labels[lbl.0] = Some(dest);
     }
 
+    // handlers are searched in the order they were added, so inner
+    // handlers need to be added first
+    pub fn add_exception_handler(
+        &mut self,
+        start: Label,
+        end: Label,
+        catch: Label,
+        catch_type: Option<ClassDefId>,
+        exception: Option<Register>,
+    ) {
+        self.handlers.push(UnresolvedHandler {
+            start,
+            end,
+            catch,
+            catch_type,
+            exception,
+        });
+    }
+
     fn dest_label(&self, lbl: Label) -> Option<BytecodeIdx> {
         self.labels[lbl.0]
     }
@@ -414,6 +471,21 @@ impl BytecodeGenerator {
         }
     }
 
+    pub fn emit_jump_table(&mut self, opnd: Register, lbls: &[Label]) {
+        let mut targets = Vec::with_capacity(lbls.len());
+
+        for &lbl in lbls {
+            if let Some(idx) = self.dest_label(lbl) {
+                targets.push(idx);
+            } else {
+                self.unresolved_jumps.push((self.pc(), lbl));
+                targets.push(BytecodeIdx::invalid());
+            }
+        }
+
+        self.code.push(Bytecode::JumpTable(opnd, targets));
+    }
+
     pub fn emit_mod_int(&mut self, dest: Register, lhs: Register, rhs: Register) {
         self.code.push(Bytecode::ModInt(dest, lhs, rhs));
     }
@@ -546,6 +618,118 @@ impl BytecodeGenerator {
         self.code.push(Bytecode::TestLeInt(dest, lhs, rhs));
     }
 
+    pub fn emit_test_eq_long(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestEqLong(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ne_long(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestNeLong(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_eq_bool(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestEqBool(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ne_bool(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestNeBool(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_eq_byte(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestEqByte(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ne_byte(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestNeByte(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_gt_byte(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGtByte(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ge_byte(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGeByte(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_lt_byte(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLtByte(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_le_byte(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLeByte(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_eq_char(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestEqChar(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ne_char(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestNeChar(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_gt_char(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGtChar(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ge_char(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGeChar(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_lt_char(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLtChar(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_le_char(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLeChar(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_eq_float(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestEqFloat(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ne_float(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestNeFloat(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_gt_float(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGtFloat(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ge_float(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGeFloat(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_lt_float(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLtFloat(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_le_float(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLeFloat(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_eq_double(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestEqDouble(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ne_double(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestNeDouble(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_gt_double(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGtDouble(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_ge_double(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestGeDouble(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_lt_double(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLtDouble(dest, lhs, rhs));
+    }
+
+    pub fn emit_test_le_double(&mut self, dest: Register, lhs: Register, rhs: Register) {
+        self.code.push(Bytecode::TestLeDouble(dest, lhs, rhs));
+    }
+
     pub fn emit_load_global_bool(&mut self, dest: Register, gid: GlobalId) {
         self.code.push(Bytecode::LoadGlobalBool(dest, gid));
     }
@@ -864,15 +1048,31 @@ impl BytecodeGenerator {
 
     pub fn generate(mut self) -> BytecodeFunction {
         self.resolve_forward_jumps();
+        let exception_handlers = self.resolve_exception_handlers();
 
         BytecodeFunction {
             code: self.code,
             offset: generate_offset(&self.registers),
             registers: self.registers,
             string_pool: generate_string_pool(self.string_pool_map),
+            positions: self.positions,
+            exception_handlers,
         }
     }
 
+    fn resolve_exception_handlers(&self) -> Vec<ExceptionHandler> {
+        self.handlers
+            .iter()
+            .map(|handler| ExceptionHandler {
+                start: self.dest_label(handler.start).expect("label unresolved"),
+                end: self.dest_label(handler.end).expect("label unresolved"),
+                catch: self.dest_label(handler.catch).expect("label unresolved"),
+                catch_type: handler.catch_type,
+                exception: handler.exception,
+            })
+            .collect()
+    }
+
     fn resolve_forward_jumps(&mut self) {
         let unresolved_jumps = mem::replace(&mut self.unresolved_jumps, Vec::new());
 
@@ -896,6 +1096,15 @@ impl BytecodeGenerator {
                     *dest = lbl_dest;
                 }
 
+                // unresolved targets are recorded in order
+                Bytecode::JumpTable(_, ref mut targets) => {
+                    let dest = targets
+                        .iter_mut()
+                        .find(|dest| dest.is_invalid())
+                        .expect("no unresolved target");
+                    *dest = lbl_dest;
+                }
+
                 _ => unreachable!(),
             }
         }
@@ -927,6 +1136,8 @@ pub struct BytecodeFunction {
     registers: Vec<BytecodeType>,
     string_pool: Vec<String>,
     offset: Vec<i32>,
+    positions: Vec<(BytecodeIdx, Position)>,
+    exception_handlers: Vec<ExceptionHandler>,
 }
 
 impl BytecodeFunction {
@@ -954,6 +1165,30 @@ impl BytecodeFunction {
         *self.offset.get(register.0).expect("offset not found")
     }
 
+    // instructions starting a new source position, ordered by index
+    pub fn positions(&self) -> &[(BytecodeIdx, Position)] {
+        &self.positions
+    }
+
+    pub fn exception_handlers(&self) -> &[ExceptionHandler] {
+        &self.exception_handlers
+    }
+
+    // source position of the instruction, None if no position was recorded
+    pub fn position(&self, idx: BytecodeIdx) -> Option<Position> {
+        let next = self
+            .positions
+            .iter()
+            .position(|&(start, _)| start.0 > idx.0)
+            .unwrap_or_else(|| self.positions.len());
+
+        if next == 0 {
+            None
+        } else {
+            Some(self.positions[next - 1].1)
+        }
+    }
+
     pub fn stacksize(&self) -> i32 {
         match self.offset.last() {
             None => 0,
@@ -1016,6 +1251,14 @@ impl BytecodeFunction {
                     println!("{}: if {} goto {}", btidx, opnd, target)
                 }
                 Bytecode::Jump(dest) => println!("{}: Jump bc#{}", btidx, dest),
+                Bytecode::JumpTable(opnd, ref targets) => {
+                    let targets = targets
+                        .iter()
+                        .map(|target| target.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    println!("{}: JumpTable {} [{}]", btidx, opnd, targets)
+                }
                 Bytecode::ModInt(dest, lhs, rhs) => {
                     println!("{}: {} <-int {} % {}", btidx, dest, lhs, rhs)
                 }
@@ -1094,6 +1337,90 @@ impl BytecodeFunction {
                 Bytecode::TestLeInt(dest, lhs, rhs) => {
                     println!("{}: {} <- {} >=.int {}", btidx, dest, lhs, rhs)
                 }
+                Bytecode::TestEqLong(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} =.long {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestNeLong(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} !=.long {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestEqBool(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} =.bool {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestNeBool(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} !=.bool {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestEqByte(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} =.byte {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestNeByte(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} !=.byte {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGtByte(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >.byte {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGeByte(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >=.byte {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLtByte(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <.byte {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLeByte(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <=.byte {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestEqChar(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} =.char {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestNeChar(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} !=.char {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGtChar(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >.char {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGeChar(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >=.char {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLtChar(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <.char {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLeChar(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <=.char {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestEqFloat(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} =.float {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestNeFloat(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} !=.float {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGtFloat(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >.float {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGeFloat(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >=.float {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLtFloat(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <.float {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLeFloat(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <=.float {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestEqDouble(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} =.double {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestNeDouble(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} !=.double {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGtDouble(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >.double {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestGeDouble(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} >=.double {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLtDouble(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <.double {}", btidx, dest, lhs, rhs)
+                }
+                Bytecode::TestLeDouble(dest, lhs, rhs) => {
+                    println!("{}: {} <- {} <=.double {}", btidx, dest, lhs, rhs)
+                }
                 Bytecode::LoadGlobalBool(dest, gid) => {
                     println!("{}: {} <-bool global {:?}", btidx, dest, gid)
                 }
@@ -1288,5 +1615,12 @@ impl BytecodeFunction {
             }
             btidx = btidx + 1;
         }
+
+        for handler in &self.exception_handlers {
+            println!(
+                "catch {}..{} -> {}: {:?} in {:?}",
+                handler.start, handler.end, handler.catch, handler.catch_type, handler.exception
+            );
+        }
     }
 }
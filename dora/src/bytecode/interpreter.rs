@@ -0,0 +1,1174 @@
+use parking_lot::Mutex;
+use std::cell::Cell;
+use std::mem;
+
+use dora_parser::ast::Expr::*;
+use dora_parser::ast::Stmt::*;
+use dora_parser::ast::*;
+use dora_parser::lexer::position::Position;
+
+use crate::baseline::codegen::{self, ensure_native_stub};
+use crate::baseline::dora_interpreter;
+use crate::baseline::dora_native::{InternalFct, InternalFctDescriptor};
+use crate::bytecode::encoding::{encode_with_offsets, BytecodeInst, BytecodeReader};
+use crate::bytecode::generate::{BytecodeFunction, BytecodeIdx, BytecodeType, Register};
+use crate::class::{ClassDef, ClassDefId};
+use crate::cpu::{FREG_PARAMS, REG_PARAMS};
+use crate::exception;
+use crate::gc::Address;
+use crate::object::{self, Obj, Str};
+use crate::os::signal::Trap;
+use crate::semck::specialize::specialize_class_id_params;
+use crate::size::InstanceSize;
+use crate::threads::{ThreadLocalData, THREAD};
+use crate::ty::{BuiltinType, TypeList};
+use crate::vm::{get_vm, CallType, Fct, FctId, FctKind, FctSrc, IdentType, Intrinsic, VM};
+
+// Checks whether the bytecode generator supports every construct used in
+// the function and whether the interpreter can be entered from compiled code.
+pub fn is_supported<'ast>(
+    vm: &VM<'ast>,
+    fct: &Fct<'ast>,
+    src: &FctSrc,
+    cls_type_params: &TypeList,
+    fct_type_params: &TypeList,
+) -> bool {
+    if cls_type_params.len() > 0 || fct_type_params.len() > 0 {
+        return false;
+    }
+
+    if fct.is_lambda() || !fct.type_params.is_empty() {
+        return false;
+    }
+
+    if fct.has_self() && !src.var_self().ty.is_cls() {
+        return false;
+    }
+
+    if !is_supported_signature(fct) {
+        return false;
+    }
+
+    let check = SupportCheck { vm, fct, src };
+    let block = fct.ast.block.as_ref().unwrap();
+
+    block.stmts.iter().all(|stmt| check.stmt(stmt))
+        && block.expr.as_ref().map_or(true, |expr| check.expr(expr))
+}
+
+fn is_supported_signature(fct: &Fct) -> bool {
+    let mut regs = 0;
+    let mut fregs = 0;
+
+    for &ty in fct.params_with_self() {
+        match bytecode_type(ty) {
+            Some(ty) if ty.mode().is_float() => fregs += 1,
+            Some(_) => regs += 1,
+            None => return false,
+        }
+    }
+
+    regs <= REG_PARAMS.len()
+        && fregs <= FREG_PARAMS.len()
+        && (fct.return_type.is_unit() || bytecode_type(fct.return_type).is_some())
+}
+
+fn bytecode_type(ty: BuiltinType) -> Option<BytecodeType> {
+    match ty {
+        BuiltinType::Bool
+        | BuiltinType::Byte
+        | BuiltinType::Char
+        | BuiltinType::Int
+        | BuiltinType::Long
+        | BuiltinType::Float
+        | BuiltinType::Double
+        | BuiltinType::Enum(_)
+        | BuiltinType::Class(_, _)
+        | BuiltinType::EnumObject(_) => Some(ty.into()),
+        _ => None,
+    }
+}
+
+struct SupportCheck<'a, 'ast: 'a> {
+    vm: &'a VM<'ast>,
+    fct: &'a Fct<'ast>,
+    src: &'a FctSrc,
+}
+
+impl<'a, 'ast> SupportCheck<'a, 'ast> {
+    fn stmt(&self, stmt: &Stmt) -> bool {
+        match *stmt {
+            StmtReturn(ref stmt) => stmt.expr.as_ref().map_or(true, |expr| self.expr(expr)),
+            StmtBreak(_) | StmtContinue(_) => true,
+            StmtExpr(ref stmt) => self.expr(&stmt.expr),
+            StmtVar(ref stmt) => {
+                let var_id = *self.src.map_vars.get(stmt.id).unwrap();
+
+                bytecode_type(self.src.vars[var_id].ty).is_some()
+                    && stmt.expr.as_ref().map_or(true, |expr| self.expr(expr))
+            }
+            StmtWhile(ref stmt) => self.expr(&stmt.cond) && self.stmt(&stmt.block),
+            StmtLoop(ref stmt) => self.stmt(&stmt.block),
+            StmtThrow(ref stmt) => self.expr(&stmt.expr),
+            StmtDo(ref stmt) => {
+                self.stmt(&stmt.do_block)
+                    && stmt
+                        .catch_blocks
+                        .iter()
+                        .all(|catch| self.stmt(&catch.block))
+                    && stmt
+                        .finally_block
+                        .as_ref()
+                        .map_or(true, |finally| self.stmt(&finally.block))
+            }
+            _ => false,
+        }
+    }
+
+    fn expr(&self, expr: &Expr) -> bool {
+        match *expr {
+            ExprUn(ref expr) => self.expr_un(expr),
+            ExprBin(ref expr) => self.expr_bin(expr),
+            ExprDot(ref expr) => self.expr_dot(expr),
+            ExprBlock(ref block) => {
+                block.stmts.iter().all(|stmt| self.stmt(stmt))
+                    && block.expr.as_ref().map_or(true, |expr| self.expr(expr))
+            }
+            ExprIf(ref expr) => {
+                self.is_supported_value(expr.id)
+                    && self.expr(&expr.cond)
+                    && self.expr(&expr.then_block)
+                    && expr
+                        .else_block
+                        .as_ref()
+                        .map_or(true, |expr| self.expr(expr))
+            }
+            ExprMatch(ref expr) => {
+                !self.src.ty(expr.expr.id()).is_enum_object()
+                    && self.is_supported_value(expr.id)
+                    && self.expr(&expr.expr)
+                    && expr.cases.iter().all(|case| self.expr(&case.value))
+            }
+            ExprLitChar(_) | ExprLitInt(_) | ExprLitFloat(_) | ExprLitStr(_) => true,
+            ExprLitBool(_) | ExprSelf(_) | ExprNil(_) => true,
+            ExprIdent(ref ident) => match self.src.map_idents.get(ident.id) {
+                Some(&IdentType::Var(_)) => true,
+                Some(&IdentType::Global(gid)) => {
+                    let glob = self.vm.globals.idx(gid);
+                    let glob = glob.lock();
+
+                    glob.getter.is_none() && bytecode_type(glob.ty).is_some()
+                }
+                _ => false,
+            },
+            ExprPath(ref path) => match self.src.map_idents.get(path.id) {
+                Some(&IdentType::EnumValue(_, _)) => !self.src.ty(path.id).is_enum_object(),
+                _ => false,
+            },
+            ExprCall(ref call) => self.expr_call(call),
+            ExprDelegation(ref expr) => self.expr_delegation(expr),
+            ExprTry(ref expr) => expr.mode.is_normal() && self.expr(&expr.expr),
+            _ => false,
+        }
+    }
+
+    fn is_supported_value(&self, id: NodeId) -> bool {
+        let ty = self.src.ty(id);
+        ty.is_unit() || bytecode_type(ty).is_some()
+    }
+
+    fn expr_un(&self, expr: &ExprUnType) -> bool {
+        match self.intrinsic(expr.id) {
+            Some(Intrinsic::IntPlus)
+            | Some(Intrinsic::LongPlus)
+            | Some(Intrinsic::FloatPlus)
+            | Some(Intrinsic::DoublePlus)
+            | Some(Intrinsic::IntNeg)
+            | Some(Intrinsic::LongNeg)
+            | Some(Intrinsic::BoolNot) => self.expr(&expr.opnd),
+            _ => false,
+        }
+    }
+
+    fn expr_bin(&self, expr: &ExprBinType) -> bool {
+        if expr.op.is_any_assign() {
+            match self.src.map_idents.get(expr.lhs.id()) {
+                Some(&IdentType::Var(_)) if expr.lhs.is_ident() => self.expr(&expr.rhs),
+                _ => false,
+            }
+        } else if expr.op == BinOp::Cmp(CmpOp::Is) || expr.op == BinOp::Cmp(CmpOp::IsNot) {
+            // floats are compared by their bits, which needs conversions
+            !self.src.ty(expr.lhs.id()).is_float() && self.expr(&expr.lhs) && self.expr(&expr.rhs)
+        } else if expr.op == BinOp::Or || expr.op == BinOp::And {
+            self.expr(&expr.lhs) && self.expr(&expr.rhs)
+        } else {
+            match self.intrinsic(expr.id) {
+                Some(Intrinsic::IntAdd)
+                | Some(Intrinsic::IntSub)
+                | Some(Intrinsic::IntMul)
+                | Some(Intrinsic::IntDiv)
+                | Some(Intrinsic::IntMod)
+                | Some(Intrinsic::IntOr)
+                | Some(Intrinsic::IntAnd)
+                | Some(Intrinsic::IntXor)
+                | Some(Intrinsic::IntShl)
+                | Some(Intrinsic::IntShr)
+                | Some(Intrinsic::IntSar)
+                | Some(Intrinsic::IntEq)
+                | Some(Intrinsic::IntCmp)
+                | Some(Intrinsic::BoolEq)
+                | Some(Intrinsic::ByteEq)
+                | Some(Intrinsic::ByteCmp)
+                | Some(Intrinsic::CharEq)
+                | Some(Intrinsic::CharCmp)
+                | Some(Intrinsic::EnumEq)
+                | Some(Intrinsic::EnumNe)
+                | Some(Intrinsic::FloatEq)
+                | Some(Intrinsic::FloatCmp)
+                | Some(Intrinsic::DoubleEq)
+                | Some(Intrinsic::DoubleCmp) => self.expr(&expr.lhs) && self.expr(&expr.rhs),
+                Some(_) => false,
+                None => self.operator_call(expr.id) && self.expr(&expr.lhs) && self.expr(&expr.rhs),
+            }
+        }
+    }
+
+    // operators without intrinsic call a method of the left operand
+    fn operator_call(&self, id: NodeId) -> bool {
+        match **self.src.map_calls.get(id).unwrap() {
+            CallType::Method(obj_ty, callee_id, ref fct_type_params) => {
+                self.receiver(obj_ty) && fct_type_params.len() == 0 && self.callee(callee_id)
+            }
+
+            _ => false,
+        }
+    }
+
+    fn expr_dot(&self, expr: &ExprDotType) -> bool {
+        match self.src.map_idents.get(expr.id) {
+            Some(&IdentType::Field(BuiltinType::Class(cls_id, list_id), field_id)) => {
+                let type_params = self.vm.lists.lock().get(list_id);
+
+                if type_params.len() > 0 {
+                    return false;
+                }
+
+                let cls_def_id = specialize_class_id_params(self.vm, cls_id, &type_params);
+                let cls_def = self.vm.class_defs.idx(cls_def_id);
+                let cls_def = cls_def.read();
+
+                bytecode_type(cls_def.fields[field_id.idx()].ty).is_some() && self.expr(&expr.lhs)
+            }
+
+            _ => false,
+        }
+    }
+
+    fn expr_call(&self, expr: &ExprCallType) -> bool {
+        let call_type = self.src.map_calls.get(expr.id).unwrap().clone();
+
+        if let Some(intrinsic) = self.intrinsic(expr.id) {
+            return intrinsic == Intrinsic::Assert && self.expr(&expr.args[0]);
+        }
+
+        let callee_id = match *call_type {
+            CallType::Fct(callee_id, ref cls_type_params, ref fct_type_params) => {
+                if cls_type_params.len() > 0 || fct_type_params.len() > 0 {
+                    return false;
+                }
+
+                callee_id
+            }
+
+            CallType::CtorNew(cls_id, callee_id, ref type_params) => {
+                if type_params.len() > 0 {
+                    return false;
+                }
+
+                let cls_def_id = specialize_class_id_params(self.vm, cls_id, type_params);
+                let cls_def = self.vm.class_defs.idx(cls_def_id);
+                let cls_def = cls_def.read();
+
+                match cls_def.size {
+                    InstanceSize::Fixed(_) => {}
+                    _ => return false,
+                }
+
+                callee_id
+            }
+
+            // super calls aren't supported since `self.expr` rejects `super`
+            CallType::Method(obj_ty, callee_id, ref fct_type_params) => {
+                if !self.receiver(obj_ty) || fct_type_params.len() > 0 {
+                    return false;
+                }
+
+                if !expr.object().map_or(true, |obj| self.expr(obj)) {
+                    return false;
+                }
+
+                callee_id
+            }
+
+            _ => return false,
+        };
+
+        self.callee(callee_id) && expr.args.iter().all(|arg| self.expr(arg))
+    }
+
+    fn expr_delegation(&self, expr: &ExprDelegationType) -> bool {
+        let callee_id = match **self.src.map_calls.get(expr.id).unwrap() {
+            CallType::Ctor(_, callee_id, ref type_params) if type_params.len() == 0 => callee_id,
+            _ => return false,
+        };
+
+        self.callee(callee_id) && expr.args.iter().all(|arg| self.expr(arg))
+    }
+
+    // methods are only supported on objects of non-generic classes
+    fn receiver(&self, ty: BuiltinType) -> bool {
+        match ty {
+            BuiltinType::Class(_, list_id) => self.vm.lists.lock().get(list_id).len() == 0,
+            _ => false,
+        }
+    }
+
+    fn callee(&self, callee_id: FctId) -> bool {
+        if callee_id == self.fct.id {
+            return true;
+        }
+
+        let callee = self.vm.fcts.idx(callee_id);
+        let callee = callee.read();
+
+        match callee.kind {
+            FctKind::Source(_) | FctKind::Native(_) => is_supported_signature(&*callee),
+            FctKind::Definition | FctKind::Builtin(_) => false,
+        }
+    }
+
+    fn intrinsic(&self, id: NodeId) -> Option<Intrinsic> {
+        let call_type = self.src.map_calls.get(id)?;
+
+        if let Some(intrinsic) = call_type.to_intrinsic() {
+            return Some(intrinsic);
+        }
+
+        let fid = call_type.fct_id()?;
+
+        if self.fct.id == fid {
+            return None;
+        }
+
+        let fct = self.vm.fcts.idx(fid);
+        let fct = fct.read();
+
+        match fct.kind {
+            FctKind::Builtin(intrinsic) => Some(intrinsic),
+            _ => None,
+        }
+    }
+}
+
+pub struct InterpretedFct {
+    pub fct_id: FctId,
+    code: Vec<u8>,
+    registers: Vec<BytecodeType>,
+    ref_registers: Vec<usize>,
+    strings: Vec<Address>,
+    // (byte offset, position) for instructions starting a new position
+    positions: Vec<(usize, Position)>,
+    handlers: Vec<Handler>,
+    params: Vec<BytecodeType>,
+    return_type: Option<BytecodeType>,
+}
+
+impl InterpretedFct {
+    pub fn new(vm: &VM, fct: &Fct, bytecode: &BytecodeFunction) -> InterpretedFct {
+        let ref_registers = bytecode
+            .registers()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &ty)| ty == BytecodeType::Ptr)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let strings = bytecode
+            .string_pool()
+            .iter()
+            .map(|value| Str::from_buffer_in_perm(vm, value.as_bytes()).address())
+            .collect();
+
+        let (code, offsets) = encode_with_offsets(bytecode.code());
+
+        let positions = bytecode
+            .positions()
+            .iter()
+            .filter(|&&(idx, _)| idx.0 < offsets.len())
+            .map(|&(idx, pos)| (offsets[idx.0], pos))
+            .collect();
+
+        let offset = |idx: BytecodeIdx| offsets.get(idx.0).cloned().unwrap_or(code.len());
+
+        let handlers = bytecode
+            .exception_handlers()
+            .iter()
+            .map(|handler| Handler {
+                start: offset(handler.start),
+                end: offset(handler.end),
+                catch: offset(handler.catch),
+                catch_type: handler.catch_type,
+                exception: handler.exception,
+            })
+            .collect();
+
+        InterpretedFct {
+            fct_id: fct.id,
+            code,
+            registers: bytecode.registers().to_vec(),
+            ref_registers,
+            strings,
+            positions,
+            handlers,
+            params: fct.params_with_self().iter().map(|&ty| ty.into()).collect(),
+            return_type: return_type(fct.return_type),
+        }
+    }
+
+    pub fn params(&self) -> &[BytecodeType] {
+        &self.params
+    }
+
+    pub fn return_type(&self) -> Option<BytecodeType> {
+        self.return_type
+    }
+
+    // source position of the instruction at the byte offset
+    fn position(&self, offset: usize) -> Option<Position> {
+        self.positions
+            .iter()
+            .take_while(|&&(start, _)| start <= offset)
+            .last()
+            .map(|&(_, pos)| pos)
+    }
+}
+
+// exception handler with byte offsets instead of instruction indices
+struct Handler {
+    start: usize,
+    end: usize,
+    catch: usize,
+    catch_type: Option<ClassDefId>,
+    exception: Option<Register>,
+}
+
+fn return_type(ty: BuiltinType) -> Option<BytecodeType> {
+    if ty.is_unit() {
+        None
+    } else {
+        Some(ty.into())
+    }
+}
+
+// The register files of all active interpreter invocations of a thread,
+// these are part of the root set.
+pub struct InterpreterFrames {
+    frames: Mutex<Vec<InterpreterFrame>>,
+}
+
+struct InterpreterFrame {
+    registers: *const u64,
+    fct: *const InterpretedFct,
+    // byte offset of the current instruction
+    pc: *const Cell<usize>,
+}
+
+impl InterpreterFrames {
+    pub fn new() -> InterpreterFrames {
+        InterpreterFrames {
+            frames: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn push(&self, registers: &[u64], fct: &InterpretedFct, pc: &Cell<usize>) {
+        self.frames.lock().push(InterpreterFrame {
+            registers: registers.as_ptr(),
+            fct: fct as *const _,
+            pc: pc as *const _,
+        });
+    }
+
+    fn pop(&self) {
+        self.frames.lock().pop().expect("no interpreter frame");
+    }
+
+    pub fn roots(&self) -> Vec<Address> {
+        let frames = self.frames.lock();
+        let mut roots = Vec::new();
+
+        for frame in frames.iter() {
+            let fct = unsafe { &*frame.fct };
+
+            for &idx in &fct.ref_registers {
+                roots.push(Address::from_ptr(unsafe { frame.registers.add(idx) }));
+            }
+        }
+
+        roots
+    }
+
+    // function and position of the current instruction for every frame,
+    // innermost frame first
+    pub fn positions(&self) -> Vec<(FctId, Option<Position>)> {
+        let frames = self.frames.lock();
+
+        frames
+            .iter()
+            .rev()
+            .map(|frame| {
+                let fct = unsafe { &*frame.fct };
+                let pc = unsafe { (*frame.pc).get() };
+
+                (fct.fct_id, fct.position(pc))
+            })
+            .collect()
+    }
+}
+
+// Entered from the trampoline of an interpreted function: arguments are
+// read from `args`, the result is written to the first slot. A pending
+// exception is left in the thread local data.
+pub extern "C" fn interpret(fct: &InterpretedFct, args: *mut u64) {
+    let vm = get_vm();
+    let thread = THREAD.with(|thread| thread.borrow().clone());
+    let mut registers = vec![0; fct.registers.len()];
+
+    for (idx, &ty) in fct.params.iter().enumerate() {
+        registers[idx] = normalize(ty, unsafe { *args.add(idx) });
+    }
+
+    let pc = Cell::new(0);
+    thread.interpreter.push(&registers, fct, &pc);
+
+    let result = Interpreter {
+        vm,
+        fct,
+        registers: &mut registers,
+        pc: &pc,
+        tld: &thread.tld,
+    }
+    .run();
+
+    thread.interpreter.pop();
+
+    if let Some(value) = result {
+        unsafe {
+            *args = value;
+        }
+    }
+}
+
+struct Interpreter<'a, 'ast: 'a> {
+    vm: &'a VM<'ast>,
+    fct: &'a InterpretedFct,
+    registers: &'a mut [u64],
+    pc: &'a Cell<usize>,
+    tld: &'a ThreadLocalData,
+}
+
+// An exception is pending in the thread local data.
+struct Exception;
+
+impl<'a, 'ast> Interpreter<'a, 'ast> {
+    // Returns the result of the function, nothing is returned for
+    // functions without result or when an exception is pending.
+    fn run(&mut self) -> Option<u64> {
+        let mut reader = BytecodeReader::new(&self.fct.code);
+
+        loop {
+            match self.execute(&mut reader) {
+                Ok(result) => return result,
+                Err(Exception) => {
+                    if !self.catch(&mut reader) {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    // Continues at the first handler of the current instruction that
+    // matches the pending exception.
+    fn catch(&mut self, reader: &mut BytecodeReader) -> bool {
+        let pc = self.pc.get();
+        let exception = self.tld.exception_object();
+        let obj = unsafe { &*exception.to_ptr::<Obj>() };
+        let cls_def = obj.header().vtbl().classptr() as *const ClassDef;
+
+        let fct = self.fct;
+        let handler = fct.handlers.iter().find(|handler| {
+            handler.start <= pc
+                && pc < handler.end
+                && handler.catch_type.map_or(true, |cls_def_id| {
+                    let catch_cls_def = self.vm.class_defs.idx(cls_def_id);
+                    let catch_cls_def = catch_cls_def.read();
+                    &*catch_cls_def as *const ClassDef == cls_def
+                })
+        });
+
+        let handler = match handler {
+            Some(handler) => handler,
+            None => return false,
+        };
+
+        if let Some(exception_reg) = handler.exception {
+            self.set(exception_reg, exception.to_usize() as u64);
+        }
+
+        self.tld.set_exception_object(Address::null());
+        reader.set_pos(handler.catch);
+
+        true
+    }
+
+    fn execute(&mut self, reader: &mut BytecodeReader) -> Result<Option<u64>, Exception> {
+        loop {
+            let start = reader.pos();
+            self.pc.set(start);
+
+            match reader.read_opcode() {
+                BytecodeInst::Wide => unreachable!(),
+
+                BytecodeInst::AddInt => self.int_op(reader, |l, r| l.wrapping_add(r)),
+                BytecodeInst::AddLong => self.long_op(reader, |l, r| l.wrapping_add(r)),
+                BytecodeInst::AddFloat => {
+                    let (dest, lhs, rhs) = read_reg3(reader);
+                    let value = self.float(lhs) + self.float(rhs);
+                    self.set(dest, value.to_bits() as u64);
+                }
+                BytecodeInst::AddDouble => {
+                    let (dest, lhs, rhs) = read_reg3(reader);
+                    let value = self.double(lhs) + self.double(rhs);
+                    self.set(dest, value.to_bits());
+                }
+
+                BytecodeInst::SubInt => self.int_op(reader, |l, r| l.wrapping_sub(r)),
+                BytecodeInst::NegInt => {
+                    let (dest, src) = read_reg2(reader);
+                    let value = self.int(src).wrapping_neg();
+                    self.set_int(dest, value);
+                }
+                BytecodeInst::NegLong => {
+                    let (dest, src) = read_reg2(reader);
+                    let value = (self.get(src) as i64).wrapping_neg();
+                    self.set(dest, value as u64);
+                }
+                BytecodeInst::MulInt => self.int_op(reader, |l, r| l.wrapping_mul(r)),
+                BytecodeInst::DivInt => {
+                    let (dest, lhs, rhs) = read_reg3(reader);
+                    let value = self.int(lhs).wrapping_div(self.divisor(rhs));
+                    self.set_int(dest, value);
+                }
+                BytecodeInst::ModInt => {
+                    let (dest, lhs, rhs) = read_reg3(reader);
+                    let value = self.int(lhs).wrapping_rem(self.divisor(rhs));
+                    self.set_int(dest, value);
+                }
+                BytecodeInst::AndInt => self.int_op(reader, |l, r| l & r),
+                BytecodeInst::OrInt => self.int_op(reader, |l, r| l | r),
+                BytecodeInst::XorInt => self.int_op(reader, |l, r| l ^ r),
+                BytecodeInst::NotBool => {
+                    let (dest, src) = read_reg2(reader);
+                    let value = self.get(src) == 0;
+                    self.set(dest, value as u64);
+                }
+
+                BytecodeInst::ShlInt => self.int_op(reader, |l, r| l.wrapping_shl(r as u32)),
+                BytecodeInst::ShrInt => {
+                    self.int_op(reader, |l, r| (l as u32).wrapping_shr(r as u32) as i32)
+                }
+                BytecodeInst::SarInt => self.int_op(reader, |l, r| l.wrapping_shr(r as u32)),
+
+                BytecodeInst::MovBool
+                | BytecodeInst::MovByte
+                | BytecodeInst::MovChar
+                | BytecodeInst::MovInt
+                | BytecodeInst::MovLong
+                | BytecodeInst::MovFloat
+                | BytecodeInst::MovDouble
+                | BytecodeInst::MovPtr => {
+                    let (dest, src) = read_reg2(reader);
+                    let value = self.get(src);
+                    self.set(dest, value);
+                }
+
+                BytecodeInst::LoadFieldBool => self.load_field(reader, BytecodeType::Bool),
+                BytecodeInst::LoadFieldByte => self.load_field(reader, BytecodeType::Byte),
+                BytecodeInst::LoadFieldChar => self.load_field(reader, BytecodeType::Char),
+                BytecodeInst::LoadFieldInt => self.load_field(reader, BytecodeType::Int),
+                BytecodeInst::LoadFieldLong => self.load_field(reader, BytecodeType::Long),
+                BytecodeInst::LoadFieldFloat => self.load_field(reader, BytecodeType::Float),
+                BytecodeInst::LoadFieldDouble => self.load_field(reader, BytecodeType::Double),
+                BytecodeInst::LoadFieldPtr => self.load_field(reader, BytecodeType::Ptr),
+
+                BytecodeInst::LoadGlobalBool => self.load_global(reader, BytecodeType::Bool),
+                BytecodeInst::LoadGlobalByte => self.load_global(reader, BytecodeType::Byte),
+                BytecodeInst::LoadGlobalChar => self.load_global(reader, BytecodeType::Char),
+                BytecodeInst::LoadGlobalInt => self.load_global(reader, BytecodeType::Int),
+                BytecodeInst::LoadGlobalLong => self.load_global(reader, BytecodeType::Long),
+                BytecodeInst::LoadGlobalFloat => self.load_global(reader, BytecodeType::Float),
+                BytecodeInst::LoadGlobalDouble => self.load_global(reader, BytecodeType::Double),
+                BytecodeInst::LoadGlobalPtr => self.load_global(reader, BytecodeType::Ptr),
+
+                BytecodeInst::ConstNil
+                | BytecodeInst::ConstFalse
+                | BytecodeInst::ConstZeroByte
+                | BytecodeInst::ConstZeroInt
+                | BytecodeInst::ConstZeroLong
+                | BytecodeInst::ConstZeroFloat
+                | BytecodeInst::ConstZeroDouble => {
+                    let dest = reader.read_register();
+                    self.set(dest, 0);
+                }
+                BytecodeInst::ConstTrue => {
+                    let dest = reader.read_register();
+                    self.set(dest, 1);
+                }
+                BytecodeInst::ConstByte => {
+                    let dest = reader.read_register();
+                    let value = reader.read_u8();
+                    self.set(dest, value as u64);
+                }
+                BytecodeInst::ConstChar | BytecodeInst::ConstInt | BytecodeInst::ConstFloat => {
+                    let dest = reader.read_register();
+                    let value = reader.read_u32();
+                    self.set(dest, value as u64);
+                }
+                BytecodeInst::ConstLong | BytecodeInst::ConstDouble => {
+                    let dest = reader.read_register();
+                    let value = reader.read_u64();
+                    self.set(dest, value);
+                }
+                BytecodeInst::ConstString => {
+                    let dest = reader.read_register();
+                    let idx = reader.read_index();
+                    self.set(dest, self.fct.strings[idx].to_usize() as u64);
+                }
+
+                BytecodeInst::TestEqPtr => self.test_op(reader, |l, r| l == r),
+                BytecodeInst::TestNePtr => self.test_op(reader, |l, r| l != r),
+
+                BytecodeInst::TestEqInt => self.test_op(reader, |l, r| l as i32 == r as i32),
+                BytecodeInst::TestNeInt => self.test_op(reader, |l, r| l as i32 != r as i32),
+                BytecodeInst::TestGtInt => self.test_op(reader, |l, r| l as i32 > r as i32),
+                BytecodeInst::TestGeInt => self.test_op(reader, |l, r| l as i32 >= r as i32),
+                BytecodeInst::TestLtInt => self.test_op(reader, |l, r| (l as i32) < r as i32),
+                BytecodeInst::TestLeInt => self.test_op(reader, |l, r| l as i32 <= r as i32),
+
+                BytecodeInst::TestEqLong => self.test_op(reader, |l, r| l == r),
+                BytecodeInst::TestNeLong => self.test_op(reader, |l, r| l != r),
+
+                BytecodeInst::TestEqBool => self.test_op(reader, |l, r| l == r),
+                BytecodeInst::TestNeBool => self.test_op(reader, |l, r| l != r),
+
+                BytecodeInst::TestEqByte => self.test_op(reader, |l, r| l == r),
+                BytecodeInst::TestNeByte => self.test_op(reader, |l, r| l != r),
+                BytecodeInst::TestGtByte => self.test_op(reader, |l, r| l > r),
+                BytecodeInst::TestGeByte => self.test_op(reader, |l, r| l >= r),
+                BytecodeInst::TestLtByte => self.test_op(reader, |l, r| l < r),
+                BytecodeInst::TestLeByte => self.test_op(reader, |l, r| l <= r),
+
+                BytecodeInst::TestEqChar => self.test_op(reader, |l, r| l == r),
+                BytecodeInst::TestNeChar => self.test_op(reader, |l, r| l != r),
+                BytecodeInst::TestGtChar => self.test_op(reader, |l, r| l > r),
+                BytecodeInst::TestGeChar => self.test_op(reader, |l, r| l >= r),
+                BytecodeInst::TestLtChar => self.test_op(reader, |l, r| l < r),
+                BytecodeInst::TestLeChar => self.test_op(reader, |l, r| l <= r),
+
+                BytecodeInst::TestEqFloat => self.float_test(reader, |l, r| l == r),
+                BytecodeInst::TestNeFloat => self.float_test(reader, |l, r| l != r),
+                BytecodeInst::TestGtFloat => self.float_test(reader, |l, r| l > r),
+                BytecodeInst::TestGeFloat => self.float_test(reader, |l, r| l >= r),
+                BytecodeInst::TestLtFloat => self.float_test(reader, |l, r| l < r),
+                BytecodeInst::TestLeFloat => self.float_test(reader, |l, r| l <= r),
+
+                BytecodeInst::TestEqDouble => self.double_test(reader, |l, r| l == r),
+                BytecodeInst::TestNeDouble => self.double_test(reader, |l, r| l != r),
+                BytecodeInst::TestGtDouble => self.double_test(reader, |l, r| l > r),
+                BytecodeInst::TestGeDouble => self.double_test(reader, |l, r| l >= r),
+                BytecodeInst::TestLtDouble => self.double_test(reader, |l, r| l < r),
+                BytecodeInst::TestLeDouble => self.double_test(reader, |l, r| l <= r),
+
+                BytecodeInst::JumpIfFalse => self.cond_jump(reader, start, false),
+                BytecodeInst::JumpIfTrue => self.cond_jump(reader, start, true),
+                BytecodeInst::Jump => {
+                    let offset = reader.read_offset();
+                    reader.set_pos(jump_target(start, offset));
+                }
+                BytecodeInst::JumpTable => {
+                    let opnd = reader.read_register();
+                    let len = reader.read_index();
+                    let idx = self.get(opnd) as usize;
+                    assert!(idx < len);
+
+                    let pos = reader.pos();
+                    reader.set_pos(pos + idx * 4);
+                    let offset = reader.read_offset();
+                    reader.set_pos(jump_target(start, offset));
+                }
+
+                BytecodeInst::InvokeDirectVoid => {
+                    let fct_id = reader.read_fct_id();
+                    let start = reader.read_register();
+                    let count = reader.read_index();
+
+                    self.object(start);
+                    self.invoke(fct_id, start, count)?;
+                }
+                BytecodeInst::InvokeDirectBool
+                | BytecodeInst::InvokeDirectByte
+                | BytecodeInst::InvokeDirectChar
+                | BytecodeInst::InvokeDirectInt
+                | BytecodeInst::InvokeDirectLong
+                | BytecodeInst::InvokeDirectFloat
+                | BytecodeInst::InvokeDirectDouble
+                | BytecodeInst::InvokeDirectPtr => {
+                    let dest = reader.read_register();
+                    let fct_id = reader.read_fct_id();
+                    let start = reader.read_register();
+                    let count = reader.read_index();
+
+                    self.object(start);
+                    let value = self.invoke(fct_id, start, count)?;
+                    self.set(dest, value);
+                }
+
+                BytecodeInst::InvokeVirtualVoid => {
+                    let fct_id = reader.read_fct_id();
+                    let start = reader.read_register();
+                    let count = reader.read_index();
+
+                    let fct_id = self.override_of(fct_id, start);
+                    self.invoke(fct_id, start, count)?;
+                }
+                BytecodeInst::InvokeVirtualBool
+                | BytecodeInst::InvokeVirtualByte
+                | BytecodeInst::InvokeVirtualChar
+                | BytecodeInst::InvokeVirtualInt
+                | BytecodeInst::InvokeVirtualLong
+                | BytecodeInst::InvokeVirtualFloat
+                | BytecodeInst::InvokeVirtualDouble
+                | BytecodeInst::InvokeVirtualPtr => {
+                    let dest = reader.read_register();
+                    let fct_id = reader.read_fct_id();
+                    let start = reader.read_register();
+                    let count = reader.read_index();
+
+                    let fct_id = self.override_of(fct_id, start);
+                    let value = self.invoke(fct_id, start, count)?;
+                    self.set(dest, value);
+                }
+
+                BytecodeInst::InvokeStaticVoid => {
+                    let fct_id = reader.read_fct_id();
+                    let start = reader.read_register();
+                    let count = reader.read_index();
+
+                    self.invoke(fct_id, start, count)?;
+                }
+                BytecodeInst::InvokeStaticBool
+                | BytecodeInst::InvokeStaticByte
+                | BytecodeInst::InvokeStaticChar
+                | BytecodeInst::InvokeStaticInt
+                | BytecodeInst::InvokeStaticLong
+                | BytecodeInst::InvokeStaticFloat
+                | BytecodeInst::InvokeStaticDouble
+                | BytecodeInst::InvokeStaticPtr => {
+                    let dest = reader.read_register();
+                    let fct_id = reader.read_fct_id();
+                    let start = reader.read_register();
+                    let count = reader.read_index();
+
+                    let value = self.invoke(fct_id, start, count)?;
+                    self.set(dest, value);
+                }
+
+                BytecodeInst::NewObject => {
+                    let dest = reader.read_register();
+                    let cls_def_id = reader.read_class_def_id();
+                    let obj = object::alloc(self.vm, cls_def_id);
+                    self.set(dest, obj.address().to_usize() as u64);
+                }
+
+                BytecodeInst::Throw => {
+                    let src = reader.read_register();
+                    let exception = self.object(src);
+                    self.tld.set_exception_object(exception);
+
+                    return Err(Exception);
+                }
+
+                BytecodeInst::RetBool
+                | BytecodeInst::RetByte
+                | BytecodeInst::RetChar
+                | BytecodeInst::RetInt
+                | BytecodeInst::RetLong
+                | BytecodeInst::RetFloat
+                | BytecodeInst::RetDouble
+                | BytecodeInst::RetPtr => {
+                    let src = reader.read_register();
+                    return Ok(Some(self.get(src)));
+                }
+
+                BytecodeInst::RetVoid => return Ok(None),
+            }
+        }
+    }
+
+    fn get(&self, reg: Register) -> u64 {
+        self.registers[reg.to_usize()]
+    }
+
+    fn set(&mut self, reg: Register, value: u64) {
+        let ty = self.fct.registers[reg.to_usize()];
+        self.registers[reg.to_usize()] = normalize(ty, value);
+    }
+
+    fn int(&self, reg: Register) -> i32 {
+        self.get(reg) as u32 as i32
+    }
+
+    fn set_int(&mut self, reg: Register, value: i32) {
+        self.set(reg, value as u32 as u64);
+    }
+
+    fn float(&self, reg: Register) -> f32 {
+        f32::from_bits(self.get(reg) as u32)
+    }
+
+    fn double(&self, reg: Register) -> f64 {
+        f64::from_bits(self.get(reg))
+    }
+
+    fn divisor(&self, reg: Register) -> i32 {
+        let value = self.int(reg);
+
+        if value == 0 {
+            exception::trap(Trap::DIV0);
+        }
+
+        value
+    }
+
+    fn object(&self, reg: Register) -> Address {
+        let value = Address::from(self.get(reg) as usize);
+
+        if value.is_null() {
+            exception::trap(Trap::NIL);
+        }
+
+        value
+    }
+
+    fn int_op<F>(&mut self, reader: &mut BytecodeReader, op: F)
+    where
+        F: FnOnce(i32, i32) -> i32,
+    {
+        let (dest, lhs, rhs) = read_reg3(reader);
+        let value = op(self.int(lhs), self.int(rhs));
+        self.set_int(dest, value);
+    }
+
+    fn long_op<F>(&mut self, reader: &mut BytecodeReader, op: F)
+    where
+        F: FnOnce(i64, i64) -> i64,
+    {
+        let (dest, lhs, rhs) = read_reg3(reader);
+        let value = op(self.get(lhs) as i64, self.get(rhs) as i64);
+        self.set(dest, value as u64);
+    }
+
+    fn test_op<F>(&mut self, reader: &mut BytecodeReader, op: F)
+    where
+        F: FnOnce(u64, u64) -> bool,
+    {
+        let (dest, lhs, rhs) = read_reg3(reader);
+        let value = op(self.get(lhs), self.get(rhs));
+        self.set(dest, value as u64);
+    }
+
+    fn float_test<F>(&mut self, reader: &mut BytecodeReader, op: F)
+    where
+        F: FnOnce(f32, f32) -> bool,
+    {
+        let (dest, lhs, rhs) = read_reg3(reader);
+        let value = op(self.float(lhs), self.float(rhs));
+        self.set(dest, value as u64);
+    }
+
+    fn double_test<F>(&mut self, reader: &mut BytecodeReader, op: F)
+    where
+        F: FnOnce(f64, f64) -> bool,
+    {
+        let (dest, lhs, rhs) = read_reg3(reader);
+        let value = op(self.double(lhs), self.double(rhs));
+        self.set(dest, value as u64);
+    }
+
+    fn cond_jump(&self, reader: &mut BytecodeReader, start: usize, expected: bool) {
+        let cond = reader.read_register();
+        let offset = reader.read_offset();
+
+        if (self.get(cond) != 0) == expected {
+            reader.set_pos(jump_target(start, offset));
+        }
+    }
+
+    fn load_field(&mut self, reader: &mut BytecodeReader, ty: BytecodeType) {
+        let dest = reader.read_register();
+        let obj = reader.read_register();
+        let cls_def_id = reader.read_class_def_id();
+        let field_id = reader.read_field_id();
+
+        let offset = {
+            let cls_def = self.vm.class_defs.idx(cls_def_id);
+            let cls_def = cls_def.read();
+            cls_def.fields[field_id.idx()].offset
+        };
+
+        let obj = self.object(obj);
+        let value = load(ty, obj.offset(offset as usize));
+        self.set(dest, value);
+    }
+
+    fn load_global(&mut self, reader: &mut BytecodeReader, ty: BytecodeType) {
+        let dest = reader.read_register();
+        let global_id = reader.read_global_id();
+
+        let address = {
+            let glob = self.vm.globals.idx(global_id);
+            let glob = glob.lock();
+            glob.address_value
+        };
+
+        self.set(dest, load(ty, address));
+    }
+
+    // Looks up the method in the vtable of the receiver like compiled code
+    // does for virtual calls, traps on nil.
+    fn override_of(&self, fct_id: FctId, receiver: Register) -> FctId {
+        let receiver = self.object(receiver);
+
+        let vtable_index = {
+            let fct = self.vm.fcts.idx(fct_id);
+            let fct = fct.read();
+            fct.vtable_index.expect("no vtable index")
+        };
+
+        let obj = unsafe { &*receiver.to_ptr::<Obj>() };
+        let cls_id = obj
+            .header()
+            .vtbl()
+            .class()
+            .cls_id
+            .expect("no corresponding class");
+
+        let cls = self.vm.classes.idx(cls_id);
+        let cls = cls.read();
+        cls.virtual_fcts[vtable_index as usize]
+    }
+
+    // Calls the compiled code of the function, fails when the callee threw
+    // an exception.
+    fn invoke(&mut self, fct_id: FctId, start: Register, count: usize) -> Result<u64, Exception> {
+        let (ptr, params, return_type) = callee(self.vm, fct_id);
+        let stub = dora_interpreter::call_stub(self.vm, &params, return_type);
+
+        let start = start.to_usize();
+        let mut args = vec![0; count.max(1)];
+        args[..count].copy_from_slice(&self.registers[start..start + count]);
+
+        let stub: extern "C" fn(*const ThreadLocalData, Address, *mut u64) =
+            unsafe { mem::transmute(stub.to_usize()) };
+        stub(self.tld, ptr, args.as_mut_ptr());
+
+        if !self.tld.exception_object().is_null() {
+            return Err(Exception);
+        }
+
+        Ok(args[0])
+    }
+}
+
+fn callee(vm: &VM, fct_id: FctId) -> (Address, Vec<BytecodeType>, Option<BytecodeType>) {
+    let fct = vm.fcts.idx(fct_id);
+    let fct = fct.read();
+
+    let params = fct.params_with_self().iter().map(|&ty| ty.into()).collect();
+    let ret = return_type(fct.return_type);
+
+    let ptr = match fct.kind {
+        FctKind::Source(_) => {
+            drop(fct);
+            codegen::generate(vm, fct_id, &TypeList::empty(), &TypeList::empty())
+        }
+
+        FctKind::Native(ptr) => {
+            let internal_fct = InternalFct {
+                ptr,
+                args: fct.params_with_self(),
+                return_type: fct.return_type,
+                throws: fct.ast.throws,
+                desc: InternalFctDescriptor::NativeThunk(fct_id),
+            };
+
+            ensure_native_stub(vm, fct_id, internal_fct)
+        }
+
+        FctKind::Definition | FctKind::Builtin(_) => unreachable!(),
+    };
+
+    (ptr, params, ret)
+}
+
+fn read_reg2(reader: &mut BytecodeReader) -> (Register, Register) {
+    let dest = reader.read_register();
+    let src = reader.read_register();
+
+    (dest, src)
+}
+
+fn read_reg3(reader: &mut BytecodeReader) -> (Register, Register, Register) {
+    let dest = reader.read_register();
+    let lhs = reader.read_register();
+    let rhs = reader.read_register();
+
+    (dest, lhs, rhs)
+}
+
+fn jump_target(start: usize, offset: i32) -> usize {
+    (start as isize + offset as isize) as usize
+}
+
+fn load(ty: BytecodeType, address: Address) -> u64 {
+    unsafe {
+        match ty {
+            BytecodeType::Bool | BytecodeType::Byte => *address.to_ptr::<u8>() as u64,
+            BytecodeType::Char | BytecodeType::Int | BytecodeType::Float => {
+                *address.to_ptr::<u32>() as u64
+            }
+            BytecodeType::Long | BytecodeType::Double | BytecodeType::Ptr => {
+                *address.to_ptr::<u64>()
+            }
+        }
+    }
+}
+
+// Values smaller than a register are zero-extended.
+pub fn normalize(ty: BytecodeType, value: u64) -> u64 {
+    match ty {
+        BytecodeType::Bool | BytecodeType::Byte => value & 0xFF,
+        BytecodeType::Char | BytecodeType::Int | BytecodeType::Float => value & 0xFFFF_FFFF,
+        BytecodeType::Long | BytecodeType::Double | BytecodeType::Ptr => value,
+    }
+}
@@ -78,9 +78,44 @@ pub enum Bytecode {
     TestLtInt(Register, Register, Register),
     TestLeInt(Register, Register, Register),
 
+    TestEqLong(Register, Register, Register),
+    TestNeLong(Register, Register, Register),
+
+    TestEqBool(Register, Register, Register),
+    TestNeBool(Register, Register, Register),
+
+    TestEqByte(Register, Register, Register),
+    TestNeByte(Register, Register, Register),
+    TestGtByte(Register, Register, Register),
+    TestGeByte(Register, Register, Register),
+    TestLtByte(Register, Register, Register),
+    TestLeByte(Register, Register, Register),
+
+    TestEqChar(Register, Register, Register),
+    TestNeChar(Register, Register, Register),
+    TestGtChar(Register, Register, Register),
+    TestGeChar(Register, Register, Register),
+    TestLtChar(Register, Register, Register),
+    TestLeChar(Register, Register, Register),
+
+    TestEqFloat(Register, Register, Register),
+    TestNeFloat(Register, Register, Register),
+    TestGtFloat(Register, Register, Register),
+    TestGeFloat(Register, Register, Register),
+    TestLtFloat(Register, Register, Register),
+    TestLeFloat(Register, Register, Register),
+
+    TestEqDouble(Register, Register, Register),
+    TestNeDouble(Register, Register, Register),
+    TestGtDouble(Register, Register, Register),
+    TestGeDouble(Register, Register, Register),
+    TestLtDouble(Register, Register, Register),
+    TestLeDouble(Register, Register, Register),
+
     JumpIfFalse(Register, BytecodeIdx),
     JumpIfTrue(Register, BytecodeIdx),
     Jump(BytecodeIdx),
+    JumpTable(Register, Vec<BytecodeIdx>),
 
     InvokeDirectVoid(FctId, Register, usize),
     InvokeDirectBool(Register, FctId, Register, usize),
@@ -10,7 +10,7 @@ use crate::size::InstanceSize;
 use crate::ty::{BuiltinType, TypeList};
 use crate::utils::GrowableVec;
 use crate::vm::VM;
-use crate::vm::{FctId, FileId, ImplId, TraitId, TypeParam};
+use crate::vm::{EnumId, FctId, FileId, ImplId, TraitId, TypeParam};
 use crate::vtable::VTableBox;
 use dora_parser::interner::Name;
 use dora_parser::lexer::position::Position;
@@ -349,6 +349,13 @@ pub struct ClassDef {
     pub size: InstanceSize,
     pub ref_fields: Vec<i32>,
     pub vtable: Option<VTableBox>,
+
+    // set for closure objects: the lambda and its function type params,
+    // class type params are stored in `type_params`
+    pub lambda: Option<(FctId, TypeList)>,
+
+    // set for values of enums with payload data
+    pub enum_value: Option<(EnumId, u32)>,
 }
 
 impl ClassDef {
@@ -369,6 +376,15 @@ impl ClassDef {
             };
 
             format!("{}<{}>", name, params)
+        } else if self.lambda.is_some() {
+            "<lambda>".into()
+        } else if let Some((enum_id, value)) = self.enum_value {
+            let xenum = vm.enums[enum_id].read();
+            format!(
+                "{}::{}",
+                vm.interner.str(xenum.name),
+                vm.interner.str(xenum.values[value as usize])
+            )
         } else {
             "<Unknown>".into()
         }
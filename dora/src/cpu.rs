@@ -30,6 +30,7 @@ impl From<FReg> for u32 {
     }
 }
 
+#[derive(Copy, Clone)]
 pub enum Mem {
     // rbp + val1
     Local(i32),
@@ -56,7 +56,8 @@ Options:
     --gc-young-appel        Use Appel dynamic resizing of young generation.
     --gc-semi-ratio=<num>   Use fixed ratio of semi space in young generation.
 
-    --bc=<name>             Switch Baseline Compiler. Possible values: cannon, astcompiler [default: astcompiler].
+    --bc=<name>             Switch Baseline Compiler. Possible values: cannon, astcompiler, interpreter [default: astcompiler].
+    --tier=<name>           Tier functions are executed in first. Possible values: interpreter, baseline [default: baseline].
 
     --disable-tlab          Disable tlab allocation.
     --disable-barrier       Disable barriers.
@@ -104,6 +105,7 @@ pub struct Args {
     pub flag_gc_semi_ratio: Option<usize>,
     pub flag_gc: Option<CollectorName>,
     pub flag_bc: Option<BaselineName>,
+    pub flag_tier: Option<TierName>,
     pub flag_min_heap_size: Option<MemSize>,
     pub flag_max_heap_size: Option<MemSize>,
     pub flag_code_size: Option<MemSize>,
@@ -167,7 +169,10 @@ impl Args {
     }
 
     pub fn bc(&self) -> BaselineName {
-        self.flag_bc.unwrap_or(BaselineName::AstCompiler)
+        match self.flag_tier {
+            Some(TierName::Interpreter) => BaselineName::Interpreter,
+            _ => self.flag_bc.unwrap_or(BaselineName::AstCompiler),
+        }
     }
 }
 
@@ -207,6 +212,7 @@ impl Default for Args {
             flag_gc_semi_ratio: None,
             flag_gc: None,
             flag_bc: None,
+            flag_tier: None,
             flag_min_heap_size: None,
             flag_max_heap_size: None,
             flag_code_size: None,
@@ -235,6 +241,13 @@ pub enum CollectorName {
 pub enum BaselineName {
     Cannon,
     AstCompiler,
+    Interpreter,
+}
+
+#[derive(Copy, Clone, Debug, RustcDecodable)]
+pub enum TierName {
+    Interpreter,
+    Baseline,
 }
 
 #[derive(Copy, Clone, Debug, RustcDecodable)]
@@ -1,10 +1,12 @@
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::msg::SemError;
 use crate::vm::VM;
-use crate::vm::{exception_get_and_clear, Fct, FctId};
+use crate::vm::{exception_get_and_clear, Fct, FctId, ModuleId};
 use dora_parser::ast::{self, Ast};
+use dora_parser::interner::Name;
 
 use crate::driver::cmd;
 use crate::object;
@@ -125,13 +127,117 @@ fn parse_all_files(vm: &mut VM, ast: &mut Ast, content: Option<&str>) -> Result<
     let arg_file = vm.args.arg_file.clone();
     let path = Path::new(&arg_file);
 
-    if path.is_file() {
-        parse_file(&arg_file, vm, ast)
+    let root_dir = if path.is_file() {
+        parse_file(&arg_file, ModuleId::root(), vm, ast)?;
+        path.parent().unwrap_or(Path::new("")).to_path_buf()
     } else if path.is_dir() {
-        parse_dir(&arg_file, vm, ast)
+        parse_dir(&arg_file, vm, ast)?;
+        path.to_path_buf()
     } else {
         println!("file or directory `{}` does not exist.", &arg_file);
-        Err(1)
+        return Err(1);
+    };
+
+    load_modules(&root_dir, vm, ast)
+}
+
+// loads all modules referenced in `import` and `use` declarations, paths start at the
+// directory of the program: module `a::b` is either the file `a/b.dora` or consists of
+// all files in the directory `a/b/`. Unknown modules are reported by semck.
+fn load_modules(root_dir: &Path, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
+    let mut loaded: HashSet<PathBuf> = ast
+        .files
+        .iter()
+        .filter_map(|file| fs::canonicalize(&file.path).ok())
+        .collect();
+    let mut file_idx = 0;
+
+    // modules are appended to the list of files while iterating
+    while file_idx < ast.files.len() {
+        let paths: Vec<Vec<Name>> = ast.files[file_idx]
+            .elements
+            .iter()
+            .filter_map(|elem| match *elem {
+                ast::Elem::ElemImport(ref import) => Some(import.path.clone()),
+                ast::Elem::ElemUse(ref xuse) => Some(xuse.path[..xuse.path.len() - 1].to_vec()),
+                _ => None,
+            })
+            .collect();
+
+        for path in paths {
+            let mut module = ModuleId::root();
+            let mut dir = root_dir.to_path_buf();
+
+            for name in path {
+                dir.push(&*vm.interner.str(name));
+
+                let child = vm.module(module).symbols.get(&name).cloned();
+
+                module = match child.and_then(|sym| sym.to_module()) {
+                    Some(child) => child,
+
+                    None => match load_module(&dir, name, module, &mut loaded, vm, ast)? {
+                        Some(child) => child,
+                        None => break,
+                    },
+                };
+            }
+        }
+
+        file_idx += 1;
+    }
+
+    Ok(())
+}
+
+fn load_module(
+    dir: &Path,
+    name: Name,
+    parent: ModuleId,
+    loaded: &mut HashSet<PathBuf>,
+    vm: &mut VM,
+    ast: &mut Ast,
+) -> Result<Option<ModuleId>, i32> {
+    let file = dir.with_extension("dora");
+
+    if let Some(file) = unloaded_file(&file, loaded) {
+        let module = vm.add_module(name, parent);
+        parse_file(file.to_str().unwrap(), module, vm, ast)?;
+
+        return Ok(Some(module));
+    }
+
+    if !dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .filter_map(|entry| unloaded_file(&entry.unwrap().path(), loaded))
+        .collect();
+    files.sort();
+
+    let module = vm.add_module(name, parent);
+
+    for file in files {
+        parse_file(file.to_str().unwrap(), module, vm, ast)?;
+    }
+
+    Ok(Some(module))
+}
+
+// returns the path of a not yet parsed dora file and marks it as loaded
+fn unloaded_file(path: &Path, loaded: &mut HashSet<PathBuf>) -> Option<PathBuf> {
+    if !path.is_file() || path.extension().map_or(true, |ext| ext != "dora") {
+        return None;
+    }
+
+    let canonical = fs::canonicalize(path).ok()?;
+
+    if loaded.insert(canonical) {
+        Some(path.to_path_buf())
+    } else {
+        None
     }
 }
 
@@ -225,7 +331,7 @@ fn parse_dir(dirname: &str, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
             let path = entry.unwrap().path();
 
             if path.is_file() && path.extension().unwrap() == "dora" {
-                parse_file(path.to_str().unwrap(), vm, ast)?;
+                parse_file(path.to_str().unwrap(), ModuleId::root(), vm, ast)?;
             }
         }
 
@@ -237,7 +343,7 @@ fn parse_dir(dirname: &str, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
     }
 }
 
-fn parse_file(filename: &str, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
+fn parse_file(filename: &str, module: ModuleId, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
     let reader = if filename == "-" {
         match Reader::from_input() {
             Ok(reader) => reader,
@@ -258,26 +364,27 @@ fn parse_file(filename: &str, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
         }
     };
 
-    parse_reader(reader, vm, ast)
+    parse_reader(reader, module, vm, ast)
 }
 
 fn parse_bundle(filename: &str, data: &str, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
     let reader = Reader::from_string(filename, data);
-    parse_reader(reader, vm, ast)
+    parse_reader(reader, ModuleId::root(), vm, ast)
 }
 
 fn parse_str(file: &str, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
     let reader = Reader::from_string("<<code>>", file);
-    parse_reader(reader, vm, ast)
+    parse_reader(reader, ModuleId::root(), vm, ast)
 }
 
-fn parse_reader(reader: Reader, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
+fn parse_reader(reader: Reader, module: ModuleId, vm: &mut VM, ast: &mut Ast) -> Result<(), i32> {
     let filename: String = reader.path().into();
     let parser = Parser::new(reader, &vm.id_generator, ast, &mut vm.interner);
 
     match parser.parse() {
         Ok(file) => {
             vm.files.push(file);
+            vm.file_modules.push(module);
             assert_eq!(ast.files.len(), vm.files.len());
             Ok(())
         }
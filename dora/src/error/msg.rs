@@ -36,6 +36,7 @@ pub enum SemError {
     NoEnumValue,
     VarNeedsTypeInfo(String),
     ParamTypesIncompatible(String, Vec<String>, Vec<String>),
+    LambdaParamTypesIncompatible(Vec<String>, Vec<String>),
     WhileCondType(String),
     IfCondType(String),
     ReturnType(String, String),
@@ -58,6 +59,7 @@ pub enum SemError {
     CatchOrFinallyExpected,
     LetMissingInitialization,
     LetReassigned,
+    ReassignableVarCaptured(String),
     FctReassigned,
     ClassReassigned,
     TypeParamReassigned,
@@ -133,6 +135,7 @@ pub enum SemError {
     MakeIteratorReturnType(String),
     UnknownStructField(String, String),
     StructFieldNotInitialized(String, String),
+    StructFieldInitializedTwice(String, String),
     InvalidLeftSideOfSeparator,
     InvalidUseOfTypeParams,
     NameOfStaticMethodExpected,
@@ -140,6 +143,23 @@ pub enum SemError {
     NameExpected,
     IndexExpected,
     IllegalTupleIndex(u64, String),
+    EnumExpected(String),
+    EnumValueExpected,
+    MatchPatternTypeMismatch(String, String),
+    MatchBranchTypesIncompatible(String, String),
+    MatchNotExhaustive(Vec<String>),
+    MatchUnreachableArm,
+    MatchBindingInAlternative,
+    MatchPatternParamsMismatch(String, usize, usize),
+    EnumValueArgsIncompatible(String, Vec<String>, Vec<String>),
+    EnumValueWithoutData(String),
+    EnumValueArgsExpected(String),
+    UnknownModule(String),
+    UnknownModuleItem(String, String),
+    NotAccessible(String),
+    ModuleExpected(String),
+    ModuleUsedAsIdentifier,
+    ShadowModule(String),
 }
 
 impl SemError {
@@ -237,6 +257,12 @@ impl SemError {
                     name, def, name, expr
                 )
             }
+            SemError::LambdaParamTypesIncompatible(ref def, ref expr) => {
+                let def = def.join(", ");
+                let expr = expr.join(", ");
+
+                format!("lambda `({})` cannot be called with `({})`", def, expr)
+            }
             SemError::WhileCondType(ref ty) => {
                 format!("`while` expects condition of type `bool` but got `{}`.", ty)
             }
@@ -282,6 +308,9 @@ impl SemError {
             SemError::CatchOrFinallyExpected => "`try` without `catch` or `finally`.".into(),
             SemError::LetMissingInitialization => "`let` binding is missing initialization.".into(),
             SemError::LetReassigned => "`let` binding cannot be reassigned.".into(),
+            SemError::ReassignableVarCaptured(ref name) => {
+                format!("`var` binding `{}` cannot be captured by lambda.", name)
+            }
             SemError::FctReassigned => "function cannot be reassigned.".into(),
             SemError::ClassReassigned => "class cannot be reassigned.".into(),
             SemError::TypeParamReassigned => "type param cannot be reassigned.".into(),
@@ -456,6 +485,9 @@ impl SemError {
             SemError::StructFieldNotInitialized(ref struc, ref field) => {
                 format!("field `{}` in struct `{}` not initialized.", field, struc)
             }
+            SemError::StructFieldInitializedTwice(ref struc, ref field) => {
+                format!("field `{}` in struct `{}` initialized twice.", field, struc)
+            }
             SemError::InvalidLeftSideOfSeparator => {
                 "left hand side of separator is not a class.".into()
             }
@@ -472,6 +504,55 @@ impl SemError {
             SemError::IllegalTupleIndex(idx, ref ty) => {
                 format!("illegal index `{}` for type `{}`", idx, ty)
             }
+            SemError::EnumExpected(ref ty) => format!("enum expected but got type `{}`.", ty),
+            SemError::EnumValueExpected => "enum value expected in pattern.".into(),
+            SemError::MatchPatternTypeMismatch(ref pattern, ref expr) => format!(
+                "pattern of type `{}` cannot match value of type `{}`.",
+                pattern, expr
+            ),
+            SemError::MatchBranchTypesIncompatible(ref first, ref other) => format!(
+                "match arms have incompatible types `{}` and `{}`.",
+                first, other
+            ),
+            SemError::MatchNotExhaustive(ref missing) => {
+                let missing = missing
+                    .iter()
+                    .map(|value| format!("`{}`", value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("match is not exhaustive: missing {}.", missing)
+            }
+            SemError::MatchUnreachableArm => "unreachable match arm.".into(),
+            SemError::MatchBindingInAlternative => {
+                "bindings not allowed in match arm with alternatives.".into()
+            }
+            SemError::MatchPatternParamsMismatch(ref name, expected, got) => format!(
+                "pattern `{}` expects {} parameter(s) but got {}.",
+                name, expected, got
+            ),
+            SemError::EnumValueArgsIncompatible(ref name, ref def, ref expr) => {
+                let def = def.join(", ");
+                let expr = expr.join(", ");
+
+                format!(
+                    "enum value `{}({})` cannot be created as `{}({})`.",
+                    name, def, name, expr
+                )
+            }
+            SemError::EnumValueWithoutData(ref name) => {
+                format!("enum value `{}` does not carry data.", name)
+            }
+            SemError::EnumValueArgsExpected(ref name) => {
+                format!("enum value `{}` needs to be created with arguments.", name)
+            }
+            SemError::UnknownModule(ref name) => format!("module `{}` does not exist.", name),
+            SemError::UnknownModuleItem(ref module, ref name) => {
+                format!("module `{}` does not define `{}`.", module, name)
+            }
+            SemError::NotAccessible(ref name) => format!("`{}` is not public.", name),
+            SemError::ModuleExpected(ref name) => format!("`{}` is not a module.", name),
+            SemError::ModuleUsedAsIdentifier => "module cannot be used as identifier.".into(),
+            SemError::ShadowModule(ref name) => format!("can not shadow module `{}`.", name),
         }
     }
 }
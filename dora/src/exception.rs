@@ -1,4 +1,6 @@
+use std::iter::Peekable;
 use std::ptr;
+use std::vec;
 
 use crate::baseline::fct::{CatchType, JitFctId};
 use crate::baseline::map::CodeDescriptor;
@@ -10,7 +12,8 @@ use crate::object::{alloc, Array, IntArray, Obj, Ref, StackTraceElement, Str, Th
 use crate::os::signal::Trap;
 use crate::stdlib;
 use crate::threads::THREAD;
-use crate::vm::{get_vm, FctParent, VM};
+use crate::vm::{get_vm, FctId, FctParent, VM};
+use dora_parser::lexer::position::Position;
 
 pub struct Stacktrace {
     elems: Vec<StackElem>,
@@ -86,21 +89,35 @@ impl DoraToNativeInfo {
     }
 }
 
+// Positions of the active interpreter invocations, innermost first. The
+// trampolines of interpreted functions show up in the same order while
+// walking the stack.
+type InterpreterFrames = Peekable<vec::IntoIter<(FctId, Option<Position>)>>;
+
+fn interpreter_frames() -> InterpreterFrames {
+    THREAD.with(|thread| {
+        let thread = thread.borrow();
+        thread.interpreter.positions().into_iter().peekable()
+    })
+}
+
 pub fn stacktrace_from_es(vm: &VM, es: &ExecState) -> Stacktrace {
     let mut stacktrace = Stacktrace::new();
+    let mut interpreter = interpreter_frames();
     let fp = fp_from_execstate(es);
-    frames_from_pc(&mut stacktrace, vm, es.pc, fp);
-    frames_from_dtns(&mut stacktrace, vm);
+    frames_from_pc(&mut stacktrace, vm, &mut interpreter, es.pc, fp);
+    frames_from_dtns(&mut stacktrace, vm, &mut interpreter);
     return stacktrace;
 }
 
 pub fn stacktrace_from_last_dtn(vm: &VM) -> Stacktrace {
     let mut stacktrace = Stacktrace::new();
-    frames_from_dtns(&mut stacktrace, vm);
+    let mut interpreter = interpreter_frames();
+    frames_from_dtns(&mut stacktrace, vm, &mut interpreter);
     return stacktrace;
 }
 
-fn frames_from_dtns(stacktrace: &mut Stacktrace, vm: &VM) {
+fn frames_from_dtns(stacktrace: &mut Stacktrace, vm: &VM, interpreter: &mut InterpreterFrames) {
     let mut dtn_ptr = THREAD.with(|thread| {
         let thread = thread.borrow();
         let dtn = thread.dtn();
@@ -114,21 +131,27 @@ fn frames_from_dtns(stacktrace: &mut Stacktrace, vm: &VM) {
         let pc: usize = dtn.pc;
         let fp: usize = dtn.fp;
 
-        frames_from_pc(stacktrace, vm, pc, fp);
+        frames_from_pc(stacktrace, vm, interpreter, pc, fp);
 
         dtn_ptr = dtn.last
     }
 }
 
-fn frames_from_pc(stacktrace: &mut Stacktrace, vm: &VM, pc: usize, mut fp: usize) {
-    if !determine_stack_entry(stacktrace, vm, pc) {
+fn frames_from_pc(
+    stacktrace: &mut Stacktrace,
+    vm: &VM,
+    interpreter: &mut InterpreterFrames,
+    pc: usize,
+    mut fp: usize,
+) {
+    if !determine_stack_entry(stacktrace, vm, interpreter, pc) {
         return;
     }
 
     while fp != 0 {
         let ra = unsafe { *((fp + 8) as *const usize) };
 
-        if !determine_stack_entry(stacktrace, vm, ra) {
+        if !determine_stack_entry(stacktrace, vm, interpreter, ra) {
             return;
         }
 
@@ -136,7 +159,12 @@ fn frames_from_pc(stacktrace: &mut Stacktrace, vm: &VM, pc: usize, mut fp: usize
     }
 }
 
-fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize) -> bool {
+fn determine_stack_entry(
+    stacktrace: &mut Stacktrace,
+    vm: &VM,
+    interpreter: &mut InterpreterFrames,
+    pc: usize,
+) -> bool {
     let code_map = vm.code_map.lock();
     let data = code_map.get(pc.into());
 
@@ -167,10 +195,32 @@ fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize) -> boo
             true
         }
 
+        Some(CodeDescriptor::InterpreterEntry(fct_id)) => {
+            let jit_fct = vm.jit_fcts.idx(fct_id);
+            let fct = vm.fcts.idx(jit_fct.fct_id());
+            let fct = fct.read();
+
+            // the trampoline hasn't entered the interpreter yet when its
+            // function has no frame on top
+            let pos = match interpreter.peek() {
+                Some(&(id, pos)) if id == fct.id => {
+                    interpreter.next();
+                    pos.unwrap_or(fct.ast.pos)
+                }
+
+                _ => fct.ast.pos,
+            };
+
+            stacktrace.push_entry(fct_id, pos.line as i32);
+
+            true
+        }
+
         Some(CodeDescriptor::TrapThunk) => true,
         Some(CodeDescriptor::ThrowThunk) => true,
         Some(CodeDescriptor::AllocThunk) => true,
         Some(CodeDescriptor::DoraEntry) => false,
+        Some(CodeDescriptor::InterpreterCall(_)) => false,
 
         _ => {
             println!("data = {:?}, pc = {:x}", data, pc);
@@ -180,6 +230,13 @@ fn determine_stack_entry(stacktrace: &mut Stacktrace, vm: &VM, pc: usize) -> boo
     }
 }
 
+// Aborts for a runtime error detected outside of compiled code (e.g. in the
+// interpreter), like the trap thunk does for compiled code.
+pub fn trap(trap: Trap) -> ! {
+    stdlib::trap(trap.int());
+    unreachable!("trap returned");
+}
+
 #[derive(PartialEq, Eq, Debug)]
 enum HandlerFound {
     Yes,
@@ -254,7 +311,10 @@ fn find_handler(
     };
 
     match data {
-        Some(CodeDescriptor::DoraFct(fct_id)) | Some(CodeDescriptor::NativeThunk(fct_id)) => {
+        Some(CodeDescriptor::DoraFct(fct_id))
+        | Some(CodeDescriptor::NativeThunk(fct_id))
+        | Some(CodeDescriptor::InterpreterEntry(fct_id))
+        | Some(CodeDescriptor::InterpreterCall(fct_id)) => {
             let jit_fct = vm.jit_fcts.idx(fct_id);
             let jit_fct = jit_fct.to_base().expect("baseline expected");
             let clsptr = exception.header().vtbl().classptr();
@@ -283,11 +343,6 @@ fn find_handler(
                     resume.fp = fp;
 
                     return HandlerFound::Yes;
-                } else if pc > entry.try_end {
-                    // exception handlers are sorted, no more possible handlers
-                    // in this function
-
-                    return HandlerFound::No;
                 }
             }
 
@@ -11,6 +11,7 @@ pub fn get_rootset(vm: &VM, threads: &[Arc<DoraThread>]) -> Vec<Slot> {
 
     determine_rootset_from_stack(&mut rootset, vm, threads);
     determine_rootset_from_handles(&mut rootset, threads);
+    determine_rootset_from_interpreter(&mut rootset, threads);
 
     determine_rootset_from_globals(&mut rootset, vm);
 
@@ -26,16 +27,22 @@ fn determine_rootset_from_handles(rootset: &mut Vec<Slot>, threads: &[Arc<DoraTh
     }
 }
 
+fn determine_rootset_from_interpreter(rootset: &mut Vec<Slot>, threads: &[Arc<DoraThread>]) {
+    for thread in threads {
+        for root in thread.interpreter.roots() {
+            rootset.push(Slot::at(root));
+        }
+    }
+}
+
 fn determine_rootset_from_globals(rootset: &mut Vec<Slot>, vm: &VM) {
     for glob in vm.globals.iter() {
         let glob = glob.lock();
 
-        if !glob.ty.reference_type() {
-            continue;
+        for offset in glob.ty.ref_offsets(vm) {
+            let slot = Slot::at(glob.address_value.offset(offset as usize));
+            rootset.push(slot);
         }
-
-        let slot = Slot::at(glob.address_value);
-        rootset.push(slot);
     }
 }
 
@@ -100,8 +107,13 @@ fn determine_rootset(rootset: &mut Vec<Slot>, vm: &VM, fp: usize, pc: usize) ->
 
         Some(CodeDescriptor::AllocThunk) => true,
         Some(CodeDescriptor::NativeThunk(_)) => true,
+        Some(CodeDescriptor::InterpreterEntry(_)) => true,
         Some(CodeDescriptor::DoraEntry) => false,
 
+        // frames below are interpreted, their roots are found through
+        // the interpreter frames of the thread
+        Some(CodeDescriptor::InterpreterCall(_)) => false,
+
         _ => {
             println!("data = {:?}", data);
             panic!("invalid stack frame");
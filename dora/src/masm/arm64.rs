@@ -139,6 +139,14 @@ impl MacroAssembler {
         self.emit_bailout_info(BailoutInfo::VirtCompile(index, TypeParams::empty()));
     }
 
+    pub fn lea(&mut self, dest: Reg, mem: Mem) {
+        match mem {
+            Mem::Local(offset) => self.int_add_imm(MachineMode::Ptr, dest, REG_FP, offset as i64),
+            Mem::Base(base, disp) => self.int_add_imm(MachineMode::Ptr, dest, base, disp as i64),
+            Mem::Index(_, _, _, _) | Mem::Offset(_, _, _) => unimplemented!(),
+        }
+    }
+
     pub fn load_array_elem(&mut self, mode: MachineMode, dest: ExprStore, array: Reg, index: Reg) {
         self.load_mem(
             mode,
@@ -283,6 +291,23 @@ impl MacroAssembler {
         self.emit_u32(asm::br(reg));
     }
 
+    // jumps to targets[index], index needs to be in bounds.
+    // the table consists of branch instructions following the indirect jump.
+    pub fn jump_table(&mut self, index: Reg, targets: &[Label]) {
+        // zero-extend 32-bit index
+        self.copy_reg(MachineMode::Int32, index, index);
+
+        let table = self.get_scratch();
+        // table starts after adr, add and br
+        self.emit_u32(asm::adr(*table, 12));
+        self.emit_u32(asm::add_shreg(1, *table, *table, index, Shift::LSL, 2));
+        self.emit_u32(asm::br(*table));
+
+        for &target in targets {
+            self.jump(target);
+        }
+    }
+
     pub fn int_div(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
         let x64 = match mode {
             MachineMode::Int32 => 0,
@@ -586,8 +611,6 @@ impl MacroAssembler {
         element_size: i32,
         with_header: bool,
     ) {
-        assert!(element_size == 1 || element_size == 2 || element_size == 4 || element_size == 8);
-
         let header_size = if with_header {
             Header::size() + ptr_width()
         } else {
@@ -601,7 +624,7 @@ impl MacroAssembler {
                 0
             };
 
-        if element_size != 1 {
+        if element_size == 2 || element_size == 4 || element_size == 8 {
             let shift = match element_size {
                 2 => 1,
                 4 => 2,
@@ -610,6 +633,11 @@ impl MacroAssembler {
             };
 
             self.emit_u32(asm::lsl_imm(1, dest, length, shift));
+        } else if element_size != 1 {
+            // element size is not a power of two (e.g. for structs)
+            let scratch = self.get_scratch();
+            self.load_int_const(MachineMode::Int64, *scratch, element_size as i64);
+            self.emit_u32(asm::mul(1, dest, length, *scratch));
         } else {
             self.copy_reg(MachineMode::Ptr, dest, length);
         }
@@ -764,7 +792,7 @@ impl MacroAssembler {
         }
     }
 
-    fn emit_barrier(&mut self, src: Reg, card_table_offset: usize) {
+    pub fn emit_barrier(&mut self, src: Reg, card_table_offset: usize) {
         self.emit_u32(asm::lsr_imm(1, src, src, CARD_SIZE_BITS as u32));
         let scratch = self.get_scratch();
         self.load_int_const(MachineMode::Ptr, *scratch, card_table_offset as i64);
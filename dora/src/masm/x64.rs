@@ -135,6 +135,10 @@ impl MacroAssembler {
         ));
     }
 
+    pub fn lea(&mut self, dest: Reg, mem: Mem) {
+        asm::lea(self, dest, mem);
+    }
+
     pub fn load_array_elem(&mut self, mode: MachineMode, dest: ExprStore, array: Reg, index: Reg) {
         self.load_mem(
             mode,
@@ -312,6 +316,28 @@ impl MacroAssembler {
         asm::emit_jmp_reg(self, reg);
     }
 
+    // jumps to targets[index], index needs to be in bounds.
+    // the table consists of 5-byte jmp instructions following the indirect jump.
+    pub fn jump_table(&mut self, index: Reg, targets: &[Label]) {
+        // zero-extend 32-bit index
+        self.copy_reg(MachineMode::Int32, index, index);
+
+        let table = self.get_scratch();
+        asm::lea(self, *table, Mem::Base(RIP, 0));
+        let lea_end = self.pos();
+
+        asm::lea(self, index, Mem::Index(index, index, 4, 0));
+        asm::emit_add_reg_reg(self, 1, index, *table);
+        asm::emit_jmp_reg(self, *table);
+
+        let table_start = self.pos();
+        self.emit_u32_at((lea_end - 4) as i32, (table_start - lea_end) as u32);
+
+        for &target in targets {
+            asm::emit_jmp(self, target);
+        }
+    }
+
     pub fn int_div(&mut self, mode: MachineMode, dest: Reg, lhs: Reg, rhs: Reg) {
         self.div_common(mode, dest, lhs, rhs, RAX);
     }
@@ -606,8 +632,6 @@ impl MacroAssembler {
         element_size: i32,
         with_header: bool,
     ) {
-        assert!(element_size == 1 || element_size == 2 || element_size == 4 || element_size == 8);
-
         let header_size = if with_header {
             Header::size() + ptr_width()
         } else {
@@ -621,7 +645,20 @@ impl MacroAssembler {
                 0
             };
 
-        asm::lea(self, dest, Mem::Offset(length, element_size, size));
+        if element_size == 1 || element_size == 2 || element_size == 4 || element_size == 8 {
+            asm::lea(self, dest, Mem::Offset(length, element_size, size));
+        } else {
+            // element size can't be encoded as scale (e.g. for structs)
+            let scratch = self.get_scratch();
+            self.load_int_const(MachineMode::Int64, *scratch, element_size as i64);
+
+            if dest != length {
+                asm::emit_mov_reg_reg(self, 1, length, dest);
+            }
+
+            asm::emit_imul_reg_reg(self, 1, *scratch, dest);
+            asm::emit_addq_imm_reg(self, size, dest);
+        }
 
         if element_size != ptr_width() {
             asm::emit_andq_imm_reg(self, -ptr_width(), dest);
@@ -718,7 +755,7 @@ impl MacroAssembler {
         }
     }
 
-    fn emit_barrier(&mut self, src: Reg, card_table_offset: usize) {
+    pub fn emit_barrier(&mut self, src: Reg, card_table_offset: usize) {
         asm::emit_shr_reg_imm(self, 1, src, CARD_SIZE_BITS as u8);
 
         // test if card table offset fits into displacement of memory store
@@ -279,6 +279,11 @@ impl Obj {
             return;
         }
 
+        if let InstanceSize::StructArray(element_size) = cls.size {
+            self.visit_struct_array_elements(element_size, &cls.ref_fields, &mut f);
+            return;
+        }
+
         let addr = self.address();
 
         for &offset in &cls.ref_fields {
@@ -287,6 +292,25 @@ impl Obj {
         }
     }
 
+    fn visit_struct_array_elements<F>(&mut self, element_size: i32, ref_fields: &[i32], f: &mut F)
+    where
+        F: FnMut(Slot),
+    {
+        let array = unsafe { &*(self as *const _ as *const ByteArray) };
+
+        // walk through all references embedded in each element
+        let mut ptr = Address::from_ptr(array.data());
+        let last = ptr.offset(array.len() * element_size as usize);
+
+        while ptr < last {
+            for &offset in ref_fields {
+                f(Slot::at(ptr.offset(offset as usize)));
+            }
+
+            ptr = ptr.offset(element_size as usize);
+        }
+    }
+
     pub fn visit_reference_fields_within<F>(&mut self, limit: Address, mut f: F)
     where
         F: FnMut(Slot),
@@ -312,6 +336,11 @@ impl Obj {
             return;
         }
 
+        if let InstanceSize::StructArray(element_size) = cls.size {
+            self.visit_struct_array_elements(element_size, &cls.ref_fields, &mut f);
+            return;
+        }
+
         let addr = self.address();
 
         // visit the whole object all the time
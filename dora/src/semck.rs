@@ -1,11 +1,15 @@
 use crate::error::msg::SemError;
 use crate::mem;
-use crate::sym::Sym::{SymClass, SymClassTypeParam, SymEnum, SymFctTypeParam, SymStruct, SymTrait};
+use crate::sym::Sym::{
+    self, SymClass, SymClassTypeParam, SymEnum, SymFctTypeParam, SymModule, SymStruct, SymTrait,
+};
 use crate::ty::{BuiltinType, TypeList};
 use crate::typeck;
-use crate::vm::{FileId, NodeMap, VM};
+use crate::vm::{FctSrc, FileId, IdentType, ModuleId, NodeMap, VM};
 use dora_parser::ast::Type::{TypeBasic, TypeLambda, TypeSelf, TypeTuple};
-use dora_parser::ast::{Expr, ExprBlockType, Stmt, Type};
+use dora_parser::ast::{Expr, ExprBlockType, ExprMatchType, MatchPatternData, Stmt, Type};
+use dora_parser::interner::Name;
+use dora_parser::lexer::position::Position;
 
 mod abstractck;
 mod clsdefck;
@@ -17,6 +21,7 @@ mod globaldef;
 mod globaldefck;
 mod implck;
 mod impldefck;
+mod importck;
 mod nameck;
 mod prelude;
 mod returnck;
@@ -57,6 +62,10 @@ pub fn check<'ast>(vm: &mut VM<'ast>) {
     );
     return_on_error!(vm);
 
+    // resolve `import` and `use` declarations of every file
+    importck::check(vm, &vm.ast);
+    return_on_error!(vm);
+
     // define internal classes
     prelude::internal_classes(vm);
 
@@ -190,12 +199,81 @@ fn init_global_addresses<'ast>(vm: &VM<'ast>) {
     }
 }
 
+// resolves the modules of a qualified name, e.g. `a::b` in `a::b::Foo`
+pub fn lookup_module_path(vm: &VM, file: FileId, pos: Position, path: &[Name]) -> Option<ModuleId> {
+    let mut module = match vm.lookup(file, path[0]) {
+        Some(SymModule(module)) => module,
+
+        sym => {
+            let name = vm.interner.str(path[0]).to_string();
+            let msg = if sym.is_some() {
+                SemError::ModuleExpected(name)
+            } else {
+                SemError::UnknownModule(name)
+            };
+
+            vm.diag.lock().report(file, pos, msg);
+            return None;
+        }
+    };
+
+    for &name in &path[1..] {
+        module = match lookup_module_item(vm, file, pos, module, name)? {
+            SymModule(module) => module,
+
+            _ => {
+                let name = vm.interner.str(name).to_string();
+                vm.diag
+                    .lock()
+                    .report(file, pos, SemError::ModuleExpected(name));
+                return None;
+            }
+        };
+    }
+
+    Some(module)
+}
+
+// resolves an item of the given module and checks that it is visible in the file
+pub fn lookup_module_item(
+    vm: &VM,
+    file: FileId,
+    pos: Position,
+    module: ModuleId,
+    name: Name,
+) -> Option<Sym> {
+    let module_name = vm.module_name(module);
+    let item_name = vm.interner.str(name).to_string();
+
+    let sym = match vm.module(module).symbols.get(&name) {
+        Some(sym) => sym.clone(),
+
+        None => {
+            let msg = SemError::UnknownModuleItem(module_name, item_name);
+            vm.diag.lock().report(file, pos, msg);
+            return None;
+        }
+    };
+
+    if !vm.is_accessible(file, module, name) {
+        let msg = SemError::NotAccessible(format!("{}::{}", module_name, item_name));
+        vm.diag.lock().report(file, pos, msg);
+    }
+
+    Some(sym)
+}
+
 pub fn read_type<'ast>(vm: &VM<'ast>, file: FileId, t: &'ast Type) -> Option<BuiltinType> {
     match *t {
         TypeSelf(_) => Some(BuiltinType::This),
 
         TypeBasic(ref basic) => {
-            let sym = vm.sym.lock().get(basic.name);
+            let sym = if basic.path.is_empty() {
+                vm.lookup(file, basic.name)
+            } else {
+                let module = lookup_module_path(vm, file, basic.pos, &basic.path)?;
+                Some(lookup_module_item(vm, file, basic.pos, module, basic.name)?)
+            };
             if let Some(sym) = sym {
                 match sym {
                     SymClass(cls_id) => {
@@ -296,7 +374,7 @@ pub fn read_type<'ast>(vm: &VM<'ast>, file: FileId, t: &'ast Type) -> Option<Bui
                             vm.diag.lock().report(file, basic.pos, msg);
                         }
 
-                        return Some(BuiltinType::Enum(enum_id));
+                        return Some(vm.enums[enum_id].read().ty());
                     }
 
                     SymClassTypeParam(cls_id, type_param_id) => {
@@ -390,6 +468,42 @@ pub fn expr_block_always_returns(e: &ExprBlockType) -> bool {
     returnck::expr_block_returns_value(e).is_ok()
 }
 
+// returns the index of the match case for every value of the enum
+pub fn match_case_table(vm: &VM, src: &FctSrc, e: &ExprMatchType) -> Vec<usize> {
+    let enum_id = match src.ty(e.expr.id()) {
+        BuiltinType::Enum(id) | BuiltinType::EnumObject(id) => id,
+        _ => unreachable!(),
+    };
+
+    let values = vm.enums[enum_id].read().values.len();
+    let mut table: Vec<Option<usize>> = vec![None; values];
+
+    for (idx, case) in e.cases.iter().enumerate() {
+        for pattern in &case.patterns {
+            match pattern.data {
+                MatchPatternData::Underscore => {
+                    for entry in &mut table {
+                        entry.get_or_insert(idx);
+                    }
+                }
+
+                MatchPatternData::Ident(_) => match src.map_idents.get(pattern.id) {
+                    Some(&IdentType::EnumValue(_, value)) => {
+                        table[value as usize].get_or_insert(idx);
+                    }
+
+                    _ => unreachable!(),
+                },
+            }
+        }
+    }
+
+    table
+        .into_iter()
+        .map(|entry| entry.expect("match not exhaustive"))
+        .collect()
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::error::msg::SemError;
@@ -67,7 +67,7 @@ impl<'x, 'ast> ClsCheck<'x, 'ast> {
 
     fn check_parent_class(&mut self, parent_class: &'ast ast::ParentClass) {
         let name = self.vm.interner.str(parent_class.name).to_string();
-        let sym = self.vm.sym.lock().get(parent_class.name);
+        let sym = self.vm.lookup(self.file_id.into(), parent_class.name);
 
         match sym {
             Some(Sym::SymClass(cls_id)) => {
@@ -2,6 +2,8 @@ use dora_parser::ast::visit::Visitor;
 use dora_parser::ast::{Ast, Enum};
 
 use crate::error::msg::SemError;
+use crate::semck;
+use crate::ty::BuiltinType;
 use crate::vm::{EnumId, NodeMap, VM};
 
 pub fn check<'ast>(vm: &mut VM<'ast>, ast: &'ast Ast, map_enum_defs: &NodeMap<EnumId>) {
@@ -30,21 +32,39 @@ impl<'x, 'ast> Visitor<'ast> for EnumCheck<'x, 'ast> {
     fn visit_enum(&mut self, e: &'ast Enum) {
         let enum_id = *self.map_enum_defs.get(e.id).unwrap();
 
+        let file = self.vm.enums[enum_id].read().file;
+
+        // payload types may refer to the enum itself, resolve them
+        // before locking it for writing
+        let value_types = e
+            .values
+            .iter()
+            .map(|value| {
+                value.types.as_ref().map(|types| {
+                    types
+                        .iter()
+                        .map(|ty| semck::read_type(self.vm, file, ty).unwrap_or(BuiltinType::Error))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
         let mut xenum = self.vm.enums[enum_id].write();
         let mut enum_value_int: u32 = 0;
 
-        for value in &e.values {
-            let ident = value.to_ident().expect("ident expected");
+        xenum.value_defs = vec![None; value_types.len()];
+        xenum.value_types = value_types;
 
-            xenum.values.push(ident.name);
-            let result = xenum.name_to_value.insert(ident.name, enum_value_int);
+        for value in &e.values {
+            xenum.values.push(value.name);
+            let result = xenum.name_to_value.insert(value.name, enum_value_int);
 
             if result.is_some() {
-                let name = self.vm.interner.str(ident.name).to_string();
+                let name = self.vm.interner.str(value.name).to_string();
                 self.vm
                     .diag
                     .lock()
-                    .report(xenum.file, ident.pos, SemError::ShadowEnumValue(name));
+                    .report(xenum.file, value.pos, SemError::ShadowEnumValue(name));
             }
 
             enum_value_int += 1;
@@ -74,4 +94,16 @@ mod tests {
             SemError::ShadowEnumValue("A".into()),
         );
     }
+
+    #[test]
+    fn enum_with_payload() {
+        ok("enum Shape { Circle(Double), Rect(Double, Double), Empty }");
+        ok("enum List { Cons(Int, List), Nil }");
+        ok("enum Foo { A(), B }");
+        err(
+            "enum Foo { A(Bar) }",
+            pos(1, 14),
+            SemError::UnknownType("Bar".into()),
+        );
+    }
 }
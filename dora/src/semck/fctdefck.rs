@@ -58,6 +58,30 @@ pub fn check<'a, 'ast>(vm: &VM<'ast>) {
                 }
             }
 
+            FctParent::Function(_) => {
+                // lambdas can use type params of the enclosing function and class
+                let owner_id = vm.lambda_owner(&*fct);
+                let owner = vm.fcts.idx(owner_id);
+                let owner = owner.read();
+
+                if let FctParent::Class(owner_class) = owner.parent {
+                    let cls = vm.classes.idx(owner_class);
+                    let cls = cls.read();
+
+                    for (type_param_id, param) in cls.type_params.iter().enumerate() {
+                        let sym = Sym::SymClassTypeParam(cls.id, type_param_id.into());
+                        vm.sym.lock().insert(param.name, sym);
+                    }
+                }
+
+                for (type_param_id, param) in owner.type_params.iter().enumerate() {
+                    let sym = Sym::SymFctTypeParam(owner_id, type_param_id.into());
+                    vm.sym.lock().insert(param.name, sym);
+                }
+
+                fct.param_types.push(BuiltinType::Ptr);
+            }
+
             FctParent::None => {}
         }
 
@@ -284,6 +308,14 @@ impl<'a, 'ast> Visitor<'ast> for FctDefCheck<'a, 'ast> {
         }
     }
 
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        match *e {
+            // lambdas are checked on their own
+            Expr::ExprLambda(_) => {}
+            _ => visit::walk_expr(self, e),
+        }
+    }
+
     fn visit_type(&mut self, t: &'ast Type) {
         self.current_type =
             semck::read_type(self.vm, self.fct.file, t).unwrap_or(BuiltinType::Unit);
@@ -5,7 +5,9 @@ use std::sync::Arc;
 use crate::class::{self, ClassId};
 use crate::error::msg::SemError;
 use crate::gc::Address;
-use crate::sym::Sym::{self, SymClass, SymConst, SymEnum, SymFct, SymGlobal, SymStruct, SymTrait};
+use crate::sym::Sym::{
+    self, SymClass, SymConst, SymEnum, SymFct, SymGlobal, SymModule, SymStruct, SymTrait,
+};
 use crate::ty::BuiltinType;
 use crate::vm;
 use crate::vm::*;
@@ -52,6 +54,29 @@ struct GlobalDef<'x, 'ast: 'x> {
     map_enum_defs: &'x mut NodeMap<EnumId>,
 }
 
+impl<'x, 'ast> GlobalDef<'x, 'ast> {
+    // items of the root module are global, all other modules have their own namespace
+    fn insert(&mut self, name: Name, sym: Sym, is_pub: bool) -> Option<Sym> {
+        let module = self.vm.file_module(self.file_id.into());
+
+        if module.is_root() {
+            return self.vm.sym.lock().insert(name, sym);
+        }
+
+        let module = &mut self.vm.modules[module.to_usize()];
+
+        if let Some(existing) = module.symbols.get(&name) {
+            return Some(existing.clone());
+        }
+
+        if is_pub {
+            module.public.insert(name);
+        }
+
+        module.symbols.insert(name, sym)
+    }
+}
+
 impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
     fn visit_file(&mut self, f: &'ast File) {
         walk_file(self, f);
@@ -73,7 +98,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
 
         self.map_trait_defs.insert(t.id, id);
 
-        if let Some(sym) = self.vm.sym.lock().insert(t.name, sym) {
+        if let Some(sym) = self.insert(t.name, sym, t.is_pub) {
             report(self.vm, t.name, self.file_id.into(), t.pos, sym);
         }
     }
@@ -102,7 +127,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
         let sym = SymGlobal(id);
         self.map_global_defs.insert(g.id, id);
 
-        if let Some(sym) = self.vm.sym.lock().insert(g.name, sym) {
+        if let Some(sym) = self.insert(g.name, sym, g.is_pub) {
             report(self.vm, g.name, self.file_id.into(), g.pos, sym);
         }
     }
@@ -145,7 +170,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
 
         let sym = SymConst(id);
 
-        if let Some(sym) = self.vm.sym.lock().insert(c.name, sym) {
+        if let Some(sym) = self.insert(c.name, sym, c.is_pub) {
             report(self.vm, c.name, self.file_id.into(), c.pos, sym);
         }
     }
@@ -198,7 +223,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
 
         self.map_cls_defs.insert(c.id, id);
 
-        if let Some(sym) = self.vm.sym.lock().insert(c.name, sym) {
+        if let Some(sym) = self.insert(c.name, sym, c.is_pub) {
             report(self.vm, c.name, self.file_id.into(), c.pos, sym);
         }
     }
@@ -225,7 +250,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
 
         self.map_struct_defs.insert(s.id, id);
 
-        if let Some(sym) = self.vm.sym.lock().insert(s.name, sym) {
+        if let Some(sym) = self.insert(s.name, sym, s.is_pub) {
             report(self.vm, s.name, self.file_id.into(), s.pos, sym);
         }
     }
@@ -250,7 +275,7 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             has_open: f.has_open,
             has_final: f.has_final,
             has_optimize_immediately: f.has_optimize_immediately,
-            is_pub: true,
+            is_pub: f.is_pub,
             is_static: false,
             is_abstract: false,
             is_test: f.is_test,
@@ -268,7 +293,16 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             kind,
         };
 
-        if let Err(sym) = self.vm.add_fct_to_sym(fct) {
+        let module = self.vm.file_module(self.file_id.into());
+
+        let result = if module.is_root() {
+            self.vm.add_fct_to_sym(fct).err()
+        } else {
+            let fct_id = self.vm.add_fct(fct);
+            self.insert(f.name, SymFct(fct_id), f.is_pub)
+        };
+
+        if let Some(sym) = result {
             report(self.vm, f.name, self.file_id.into(), f.pos, sym);
         }
     }
@@ -282,6 +316,9 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
             name: e.name,
             values: Vec::new(),
             name_to_value: HashMap::new(),
+            value_types: Vec::new(),
+            has_payload: e.values.iter().any(|value| value.types.is_some()),
+            value_defs: Vec::new(),
         };
 
         self.vm.enums.push(RwLock::new(xenum));
@@ -289,13 +326,13 @@ impl<'x, 'ast> Visitor<'ast> for GlobalDef<'x, 'ast> {
 
         let sym = SymEnum(id);
 
-        if let Some(sym) = self.vm.sym.lock().insert(e.name, sym) {
+        if let Some(sym) = self.insert(e.name, sym, e.is_pub) {
             report(self.vm, e.name, self.file_id.into(), e.pos, sym);
         }
     }
 }
 
-fn report(vm: &VM, name: Name, file: FileId, pos: Position, sym: Sym) {
+pub fn report(vm: &VM, name: Name, file: FileId, pos: Position, sym: Sym) {
     let name = vm.interner.str(name).to_string();
 
     let msg = match sym {
@@ -306,6 +343,7 @@ fn report(vm: &VM, name: Name, file: FileId, pos: Position, sym: Sym) {
         SymGlobal(_) => SemError::ShadowGlobal(name),
         SymConst(_) => SemError::ShadowConst(name),
         SymEnum(_) => SemError::ShadowEnum(name),
+        SymModule(_) => SemError::ShadowModule(name),
         _ => unimplemented!(),
     };
 
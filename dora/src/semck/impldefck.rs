@@ -58,7 +58,7 @@ impl<'x, 'ast> Visitor<'ast> for ImplCheck<'x, 'ast> {
 
         if let Some(ref trait_type) = i.trait_type {
             if let Some(trait_name) = trait_type.to_basic_without_type_params() {
-                if let Some(Sym::SymTrait(trait_id)) = self.vm.sym.lock().get(trait_name) {
+                if let Some(Sym::SymTrait(trait_id)) = self.vm.lookup(ximpl.file, trait_name) {
                     ximpl.trait_id = Some(trait_id);
                 } else {
                     let name = self.vm.interner.str(trait_name).to_string();
@@ -78,7 +78,7 @@ impl<'x, 'ast> Visitor<'ast> for ImplCheck<'x, 'ast> {
         }
 
         if let Some(class_name) = i.class_type.to_basic_without_type_params() {
-            if let Some(Sym::SymClass(class_id)) = self.vm.sym.lock().get(class_name) {
+            if let Some(Sym::SymClass(class_id)) = self.vm.lookup(ximpl.file, class_name) {
                 ximpl.class_id = Some(class_id);
             } else {
                 let name = self.vm.interner.str(class_name).to_string();
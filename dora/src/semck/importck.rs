@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::error::msg::SemError;
+use crate::semck;
+use crate::semck::globaldef::report;
+use crate::sym::Sym::{self, SymModule};
+use crate::vm::{FileId, ModuleId, VM};
+
+use dora_parser::ast::Ast;
+use dora_parser::ast::Elem::{ElemImport, ElemUse};
+use dora_parser::interner::Name;
+use dora_parser::lexer::position::Position;
+
+pub fn check<'ast>(vm: &mut VM<'ast>, ast: &'ast Ast) {
+    let mut file_imports = Vec::with_capacity(ast.files.len());
+
+    for (file_id, file) in ast.files.iter().enumerate() {
+        let file_id: FileId = (file_id as u32).into();
+        let mut imports: HashMap<Name, Sym> = HashMap::new();
+
+        for elem in &file.elements {
+            let (pos, sym, name) = match *elem {
+                ElemImport(ref import) => (
+                    import.pos,
+                    resolve_module(vm, file_id, import.pos, &import.path).map(SymModule),
+                    *import.path.last().unwrap(),
+                ),
+
+                ElemUse(ref xuse) => (
+                    xuse.pos,
+                    resolve_item(vm, file_id, xuse.pos, &xuse.path),
+                    *xuse.path.last().unwrap(),
+                ),
+
+                _ => continue,
+            };
+
+            let sym = match sym {
+                Some(sym) => sym,
+                None => continue,
+            };
+
+            if let Some(existing) = imports.get(&name) {
+                report(vm, name, file_id, pos, existing.clone());
+                continue;
+            }
+
+            imports.insert(name, sym);
+        }
+
+        file_imports.push(imports);
+    }
+
+    vm.file_imports = file_imports;
+}
+
+// module paths in `import` and `use` start at the root module
+fn resolve_module(vm: &VM, file: FileId, pos: Position, path: &[Name]) -> Option<ModuleId> {
+    let mut module = ModuleId::root();
+
+    for &name in path {
+        module = match vm.module(module).symbols.get(&name) {
+            Some(&SymModule(module)) => module,
+
+            _ => {
+                let path: Vec<String> = path
+                    .iter()
+                    .map(|&name| vm.interner.str(name).to_string())
+                    .collect();
+                let msg = SemError::UnknownModule(path.join("::"));
+                vm.diag.lock().report(file, pos, msg);
+
+                return None;
+            }
+        };
+    }
+
+    Some(module)
+}
+
+fn resolve_item(vm: &VM, file: FileId, pos: Position, path: &[Name]) -> Option<Sym> {
+    if path.len() < 2 {
+        let name = vm.interner.str(path[0]).to_string();
+        vm.diag
+            .lock()
+            .report(file, pos, SemError::ModuleExpected(name));
+        return None;
+    }
+
+    let (&name, modules) = path.split_last().unwrap();
+    let module = resolve_module(vm, file, pos, modules)?;
+
+    semck::lookup_module_item(vm, file, pos, module, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::msg::SemError;
+    use crate::semck::tests::*;
+
+    #[test]
+    fn test_unknown_module() {
+        err(
+            "import foo;",
+            pos(1, 1),
+            SemError::UnknownModule("foo".into()),
+        );
+        err(
+            "import foo::bar;",
+            pos(1, 1),
+            SemError::UnknownModule("foo::bar".into()),
+        );
+        err(
+            "use foo::Bar;",
+            pos(1, 1),
+            SemError::UnknownModule("foo".into()),
+        );
+    }
+
+    #[test]
+    fn test_use_without_module() {
+        err(
+            "use Foo;",
+            pos(1, 1),
+            SemError::ModuleExpected("Foo".into()),
+        );
+    }
+}
@@ -1,4 +1,7 @@
+use parking_lot::RwLock;
+
 use crate::error::msg::SemError;
+use crate::semck;
 use crate::vm::*;
 
 use dora_parser::ast::visit::*;
@@ -16,7 +19,8 @@ pub fn check<'ast>(vm: &VM<'ast>) {
     for fct in vm.fcts.iter() {
         let fct = fct.read();
 
-        if !fct.is_src() {
+        // lambdas are checked together with their enclosing function
+        if !fct.is_src() || fct.is_lambda() {
             continue;
         }
 
@@ -29,22 +33,41 @@ pub fn check<'ast>(vm: &VM<'ast>) {
             fct: &fct,
             src: &mut src,
             ast,
+            level: 0,
+            captures: Vec::new(),
         };
 
         nameck.check();
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CaptureKey {
+    // variable of the function that owns the given symbol level
+    Var(usize, VarId),
+
+    // `self` of the enclosing method
+    SelfParam,
+}
+
 struct NameCheck<'a, 'ast: 'a> {
     vm: &'a VM<'ast>,
     fct: &'a Fct<'ast>,
     src: &'a mut FctSrc,
     ast: &'ast Function,
+
+    // first symbol level of this function, variables on
+    // lower levels belong to enclosing functions
+    level: usize,
+
+    // variables of enclosing functions used in this lambda
+    captures: Vec<(CaptureKey, VarId, Position)>,
 }
 
 impl<'a, 'ast> NameCheck<'a, 'ast> {
     fn check(&mut self) {
         self.vm.sym.lock().push_level();
+        self.level = self.vm.sym.lock().levels() - 1;
 
         if self.fct.has_self() {
             // add hidden this parameter for ctors and methods
@@ -108,6 +131,9 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
                 cls.ty
             }
 
+            // hidden parameter of lambdas is the closure object
+            FctParent::Function(_) => BuiltinType::Ptr,
+
             _ => unreachable!(),
         };
 
@@ -134,9 +160,10 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
 
         var.id = var_id;
 
-        let result = match self.vm.sym.lock().get(name) {
-            Some(sym) => {
-                if replacable(&sym) {
+        let result = match self.vm.lookup_with_level(self.fct.file, name) {
+            Some((sym, level)) => {
+                // variables of enclosing functions can always be shadowed
+                if replacable(&sym) || (sym.is_var() && level < self.level) {
                     Ok(var_id)
                 } else {
                     Err(sym)
@@ -256,49 +283,36 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
     }
 
     fn check_expr_ident(&mut self, ident: &'ast ExprIdentType) {
-        let sym = self.vm.sym.lock().get(ident.name);
+        let sym = self.vm.lookup_with_level(self.fct.file, ident.name);
 
         match sym {
-            Some(SymVar(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Var(id));
-            }
-
-            Some(SymGlobal(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Global(id));
-            }
-
-            Some(SymStruct(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Struct(id));
-            }
-
-            Some(SymConst(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Const(id));
-            }
-
-            Some(SymFct(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Fct(id));
-            }
+            Some((SymVar(id), level)) => {
+                let id = if level < self.level {
+                    self.capture(CaptureKey::Var(level, id), ident.name, ident.pos)
+                } else {
+                    id
+                };
 
-            Some(SymClass(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Class(id));
+                self.src.map_idents.insert(ident.id, IdentType::Var(id));
             }
 
-            Some(SymFctTypeParam(fct_id, id)) => {
+            Some((SymFctTypeParam(fct_id, id), _)) => {
                 let ty = BuiltinType::FctTypeParam(fct_id, id);
                 self.src
                     .map_idents
                     .insert(ident.id, IdentType::TypeParam(ty));
             }
 
-            Some(SymClassTypeParam(cls_id, id)) => {
+            Some((SymClassTypeParam(cls_id, id), _)) => {
                 let ty = BuiltinType::ClassTypeParam(cls_id, id);
                 self.src
                     .map_idents
                     .insert(ident.id, IdentType::TypeParam(ty));
             }
 
-            Some(SymEnum(id)) => {
-                self.src.map_idents.insert(ident.id, IdentType::Enum(id));
+            Some((sym, _)) if item_ident_type(&sym).is_some() => {
+                let ident_type = item_ident_type(&sym).unwrap();
+                self.src.map_idents.insert(ident.id, ident_type);
             }
 
             _ => {
@@ -313,9 +327,159 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
         }
     }
 
+    fn check_expr_self(&mut self, expr: &'ast ExprSelfType) {
+        // `self` in a lambda refers to `self` of the enclosing method
+        if self.fct.is_lambda() {
+            let name = self.vm.interner.intern("self");
+            let var_id = self.capture(CaptureKey::SelfParam, name, expr.pos);
+            self.src.map_idents.insert(expr.id, IdentType::Var(var_id));
+        }
+    }
+
+    fn check_expr_super(&mut self, expr: &'ast ExprSuperType) {
+        if self.fct.is_lambda() {
+            report(self.vm, self.fct.file, expr.pos, SemError::SuperUnavailable);
+        }
+    }
+
+    fn check_expr_lambda(&mut self, lambda: &'ast ExprLambdaType) {
+        let ast = &*lambda.fct;
+
+        let fct = Fct {
+            id: FctId(0),
+            file: self.fct.file,
+            pos: ast.pos,
+            ast,
+            name: ast.name,
+            param_types: Vec::new(),
+            return_type: BuiltinType::Unit,
+            parent: FctParent::Function(self.fct.id),
+            has_override: false,
+            has_open: false,
+            has_final: false,
+            has_optimize_immediately: false,
+            is_pub: false,
+            is_static: false,
+            is_abstract: false,
+            is_test: false,
+            use_cannon: false,
+            internal: false,
+            internal_resolved: false,
+            overrides: None,
+            throws: false,
+            is_constructor: false,
+            vtable_index: None,
+            initialized: false,
+            impl_for: None,
+
+            type_params: Vec::new(),
+            kind: FctKind::Source(RwLock::new(FctSrc::new())),
+        };
+
+        let lambda_id = self.vm.add_fct(fct);
+        self.src.map_lambdas.insert(lambda.id, lambda_id);
+
+        let fct = self.vm.fcts.idx(lambda_id);
+        let fct = fct.read();
+        let src = fct.src();
+        let mut src = src.write();
+
+        let captures = {
+            let mut nameck = NameCheck {
+                vm: self.vm,
+                fct: &fct,
+                src: &mut src,
+                ast,
+                level: 0,
+                captures: Vec::new(),
+            };
+
+            nameck.check();
+            nameck.captures
+        };
+
+        // bind captured variables to variables of this function,
+        // variables of functions further out need to be captured by this function as well
+        for (key, inner, pos) in captures {
+            let name = src.vars[inner].name;
+
+            let outer = match key {
+                CaptureKey::Var(level, var_id) if level >= self.level => {
+                    let var = &self.src.vars[var_id];
+
+                    if var.reassignable {
+                        let name = str(self.vm, var.name);
+                        report(
+                            self.vm,
+                            self.fct.file,
+                            pos,
+                            SemError::ReassignableVarCaptured(name),
+                        );
+                    }
+
+                    var_id
+                }
+
+                CaptureKey::Var(_, _) => self.capture(key, name, pos),
+
+                CaptureKey::SelfParam => {
+                    if self.fct.is_lambda() {
+                        self.capture(key, name, pos)
+                    } else if self.fct.has_self() {
+                        self.src.var_self().id
+                    } else {
+                        report(self.vm, self.fct.file, pos, SemError::ThisUnavailable);
+                        src.vars[inner].ty = BuiltinType::Error;
+                        continue;
+                    }
+                }
+            };
+
+            src.captures.push(Capture { outer, inner });
+        }
+    }
+
+    fn capture(&mut self, key: CaptureKey, name: Name, pos: Position) -> VarId {
+        if let Some(&(_, var_id, _)) = self.captures.iter().find(|c| c.0 == key) {
+            return var_id;
+        }
+
+        let var_id = VarId(self.src.vars.len());
+
+        self.src.vars.push(Var {
+            id: var_id,
+            name,
+            ty: BuiltinType::Unit,
+            reassignable: false,
+            node_id: self.ast.id,
+        });
+
+        self.captures.push((key, var_id, pos));
+
+        var_id
+    }
+
     fn check_expr_path(&mut self, path: &'ast ExprPathType) {
         self.visit_expr(&path.lhs);
-        // do not check right hand site of path
+
+        // right hand site of path is only resolved for modules
+        let module = match self.src.map_idents.get(path.lhs.id()) {
+            Some(&IdentType::Module(module)) => module,
+            _ => return,
+        };
+
+        let name = match path.rhs.to_ident() {
+            Some(ident) => ident.name,
+            None => return,
+        };
+
+        let file = self.fct.file;
+
+        if let Some(sym) = semck::lookup_module_item(self.vm, file, path.pos, module, name) {
+            if let Some(ident_type) = item_ident_type(&sym) {
+                self.src.map_idents.insert(path.id, ident_type);
+            }
+        }
     }
 
     fn check_expr_dot(&mut self, dot: &'ast ExprDotType) {
@@ -323,6 +487,112 @@ impl<'a, 'ast> NameCheck<'a, 'ast> {
         // do not check right hand site of dot
     }
 
+    fn check_expr_match(&mut self, expr: &'ast ExprMatchType) {
+        self.visit_expr(&expr.expr);
+
+        for case in &expr.cases {
+            self.vm.sym.lock().push_level();
+
+            for pattern in &case.patterns {
+                self.check_match_pattern(pattern, case.patterns.len() > 1);
+            }
+
+            self.visit_expr(&case.value);
+            self.vm.sym.lock().pop_level();
+        }
+    }
+
+    fn check_match_pattern(&mut self, pattern: &'ast MatchPattern, alternative: bool) {
+        let ident = match pattern.data {
+            MatchPatternData::Underscore => return,
+            MatchPatternData::Ident(ref ident) => ident,
+        };
+
+        for param in ident.params.iter().flatten() {
+            let name = match param.name {
+                Some(name) => name,
+                None => continue,
+            };
+
+            // bindings would be undefined for the other alternatives
+            if alternative {
+                let msg = SemError::MatchBindingInAlternative;
+                report(self.vm, self.fct.file, param.pos, msg);
+                continue;
+            }
+
+            let var_ctxt = Var {
+                id: VarId(0),
+                name,
+                ty: BuiltinType::Unit,
+                reassignable: false,
+                node_id: param.id,
+            };
+
+            match self.add_var(var_ctxt, |sym| !sym.is_class()) {
+                Ok(var_id) => {
+                    self.src.map_vars.insert(param.id, var_id);
+                }
+
+                Err(_) => {
+                    let name = str(self.vm, name);
+                    report(
+                        self.vm,
+                        self.fct.file,
+                        param.pos,
+                        SemError::ShadowClass(name),
+                    );
+                }
+            }
+        }
+
+        let path = &ident.path;
+        let file = self.fct.file;
+
+        // the enum itself might be qualified with modules, e.g. `a::Foo::A`
+        let sym = if path.len() > 2 {
+            let modules = &path[..path.len() - 2];
+            let name = path[path.len() - 2];
+
+            let module = match semck::lookup_module_path(self.vm, file, pattern.pos, modules) {
+                Some(module) => module,
+                None => return,
+            };
+
+            match semck::lookup_module_item(self.vm, file, pattern.pos, module, name) {
+                Some(sym) => Some(sym),
+                None => return,
+            }
+        } else {
+            self.vm.lookup(file, path[0])
+        };
+
+        match sym {
+            Some(SymEnum(id)) if path.len() >= 2 => {
+                self.src.map_idents.insert(pattern.id, IdentType::Enum(id));
+            }
+
+            None => {
+                let name = str(self.vm, path[0]);
+                report(
+                    self.vm,
+                    self.fct.file,
+                    pattern.pos,
+                    SemError::UnknownIdentifier(name),
+                );
+            }
+
+            _ => {
+                report(
+                    self.vm,
+                    self.fct.file,
+                    pattern.pos,
+                    SemError::EnumValueExpected,
+                );
+            }
+        }
+    }
+
     fn check_expr_block(&mut self, block: &'ast ExprBlockType) {
         self.vm.sym.lock().push_level();
 
@@ -385,6 +655,10 @@ impl<'a, 'ast> Visitor<'ast> for NameCheck<'a, 'ast> {
             &ExprPath(ref path) => self.check_expr_path(path),
             &ExprDot(ref dot) => self.check_expr_dot(dot),
             &ExprBlock(ref block) => self.check_expr_block(block),
+            &ExprMatch(ref expr) => self.check_expr_match(expr),
+            &ExprSelf(ref expr) => self.check_expr_self(expr),
+            &ExprSuper(ref expr) => self.check_expr_super(expr),
+            &ExprLambda(ref lambda) => self.check_expr_lambda(lambda),
 
             // no need to handle rest of expressions
             _ => visit::walk_expr(self, e),
@@ -392,6 +666,20 @@ impl<'a, 'ast> Visitor<'ast> for NameCheck<'a, 'ast> {
     }
 }
 
+// identifier type for items that can also be accessed through modules
+fn item_ident_type(sym: &Sym) -> Option<IdentType> {
+    match *sym {
+        SymGlobal(id) => Some(IdentType::Global(id)),
+        SymStruct(id) => Some(IdentType::Struct(id)),
+        SymConst(id) => Some(IdentType::Const(id)),
+        SymFct(id) => Some(IdentType::Fct(id)),
+        SymClass(id) => Some(IdentType::Class(id)),
+        SymEnum(id) => Some(IdentType::Enum(id)),
+        SymModule(id) => Some(IdentType::Module(id)),
+        _ => None,
+    }
+}
+
 fn report(vm: &VM, file: FileId, pos: Position, msg: SemError) {
     vm.diag.lock().report(file, pos, msg);
 }
@@ -532,4 +820,25 @@ mod tests {
     fn for_var() {
         ok("fun f() { for i in range(0, 4) { i; } }");
     }
+
+    #[test]
+    fn lambda_capture() {
+        ok("fun f() { let a = 1; || { a; }; }");
+        ok("fun f(a: Int) { || { || { a; }; }; }");
+        err(
+            "fun f() { var a = 1; || { a; }; }",
+            pos(1, 27),
+            SemError::ReassignableVarCaptured("a".into()),
+        );
+        err(
+            "fun f() { || { a; }; let a = 1; }",
+            pos(1, 16),
+            SemError::UnknownIdentifier("a".into()),
+        );
+        err(
+            "@open class A { fun f() {} } class B: A { fun g() { || { super.f(); }; } }",
+            pos(1, 58),
+            SemError::SuperUnavailable,
+        );
+    }
 }
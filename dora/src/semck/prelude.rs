@@ -76,6 +76,8 @@ fn internal_free_classes<'ast>(vm: &mut VM<'ast>) {
             fields: Vec::new(),
             ref_fields: Vec::new(),
             vtable: None,
+            lambda: None,
+            enum_value: None,
         })));
 
         class_defs.push(Arc::new(RwLock::new(ClassDef {
@@ -87,6 +89,8 @@ fn internal_free_classes<'ast>(vm: &mut VM<'ast>) {
             fields: Vec::new(),
             ref_fields: Vec::new(),
             vtable: None,
+            lambda: None,
+            enum_value: None,
         })));
 
         {
@@ -75,6 +75,7 @@ pub fn expr_returns_value(e: &Expr) -> Result<(), Position> {
     match *e {
         Expr::ExprBlock(ref block) => expr_block_returns_value(block),
         Expr::ExprIf(ref expr) => expr_if_returns_value(expr),
+        Expr::ExprMatch(ref expr) => expr_match_returns_value(expr),
         _ => Err(e.pos()),
     }
 }
@@ -105,6 +106,18 @@ fn expr_if_returns_value(e: &ExprIfType) -> Result<(), Position> {
     }
 }
 
+fn expr_match_returns_value(e: &ExprMatchType) -> Result<(), Position> {
+    if e.cases.is_empty() {
+        return Err(e.pos);
+    }
+
+    for case in &e.cases {
+        expr_returns_value(&case.value)?;
+    }
+
+    Ok(())
+}
+
 fn do_returns_value(s: &StmtDoType) -> Result<(), Position> {
     // return in finally-block is good enough
     if let Some(ref finally_block) = s.finally_block {
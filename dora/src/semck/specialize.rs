@@ -9,7 +9,9 @@ use crate::mem;
 use crate::object::Header;
 use crate::size::InstanceSize;
 use crate::ty::{BuiltinType, TypeList};
-use crate::vm::{CallType, StructData, StructDef, StructDefId, StructFieldDef, StructId, VM};
+use crate::vm::{
+    CallType, EnumId, Fct, FctSrc, StructData, StructDef, StructDefId, StructFieldDef, StructId, VM,
+};
 use crate::vtable::{VTableBox, DISPLAY_SIZE};
 
 pub fn specialize_type(
@@ -53,7 +55,19 @@ pub fn specialize_type(
             BuiltinType::Class(cls_id, list_id)
         }
 
-        BuiltinType::Lambda(_) => unimplemented!(),
+        BuiltinType::Lambda(id) => {
+            let lambda = vm.lambda_types.lock().get(id);
+
+            let params = lambda
+                .params
+                .iter()
+                .map(|&t| specialize_type(vm, t, cls_type_params, fct_type_params))
+                .collect::<Vec<_>>();
+            let ret = specialize_type(vm, lambda.ret, cls_type_params, fct_type_params);
+
+            let id = vm.lambda_types.lock().insert(params, ret);
+            BuiltinType::Lambda(id)
+        }
 
         _ => ty,
     }
@@ -128,11 +142,15 @@ fn create_specialized_struct(vm: &VM, struc: &StructData, type_params: TypeList)
         size = offset + field_size;
         align = max(align, field_align);
 
-        if ty.reference_type() {
-            ref_fields.push(offset);
+        for ref_offset in ty.ref_offsets(vm) {
+            ref_fields.push(offset + ref_offset);
         }
     }
 
+    // every value needs its own storage, even for empty structs
+    let align = max(align, 1);
+    let size = max(mem::align_i32(size, align), 1);
+
     let struct_def = vm.struct_defs.idx(id);
     let mut struct_def = struct_def.lock();
     struct_def.size = size;
@@ -191,6 +209,8 @@ fn create_specialized_class(vm: &VM, cls: &class::Class, type_params: &TypeList)
             fields: Vec::new(),
             ref_fields: Vec::new(),
             vtable: None,
+            lambda: None,
+            enum_value: None,
         })));
 
         id
@@ -206,10 +226,16 @@ fn create_specialized_class(vm: &VM, cls: &class::Class, type_params: &TypeList)
         ref_fields = Vec::new();
 
         size = if cls.is_array {
-            if type_params[0].reference_type() {
+            let element_type = type_params[0];
+
+            if element_type.reference_type() {
                 InstanceSize::ObjArray
+            } else if element_type.is_struct() && !element_type.ref_offsets(vm).is_empty() {
+                // ref_fields stores offsets of references within each element
+                ref_fields = element_type.ref_offsets(vm);
+                InstanceSize::StructArray(element_type.size(vm))
             } else {
-                InstanceSize::Array(type_params[0].size(vm))
+                InstanceSize::Array(element_type.size(vm))
             }
         } else {
             InstanceSize::Str
@@ -254,8 +280,8 @@ fn create_specialized_class(vm: &VM, cls: &class::Class, type_params: &TypeList)
 
             csize = offset + field_size;
 
-            if ty.reference_type() {
-                ref_fields.push(offset);
+            for ref_offset in ty.ref_offsets(vm) {
+                ref_fields.push(offset + ref_offset);
             }
         }
 
@@ -274,7 +300,9 @@ fn create_specialized_class(vm: &VM, cls: &class::Class, type_params: &TypeList)
 
     let (instance_size, element_size) = match size {
         InstanceSize::Fixed(instance_size) => (instance_size as usize, 0),
-        InstanceSize::Array(element_size) => (0, element_size as usize),
+        InstanceSize::Array(element_size) | InstanceSize::StructArray(element_size) => {
+            (0, element_size as usize)
+        }
         InstanceSize::ObjArray => (0, mem::ptr_width_usize()),
         InstanceSize::FreeArray => (0, mem::ptr_width_usize()),
         InstanceSize::Str => (0, 1),
@@ -289,6 +317,179 @@ fn create_specialized_class(vm: &VM, cls: &class::Class, type_params: &TypeList)
     id
 }
 
+pub fn specialize_lambda(
+    vm: &VM,
+    fct: &Fct,
+    src: &FctSrc,
+    cls_type_params: &TypeList,
+    fct_type_params: &TypeList,
+) -> ClassDefId {
+    let key = (cls_type_params.clone(), fct_type_params.clone());
+
+    if let Some(&id) = src.closure_defs.read().get(&key) {
+        return id;
+    }
+
+    create_specialized_lambda(vm, fct, src, cls_type_params, fct_type_params)
+}
+
+fn create_specialized_lambda(
+    vm: &VM,
+    fct: &Fct,
+    src: &FctSrc,
+    cls_type_params: &TypeList,
+    fct_type_params: &TypeList,
+) -> ClassDefId {
+    debug_assert!(fct.is_lambda());
+
+    let mut fields = Vec::with_capacity(src.captures.len());
+    let mut ref_fields = Vec::new();
+    let mut csize = Header::size();
+
+    // closure object stores one field for each captured variable
+    for capture in &src.captures {
+        let ty = src.vars[capture.inner].ty;
+        let ty = specialize_type(vm, ty, cls_type_params, fct_type_params);
+        debug_assert!(!ty.contains_type_param(vm));
+
+        let field_size = ty.size(vm);
+        let field_align = ty.align(vm);
+
+        let offset = mem::align_i32(csize, field_align);
+        fields.push(FieldDef { offset, ty });
+
+        csize = offset + field_size;
+
+        for ref_offset in ty.ref_offsets(vm) {
+            ref_fields.push(offset + ref_offset);
+        }
+    }
+
+    let size = InstanceSize::Fixed(mem::align_i32(csize, mem::ptr_width()));
+
+    let id = {
+        let mut class_defs = vm.class_defs.lock();
+        let id: ClassDefId = class_defs.len().into();
+
+        let key = (cls_type_params.clone(), fct_type_params.clone());
+        let old = src.closure_defs.write().insert(key, id);
+        assert!(old.is_none());
+
+        class_defs.push(Arc::new(RwLock::new(ClassDef {
+            id,
+            cls_id: None,
+            type_params: cls_type_params.clone(),
+            parent_id: None,
+            size,
+            fields,
+            ref_fields,
+            vtable: None,
+            lambda: Some((fct.id, fct_type_params.clone())),
+            enum_value: None,
+        })));
+
+        id
+    };
+
+    // the only entry in the vtable is the code of the lambda
+    let stub = vm.compiler_thunk().to_usize();
+    let vtable_entries = vec![stub; 1];
+
+    let cls_def = vm.class_defs.idx(id);
+    let mut cls_def = cls_def.write();
+
+    let instance_size = match size {
+        InstanceSize::Fixed(instance_size) => instance_size as usize,
+        _ => unreachable!(),
+    };
+
+    let clsptr = (&*cls_def) as *const class::ClassDef as *mut class::ClassDef;
+    let vtable = VTableBox::new(clsptr, instance_size, 0, &vtable_entries);
+    cls_def.vtable = Some(vtable);
+
+    ensure_display(vm, &mut cls_def);
+
+    id
+}
+
+// offset of the value tag in objects of enums with payload data
+pub fn enum_tag_offset() -> i32 {
+    Header::size()
+}
+
+pub fn specialize_enum_value(vm: &VM, enum_id: EnumId, value: u32) -> ClassDefId {
+    if let Some(id) = vm.enums[enum_id].read().value_defs[value as usize] {
+        return id;
+    }
+
+    create_specialized_enum_value(vm, enum_id, value)
+}
+
+fn create_specialized_enum_value(vm: &VM, enum_id: EnumId, value: u32) -> ClassDefId {
+    let mut xenum = vm.enums[enum_id].write();
+
+    if let Some(id) = xenum.value_defs[value as usize] {
+        return id;
+    }
+
+    // value tag is followed by the payload fields
+    let types = xenum.value_types[value as usize]
+        .clone()
+        .unwrap_or_default();
+    let mut fields = Vec::with_capacity(types.len());
+    let mut ref_fields = Vec::new();
+    let mut csize = enum_tag_offset() + BuiltinType::Int.size(vm);
+
+    for ty in types {
+        let field_size = ty.size(vm);
+        let field_align = ty.align(vm);
+
+        let offset = mem::align_i32(csize, field_align);
+        fields.push(FieldDef { offset, ty });
+
+        csize = offset + field_size;
+
+        for ref_offset in ty.ref_offsets(vm) {
+            ref_fields.push(offset + ref_offset);
+        }
+    }
+
+    let instance_size = mem::align_i32(csize, mem::ptr_width());
+
+    let id = {
+        let mut class_defs = vm.class_defs.lock();
+        let id: ClassDefId = class_defs.len().into();
+
+        class_defs.push(Arc::new(RwLock::new(ClassDef {
+            id,
+            cls_id: None,
+            type_params: TypeList::empty(),
+            parent_id: None,
+            size: InstanceSize::Fixed(instance_size),
+            fields,
+            ref_fields,
+            vtable: None,
+            lambda: None,
+            enum_value: Some((enum_id, value)),
+        })));
+
+        id
+    };
+
+    xenum.value_defs[value as usize] = Some(id);
+
+    let cls_def = vm.class_defs.idx(id);
+    let mut cls_def = cls_def.write();
+
+    let clsptr = (&*cls_def) as *const class::ClassDef as *mut class::ClassDef;
+    let vtable = VTableBox::new(clsptr, instance_size as usize, 0, &[]);
+    cls_def.vtable = Some(vtable);
+
+    ensure_display(vm, &mut cls_def);
+
+    id
+}
+
 fn ensure_display<'ast>(vm: &VM<'ast>, cls_def: &mut ClassDef) -> usize {
     let vtable = cls_def.vtable.as_mut().unwrap();
 
@@ -372,7 +573,19 @@ pub fn replace_type_param(
 
         BuiltinType::This => self_ty.expect("no type for Self given"),
 
-        BuiltinType::Lambda(_) => unimplemented!(),
+        BuiltinType::Lambda(id) => {
+            let lambda = vm.lambda_types.lock().get(id);
+
+            let params = lambda
+                .params
+                .iter()
+                .map(|&p| replace_type_param(vm, p, cls_tp, fct_tp, self_ty))
+                .collect::<Vec<_>>();
+            let ret = replace_type_param(vm, lambda.ret, cls_tp, fct_tp, self_ty);
+
+            let id = vm.lambda_types.lock().insert(params, ret);
+            BuiltinType::Lambda(id)
+        }
 
         _ => ty,
     }
@@ -407,6 +620,8 @@ pub fn specialize_for_call_type(call_type: &CallType, ty: BuiltinType, vm: &VM)
 
         CallType::Intrinsic(_) => unimplemented!(),
 
+        CallType::Lambda(_) | CallType::EnumValue(_, _) => ty,
+
         CallType::TraitStatic(_, _, _) => {
             assert_ne!(ty, BuiltinType::This);
 
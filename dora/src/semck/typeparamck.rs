@@ -101,12 +101,15 @@ impl<'a, 'ast> TypeParamCheck<'a, 'ast> {
             }
         }
 
-        let cls_id = ty.cls_id(self.vm).unwrap();
-        let cls = self.vm.classes.idx(cls_id);
-        let cls = cls.read();
+        // structs do not implement any traits
+        let cls = ty.cls_id(self.vm).map(|cls_id| self.vm.classes.idx(cls_id));
+        let cls = cls.as_ref().map(|cls| cls.read());
 
         for &trait_bound in &tp.trait_bounds {
-            if !cls.traits.contains(&trait_bound) {
+            if !cls
+                .as_ref()
+                .map_or(false, |cls| cls.traits.contains(&trait_bound))
+            {
                 self.fail_trait_bound(trait_bound, ty);
                 succeeded = false;
             }
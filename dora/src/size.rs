@@ -3,6 +3,7 @@ pub enum InstanceSize {
     Fixed(i32),
     Array(i32),
     ObjArray,
+    StructArray(i32),
     FreeArray,
     Str,
 }
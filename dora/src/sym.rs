@@ -43,6 +43,17 @@ impl SymTable {
         None
     }
 
+    // returns symbol together with the index of the level it was found in
+    pub fn get_with_level(&self, name: Name) -> Option<(Sym, usize)> {
+        for (level, map) in self.levels.iter().enumerate().rev() {
+            if let Some(val) = map.get(name) {
+                return Some((val.clone(), level));
+            }
+        }
+
+        None
+    }
+
     pub fn get_var(&self, name: Name) -> Option<VarId> {
         self.get(name).and_then(|n| n.to_var())
     }
@@ -111,6 +122,7 @@ pub enum Sym {
     SymFctTypeParam(FctId, TypeListId),
     SymConst(ConstId),
     SymEnum(EnumId),
+    SymModule(ModuleId),
 }
 
 impl Sym {
@@ -233,4 +245,18 @@ impl Sym {
             _ => None,
         }
     }
+
+    pub fn is_module(&self) -> bool {
+        match *self {
+            SymModule(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn to_module(&self) -> Option<ModuleId> {
+        match *self {
+            SymModule(id) => Some(id),
+            _ => None,
+        }
+    }
 }
@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 
+use crate::bytecode::interpreter::InterpreterFrames;
 use crate::exception::DoraToNativeInfo;
 use crate::gc::{Address, Region, K};
 use crate::handle::HandleMemory;
@@ -68,6 +69,7 @@ impl Threads {
 
 pub struct DoraThread {
     pub handles: HandleMemory,
+    pub interpreter: InterpreterFrames,
     pub tld: ThreadLocalData,
     pub state: AtomicUsize,
     pub saved_pc: AtomicUsize,
@@ -81,6 +83,7 @@ impl DoraThread {
     pub fn new() -> Arc<DoraThread> {
         Arc::new(DoraThread {
             handles: HandleMemory::new(),
+            interpreter: InterpreterFrames::new(),
             tld: ThreadLocalData::new(),
             state: AtomicUsize::new(ThreadState::Uninitialized as usize),
             saved_pc: AtomicUsize::new(0),
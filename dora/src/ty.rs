@@ -57,6 +57,9 @@ pub enum BuiltinType {
 
     // some enum
     Enum(EnumId),
+
+    // some enum with payload data, values are heap objects
+    EnumObject(EnumId),
 }
 
 impl BuiltinType {
@@ -74,6 +77,13 @@ impl BuiltinType {
         }
     }
 
+    pub fn is_enum_object(&self) -> bool {
+        match *self {
+            BuiltinType::EnumObject(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_unit(&self) -> bool {
         match *self {
             BuiltinType::Unit => true,
@@ -95,6 +105,13 @@ impl BuiltinType {
         }
     }
 
+    pub fn is_struct(&self) -> bool {
+        match *self {
+            BuiltinType::Struct(_, _) => true,
+            _ => false,
+        }
+    }
+
     pub fn is_float(&self) -> bool {
         match self {
             &BuiltinType::Float | &BuiltinType::Double => true,
@@ -170,7 +187,11 @@ impl BuiltinType {
                 params.iter().any(|t| t.contains_type_param(vm))
             }
 
-            &BuiltinType::Lambda(_) => unimplemented!(),
+            &BuiltinType::Lambda(id) => {
+                let lambda = vm.lambda_types.lock().get(id);
+                lambda.params.iter().any(|t| t.contains_type_param(vm))
+                    || lambda.ret.contains_type_param(vm)
+            }
 
             _ => false,
         }
@@ -181,10 +202,29 @@ impl BuiltinType {
             BuiltinType::Ptr => true,
             BuiltinType::Class(_, _) => true,
             BuiltinType::Trait(_) => true,
+            BuiltinType::Lambda(_) => true,
+            BuiltinType::EnumObject(_) => true,
             _ => false,
         }
     }
 
+    // offsets of all references embedded in a value of this type
+    pub fn ref_offsets(&self, vm: &VM) -> Vec<i32> {
+        match *self {
+            BuiltinType::Struct(sid, list_id) => {
+                let params = vm.lists.lock().get(list_id);
+                let sid = semck::specialize::specialize_struct_id_params(vm, sid, params);
+                let struc = vm.struct_defs.idx(sid);
+                let struc = struc.lock();
+
+                struc.ref_fields.clone()
+            }
+
+            _ if self.reference_type() => vec![0],
+            _ => Vec::new(),
+        }
+    }
+
     pub fn value_type(&self) -> bool {
         match *self {
             BuiltinType::Unit
@@ -268,7 +308,7 @@ impl BuiltinType {
                 let xtrait = vm.traits[tid].read();
                 vm.interner.str(xtrait.name).to_string()
             }
-            BuiltinType::Enum(id) => {
+            BuiltinType::Enum(id) | BuiltinType::EnumObject(id) => {
                 let xenum = vm.enums[id].read();
                 vm.interner.str(xenum.name).to_string()
             }
@@ -361,7 +401,7 @@ impl BuiltinType {
                 _ => false,
             },
             BuiltinType::Trait(_) => unimplemented!(),
-            BuiltinType::Enum(_) => *self == other,
+            BuiltinType::Enum(_) | BuiltinType::EnumObject(_) => *self == other,
 
             BuiltinType::ClassTypeParam(_, _) => *self == other,
             BuiltinType::FctTypeParam(_, _) => *self == other,
@@ -397,9 +437,10 @@ impl BuiltinType {
             BuiltinType::Enum(_) => 4,
             BuiltinType::Nil => panic!("no size for nil."),
             BuiltinType::This => panic!("no size for Self."),
-            BuiltinType::Class(_, _) | BuiltinType::Lambda(_) | BuiltinType::Ptr => {
-                mem::ptr_width()
-            }
+            BuiltinType::Class(_, _)
+            | BuiltinType::Lambda(_)
+            | BuiltinType::EnumObject(_)
+            | BuiltinType::Ptr => mem::ptr_width(),
             BuiltinType::Struct(sid, list_id) => {
                 let params = vm.lists.lock().get(list_id);
                 let sid = semck::specialize::specialize_struct_id_params(vm, sid, params);
@@ -430,9 +471,10 @@ impl BuiltinType {
             BuiltinType::Nil => panic!("no alignment for nil."),
             BuiltinType::This => panic!("no alignment for Self."),
             BuiltinType::Enum(_) => 4,
-            BuiltinType::Class(_, _) | BuiltinType::Lambda(_) | BuiltinType::Ptr => {
-                mem::ptr_width()
-            }
+            BuiltinType::Class(_, _)
+            | BuiltinType::Lambda(_)
+            | BuiltinType::EnumObject(_)
+            | BuiltinType::Ptr => mem::ptr_width(),
             BuiltinType::Struct(sid, list_id) => {
                 let params = vm.lists.lock().get(list_id);
                 let sid = semck::specialize::specialize_struct_id_params(vm, sid, params);
@@ -463,10 +505,12 @@ impl BuiltinType {
             BuiltinType::Enum(_) => MachineMode::Int32,
             BuiltinType::Nil => panic!("no machine mode for nil."),
             BuiltinType::This => panic!("no machine mode for Self."),
-            BuiltinType::Class(_, _) | BuiltinType::Lambda(_) | BuiltinType::Ptr => {
-                MachineMode::Ptr
-            }
-            BuiltinType::Struct(_, _) => panic!("no machine mode for struct."),
+            BuiltinType::Class(_, _)
+            | BuiltinType::Lambda(_)
+            | BuiltinType::EnumObject(_)
+            | BuiltinType::Ptr => MachineMode::Ptr,
+            // struct values are passed around as pointers to their storage
+            BuiltinType::Struct(_, _) => MachineMode::Ptr,
             BuiltinType::Trait(_) => MachineMode::Ptr,
             BuiltinType::ClassTypeParam(_, _) | BuiltinType::FctTypeParam(_, _) => {
                 panic!("no machine mode for type variable.")
@@ -487,6 +531,7 @@ impl BuiltinType {
             | BuiltinType::Float
             | BuiltinType::Double
             | BuiltinType::Enum(_)
+            | BuiltinType::EnumObject(_)
             | BuiltinType::Ptr
             | BuiltinType::Trait(_)
             | BuiltinType::Nil => true,
@@ -501,9 +546,16 @@ impl BuiltinType {
 
                 true
             }
-            BuiltinType::Lambda(_) | BuiltinType::Struct(_, _) | BuiltinType::Tuple(_) => {
-                unimplemented!()
+            BuiltinType::Lambda(id) => {
+                let lambda = vm.lambda_types.lock().get(id);
+                lambda.params.iter().all(|t| t.is_concrete_type(vm))
+                    && lambda.ret.is_concrete_type(vm)
+            }
+            BuiltinType::Struct(_, list_id) => {
+                let params = vm.lists.lock().get(list_id);
+                params.iter().all(|param| param.is_concrete_type(vm))
             }
+            BuiltinType::Tuple(_) => unimplemented!(),
             BuiltinType::ClassTypeParam(_, _) | BuiltinType::FctTypeParam(_, _) => false,
         }
     }
@@ -718,8 +770,8 @@ impl LambdaTypes {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LambdaType {
-    params: Vec<BuiltinType>,
-    ret: BuiltinType,
+    pub params: Vec<BuiltinType>,
+    pub ret: BuiltinType,
 }
 
 #[derive(Debug, Copy, Clone)]
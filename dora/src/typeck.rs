@@ -3,7 +3,7 @@ use std::collections::hash_set::HashSet;
 use crate::ty::BuiltinType;
 use crate::typeck::constck::ConstCheck;
 use crate::typeck::expr::TypeCheck;
-use crate::vm::VM;
+use crate::vm::{FctParent, VM};
 
 use dora_parser::ast::NodeId;
 
@@ -25,6 +25,19 @@ pub fn check<'a, 'ast>(vm: &VM<'ast>) {
         let mut src = src.write();
         let ast = fct.ast;
 
+        if let FctParent::Function(outer_id) = fct.parent {
+            // enclosing function is always checked before its lambdas,
+            // therefore types of captured variables are already known
+            let outer = vm.fcts.idx(outer_id);
+            let outer = outer.read();
+            let outer_src = outer.src();
+            let outer_src = outer_src.read();
+
+            for capture in src.captures.clone() {
+                src.vars[capture.inner].ty = outer_src.vars[capture.outer].ty;
+            }
+        }
+
         let mut typeck = TypeCheck {
             vm,
             fct: &fct,
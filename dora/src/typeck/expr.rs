@@ -5,15 +5,16 @@ use std::{f32, f64};
 
 use crate::class::{find_field_in_class, find_methods_in_class, ClassId};
 use crate::error::msg::SemError;
+use crate::field::FieldId;
 use crate::semck::specialize::replace_type_param;
 use crate::semck::typeparamck;
 use crate::semck::{always_returns, expr_always_returns};
 use crate::sym::Sym::SymClass;
-use crate::ty::{BuiltinType, TypeList, TypeParamId};
+use crate::ty::{BuiltinType, LambdaId, TypeList, TypeParamId};
 use crate::typeck::lookup::MethodLookup;
 use crate::vm::{
-    self, CallType, ConvInfo, Fct, FctId, FctParent, FctSrc, FileId, ForTypeInfo, IdentType,
-    Intrinsic, VM,
+    self, CallType, ConvInfo, EnumId, Fct, FctId, FctParent, FctSrc, FileId, ForTypeInfo,
+    IdentType, Intrinsic, StructFieldId, StructId, VM,
 };
 
 use dora_parser::ast::visit::Visitor;
@@ -337,6 +338,173 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         self.expr_type = merged_type;
     }
 
+    fn check_expr_match(&mut self, expr: &'ast ExprMatchType) {
+        self.visit_expr(&expr.expr);
+        let expr_type = self.expr_type;
+
+        let enum_id = match expr_type {
+            BuiltinType::Enum(id) | BuiltinType::EnumObject(id) => Some(id),
+            BuiltinType::Error => None,
+            _ => {
+                let expr_type = expr_type.name(self.vm);
+                let msg = SemError::EnumExpected(expr_type);
+                self.vm.diag.lock().report(self.file, expr.expr.pos(), msg);
+                None
+            }
+        };
+
+        let values = enum_id.map_or(0, |id| self.vm.enums[id].read().values.len());
+        let mut covered = vec![false; values];
+        let mut covered_all = false;
+        let mut merged_type: Option<BuiltinType> = None;
+
+        for case in &expr.cases {
+            for pattern in &case.patterns {
+                if covered_all {
+                    let msg = SemError::MatchUnreachableArm;
+                    self.vm.diag.lock().report(self.file, pattern.pos, msg);
+                    continue;
+                }
+
+                match pattern.data {
+                    MatchPatternData::Underscore => {
+                        if enum_id.is_some() && covered.iter().all(|&c| c) {
+                            let msg = SemError::MatchUnreachableArm;
+                            self.vm.diag.lock().report(self.file, pattern.pos, msg);
+                        }
+
+                        covered_all = true;
+                    }
+
+                    MatchPatternData::Ident(ref ident) => {
+                        if let Some(value) = self.check_match_pattern(expr_type, pattern, ident) {
+                            if covered[value as usize] {
+                                let msg = SemError::MatchUnreachableArm;
+                                self.vm.diag.lock().report(self.file, pattern.pos, msg);
+                            }
+
+                            covered[value as usize] = true;
+                        }
+                    }
+                }
+            }
+
+            self.visit_expr(&case.value);
+            let case_type = self.expr_type;
+
+            if expr_always_returns(&case.value) {
+                continue;
+            }
+
+            match merged_type {
+                Some(merged_type) if !merged_type.allows(self.vm, case_type) => {
+                    let merged_type = merged_type.name(self.vm);
+                    let case_type = case_type.name(self.vm);
+                    let msg = SemError::MatchBranchTypesIncompatible(merged_type, case_type);
+                    self.vm.diag.lock().report(self.file, case.pos, msg);
+                }
+
+                Some(_) => {}
+                None => merged_type = Some(case_type),
+            }
+        }
+
+        if let Some(enum_id) = enum_id {
+            if !covered_all && covered.iter().any(|&c| !c) {
+                let xenum = self.vm.enums[enum_id].read();
+                let enum_name = self.vm.interner.str(xenum.name);
+                let missing = xenum
+                    .values
+                    .iter()
+                    .zip(covered.iter())
+                    .filter(|&(_, &covered)| !covered)
+                    .map(|(&name, _)| format!("{}::{}", enum_name, self.vm.interner.str(name)))
+                    .collect::<Vec<_>>();
+
+                let msg = SemError::MatchNotExhaustive(missing);
+                self.vm.diag.lock().report(self.file, expr.pos, msg);
+            }
+        }
+
+        let merged_type = merged_type.unwrap_or(BuiltinType::Unit);
+        self.src.set_ty(expr.id, merged_type);
+        self.expr_type = merged_type;
+    }
+
+    fn check_match_pattern(
+        &mut self,
+        expr_type: BuiltinType,
+        pattern: &'ast MatchPattern,
+        ident: &'ast MatchPatternIdent,
+    ) -> Option<u32> {
+        let params: &[MatchPatternParam] = ident.params.as_ref().map_or(&[], |p| &p[..]);
+
+        // bindings stay untyped when the pattern itself is invalid
+        for param in params {
+            if let Some(&var_id) = self.src.map_vars.get(param.id) {
+                self.src.vars[var_id].ty = BuiltinType::Error;
+            }
+        }
+
+        // errors for unresolved patterns are already reported in nameck
+        let enum_id = match self.src.map_idents.get(pattern.id) {
+            Some(&IdentType::Enum(id)) => id,
+            _ => return None,
+        };
+
+        let xenum = self.vm.enums[enum_id].read();
+        let value_name = *ident.path.last().unwrap();
+
+        let value = if let Some(&value) = xenum.name_to_value.get(&value_name) {
+            value
+        } else {
+            let name = self.vm.interner.str(value_name).to_string();
+            let msg = SemError::UnknownEnumValue(name);
+            self.vm.diag.lock().report(self.file, pattern.pos, msg);
+            return None;
+        };
+
+        let pattern_type = xenum.ty();
+
+        if expr_type != pattern_type {
+            if !expr_type.is_error() {
+                let pattern_type = pattern_type.name(self.vm);
+                let expr_type = expr_type.name(self.vm);
+                let msg = SemError::MatchPatternTypeMismatch(pattern_type, expr_type);
+                self.vm.diag.lock().report(self.file, pattern.pos, msg);
+            }
+
+            return None;
+        }
+
+        self.src
+            .map_idents
+            .replace(pattern.id, IdentType::EnumValue(enum_id, value));
+
+        let types = xenum.value_types[value as usize]
+            .as_ref()
+            .map_or(&[][..], |types| &types[..]);
+
+        // a pattern without parameters matches independent of the payload
+        if ident.params.is_some() && params.len() != types.len() {
+            let name = format!(
+                "{}::{}",
+                self.vm.interner.str(xenum.name),
+                self.vm.interner.str(value_name)
+            );
+            let msg = SemError::MatchPatternParamsMismatch(name, types.len(), params.len());
+            self.vm.diag.lock().report(self.file, pattern.pos, msg);
+        } else {
+            for (param, &ty) in params.iter().zip(types) {
+                if let Some(&var_id) = self.src.map_vars.get(param.id) {
+                    self.src.vars[var_id].ty = ty;
+                }
+            }
+        }
+
+        Some(value)
+    }
+
     fn check_stmt_do(&mut self, s: &'ast StmtDoType) {
         self.visit_stmt(&s.do_block);
 
@@ -371,19 +539,24 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
     }
 
     fn check_expr_ident(&mut self, e: &'ast ExprIdentType) {
-        let ident_type = self.src.map_idents.get(e.id).unwrap();
+        self.check_ident_type(e.id, e.pos);
+    }
+
+    // also used for items of other modules, e.g. `a::b`
+    fn check_ident_type(&mut self, id: NodeId, pos: Position) {
+        let ident_type = self.src.map_idents.get(id).unwrap();
 
         match ident_type {
             &IdentType::Var(varid) => {
                 let ty = self.src.vars[varid].ty;
-                self.src.set_ty(e.id, ty);
+                self.src.set_ty(id, ty);
                 self.expr_type = ty;
             }
 
             &IdentType::Global(globalid) => {
                 let glob = self.vm.globals.idx(globalid);
                 let ty = glob.lock().ty;
-                self.src.set_ty(e.id, ty);
+                self.src.set_ty(id, ty);
                 self.expr_type = ty;
             }
 
@@ -393,65 +566,77 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 let cls = cls.read();
                 let field = &cls.fields[fieldid];
 
-                self.src.set_ty(e.id, field.ty);
+                self.src.set_ty(id, field.ty);
                 self.expr_type = field.ty;
             }
 
             &IdentType::Struct(sid) => {
+                // a struct name without initializers is only valid for structs without fields
+                self.check_struct_fields_initialized(pos, sid, &[]);
+
                 let list_id = self.vm.lists.lock().insert(TypeList::empty());
                 let ty = BuiltinType::Struct(sid, list_id);
-                self.src.set_ty(e.id, ty);
+                self.src.set_ty(id, ty);
                 self.expr_type = ty;
             }
 
+            &IdentType::StructField(_, _) => unreachable!(),
+
             &IdentType::Const(const_id) => {
                 let xconst = self.vm.consts.idx(const_id);
                 let xconst = xconst.lock();
 
-                self.src.set_ty(e.id, xconst.ty);
+                self.src.set_ty(id, xconst.ty);
                 self.expr_type = xconst.ty;
             }
 
             &IdentType::Fct(_) => {
-                if !self.used_in_call.contains(&e.id) {
+                if !self.used_in_call.contains(&id) {
                     self.vm
                         .diag
                         .lock()
-                        .report(self.file, e.pos, SemError::FctUsedAsIdentifier);
+                        .report(self.file, pos, SemError::FctUsedAsIdentifier);
                 }
 
-                self.src.set_ty(e.id, BuiltinType::Error);
+                self.src.set_ty(id, BuiltinType::Error);
                 self.expr_type = BuiltinType::Error;
             }
 
             &IdentType::Class(_) => {
-                if !self.used_in_call.contains(&e.id) {
+                if !self.used_in_call.contains(&id) {
                     self.vm
                         .diag
                         .lock()
-                        .report(self.file, e.pos, SemError::ClsUsedAsIdentifier);
+                        .report(self.file, pos, SemError::ClsUsedAsIdentifier);
                 }
 
-                self.src.set_ty(e.id, BuiltinType::Error);
+                self.src.set_ty(id, BuiltinType::Error);
                 self.expr_type = BuiltinType::Error;
             }
 
             &IdentType::TypeParam(_) => {
-                let msg = if self.used_in_call.contains(&e.id) {
+                let msg = if self.used_in_call.contains(&id) {
                     SemError::TypeParamUsedAsCallee
                 } else {
                     SemError::TypeParamUsedAsIdentifier
                 };
 
-                self.vm.diag.lock().report(self.file, e.pos, msg);
-                self.src.set_ty(e.id, BuiltinType::Error);
+                self.vm.diag.lock().report(self.file, pos, msg);
+                self.src.set_ty(id, BuiltinType::Error);
                 self.expr_type = BuiltinType::Error;
             }
 
             &IdentType::Enum(_) => {
                 let msg = SemError::EnumUsedAsIdentifier;
-                self.vm.diag.lock().report(self.file, e.pos, msg);
-                self.src.set_ty(e.id, BuiltinType::Error);
+                self.vm.diag.lock().report(self.file, pos, msg);
+                self.src.set_ty(id, BuiltinType::Error);
+                self.expr_type = BuiltinType::Error;
+            }
+
+            &IdentType::Module(_) => {
+                let msg = SemError::ModuleUsedAsIdentifier;
+                self.vm.diag.lock().report(self.file, pos, msg);
+                self.src.set_ty(id, BuiltinType::Error);
                 self.expr_type = BuiltinType::Error;
             }
 
@@ -506,12 +691,18 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                         lhs_type = glob.ty;
                     }
 
-                    &IdentType::Field(_, _) => {
+                    &IdentType::Field(_, _) | &IdentType::StructField(_, _) => {
                         unreachable!();
                     }
 
                     &IdentType::Struct(_) => {
-                        unimplemented!();
+                        self.vm.diag.lock().report(
+                            self.file,
+                            e.pos,
+                            SemError::InvalidLhsAssignment,
+                        );
+
+                        return;
                     }
 
                     &IdentType::Const(_) => {
@@ -550,7 +741,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                         return;
                     }
 
-                    &IdentType::Enum(_) | &IdentType::EnumValue(_, _) => {
+                    &IdentType::Enum(_) | &IdentType::EnumValue(_, _) | &IdentType::Module(_) => {
                         self.vm.diag.lock().report(
                             self.file,
                             e.pos,
@@ -650,6 +841,11 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         self.visit_expr(&e.rhs);
         let rhs_type = self.expr_type;
 
+        if let BuiltinType::Struct(struct_id, _) = object_type {
+            self.check_expr_assign_struct_field(e, struct_id, name, rhs_type);
+            return;
+        }
+
         if object_type.cls_id(self.vm).is_some() {
             if let Some((cls_ty, field_id)) = find_field_in_class(self.vm, object_type, name) {
                 let ident_type = IdentType::Field(cls_ty, field_id);
@@ -708,6 +904,190 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         self.expr_type = BuiltinType::Unit;
     }
 
+    fn check_expr_assign_struct_field(
+        &mut self,
+        e: &'ast ExprBinType,
+        struct_id: StructId,
+        name: Name,
+        rhs_type: BuiltinType,
+    ) {
+        let field_expr = e.lhs.to_dot().unwrap();
+
+        self.src.set_ty(e.id, BuiltinType::Unit);
+        self.expr_type = BuiltinType::Unit;
+
+        let struc = self.vm.structs.idx(struct_id);
+        let struc = struc.lock();
+
+        let field_id = match struc.find_field(name) {
+            Some(field_id) => field_id,
+
+            None => {
+                let struct_name = self.vm.interner.str(struc.name).to_string();
+                let field_name = self.vm.interner.str(name).to_string();
+                let msg = SemError::UnknownStructField(struct_name, field_name);
+                self.vm.diag.lock().report(self.file, field_expr.pos, msg);
+                return;
+            }
+        };
+
+        let object_type = self.src.ty(field_expr.lhs.id());
+        let ident_type = IdentType::StructField(object_type, field_id);
+        self.src
+            .map_idents
+            .insert_or_replace(e.lhs.id(), ident_type);
+
+        // struct values are copied, modifying a field is only allowed when
+        // the struct itself is stored in a reassignable location
+        if !self.struct_value_reassignable(&field_expr.lhs) {
+            self.vm
+                .diag
+                .lock()
+                .report(self.file, e.pos, SemError::LetReassigned);
+        }
+
+        let field = &struc.fields[field_id];
+
+        if !field.ty.allows(self.vm, rhs_type) && !rhs_type.is_error() {
+            let name = self.vm.interner.str(name).to_string();
+            let object_type = object_type.name(self.vm);
+            let lhs_type = field.ty.name(self.vm);
+            let rhs_type = rhs_type.name(self.vm);
+
+            let msg = SemError::AssignField(name, object_type, lhs_type, rhs_type);
+            self.vm.diag.lock().report(self.file, e.pos, msg);
+        }
+    }
+
+    fn struct_value_reassignable(&self, e: &'ast Expr) -> bool {
+        match *e {
+            ExprIdent(ref ident) => match self.src.map_idents.get(ident.id) {
+                Some(&IdentType::Var(var_id)) => self.src.vars[var_id].reassignable,
+                Some(&IdentType::Global(global_id)) => {
+                    let glob = self.vm.globals.idx(global_id);
+                    let glob = glob.lock();
+
+                    glob.reassignable
+                }
+                Some(&IdentType::Field(cls_ty, field_id)) => {
+                    self.class_field_reassignable(cls_ty, field_id)
+                }
+                _ => false,
+            },
+
+            ExprDot(ref dot) => match self.src.map_idents.get(dot.id) {
+                Some(&IdentType::Field(cls_ty, field_id)) => {
+                    self.class_field_reassignable(cls_ty, field_id)
+                }
+
+                Some(&IdentType::StructField(_, _)) => self.struct_value_reassignable(&dot.lhs),
+
+                _ => false,
+            },
+
+            _ => false,
+        }
+    }
+
+    fn class_field_reassignable(&self, cls_ty: BuiltinType, field_id: FieldId) -> bool {
+        let cls_id = cls_ty.cls_id(self.vm).expect("no class");
+        let cls = self.vm.classes.idx(cls_id);
+        let cls = cls.read();
+
+        self.fct.is_constructor || cls.fields[field_id].reassignable
+    }
+
+    fn check_struct_fields_initialized(
+        &mut self,
+        pos: Position,
+        struct_id: StructId,
+        initialized: &[StructFieldId],
+    ) {
+        let struc = self.vm.structs.idx(struct_id);
+        let struc = struc.lock();
+
+        for field in &struc.fields {
+            if !initialized.contains(&field.id) {
+                let struct_name = self.vm.interner.str(struc.name).to_string();
+                let field_name = self.vm.interner.str(field.name).to_string();
+                let msg = SemError::StructFieldNotInitialized(struct_name, field_name);
+                self.vm.diag.lock().report(self.file, pos, msg);
+            }
+        }
+    }
+
+    fn check_expr_lit_struct(&mut self, e: &'ast ExprLitStructType) {
+        let struct_id = match self
+            .vm
+            .lookup(self.file, e.path)
+            .and_then(|sym| sym.to_struct())
+        {
+            Some(struct_id) => struct_id,
+
+            None => {
+                for arg in &e.args {
+                    self.visit_expr(&arg.expr);
+                }
+
+                let name = self.vm.interner.str(e.path).to_string();
+                let msg = SemError::UnknownStruct(name);
+                self.vm.diag.lock().report(self.file, e.pos, msg);
+
+                self.src.set_ty(e.id, BuiltinType::Error);
+                self.expr_type = BuiltinType::Error;
+                return;
+            }
+        };
+
+        let mut initialized = Vec::new();
+
+        for arg in &e.args {
+            self.visit_expr(&arg.expr);
+            let arg_type = self.expr_type;
+
+            let struc = self.vm.structs.idx(struct_id);
+            let struc = struc.lock();
+            let struct_name = self.vm.interner.str(struc.name).to_string();
+            let field_name = self.vm.interner.str(arg.name).to_string();
+
+            let field_id = match struc.find_field(arg.name) {
+                Some(field_id) => field_id,
+
+                None => {
+                    let msg = SemError::UnknownStructField(struct_name, field_name);
+                    self.vm.diag.lock().report(self.file, arg.pos, msg);
+                    continue;
+                }
+            };
+
+            if initialized.contains(&field_id) {
+                let msg = SemError::StructFieldInitializedTwice(struct_name, field_name);
+                self.vm.diag.lock().report(self.file, arg.pos, msg);
+                continue;
+            }
+
+            initialized.push(field_id);
+
+            let field_ty = struc.fields[field_id].ty;
+
+            if !field_ty.allows(self.vm, arg_type) && !arg_type.is_error() {
+                let field_ty = field_ty.name(self.vm);
+                let arg_type = arg_type.name(self.vm);
+
+                let msg = SemError::AssignField(field_name, struct_name, field_ty, arg_type);
+                self.vm.diag.lock().report(self.file, arg.pos, msg);
+            }
+        }
+
+        self.check_struct_fields_initialized(e.pos, struct_id, &initialized);
+
+        let list_id = self.vm.lists.lock().insert(TypeList::empty());
+        let ty = BuiltinType::Struct(struct_id, list_id);
+
+        self.src.set_ty(e.id, ty);
+        self.expr_type = ty;
+    }
+
     fn find_method(
         &mut self,
         pos: Position,
@@ -1066,6 +1446,10 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                 self.expr_type = BuiltinType::Error;
             }
 
+            Some(IdentType::EnumValue(enum_id, value)) => {
+                self.check_expr_call_enum_value(e, enum_id, value, &arg_types);
+            }
+
             _ => {
                 if expr_type.is_error() {
                     self.src.set_ty(e.id, expr_type);
@@ -1073,11 +1457,94 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     return;
                 }
 
-                self.check_expr_call_expr(e, expr_type, &arg_types, in_try);
+                if let BuiltinType::Lambda(lambda_id) = expr_type {
+                    self.check_expr_call_lambda(e, lambda_id, &arg_types);
+                } else {
+                    self.check_expr_call_expr(e, expr_type, &arg_types, in_try);
+                }
             }
         }
     }
 
+    fn check_expr_call_lambda(
+        &mut self,
+        e: &'ast ExprCallType,
+        lambda_id: LambdaId,
+        arg_types: &[BuiltinType],
+    ) {
+        let lambda = self.vm.lambda_types.lock().get(lambda_id);
+
+        let args_match = lambda.params.len() == arg_types.len()
+            && lambda
+                .params
+                .iter()
+                .zip(arg_types)
+                .all(|(&param, &arg)| arg.is_error() || param.allows(self.vm, arg));
+
+        if !args_match {
+            let params = lambda
+                .params
+                .iter()
+                .map(|ty| ty.name(self.vm))
+                .collect::<Vec<_>>();
+            let args = arg_types
+                .iter()
+                .map(|ty| ty.name(self.vm))
+                .collect::<Vec<_>>();
+            let msg = SemError::LambdaParamTypesIncompatible(params, args);
+            self.vm.diag.lock().report(self.file, e.pos, msg);
+        }
+
+        let call_type = CallType::Lambda(lambda_id);
+        self.src.map_calls.insert(e.id, Arc::new(call_type));
+
+        self.src.set_ty(e.id, lambda.ret);
+        self.expr_type = lambda.ret;
+    }
+
+    fn check_expr_call_enum_value(
+        &mut self,
+        e: &'ast ExprCallType,
+        enum_id: EnumId,
+        value: u32,
+        arg_types: &[BuiltinType],
+    ) {
+        let xenum = self.vm.enums[enum_id].read();
+        let name = format!(
+            "{}::{}",
+            self.vm.interner.str(xenum.name),
+            self.vm.interner.str(xenum.values[value as usize])
+        );
+
+        if let Some(ref types) = xenum.value_types[value as usize] {
+            let compatible = types.len() == arg_types.len()
+                && types
+                    .iter()
+                    .zip(arg_types)
+                    .all(|(def, &arg)| def.allows(self.vm, arg));
+
+            if !compatible {
+                let types = types.iter().map(|ty| ty.name(self.vm)).collect::<Vec<_>>();
+                let args = arg_types
+                    .iter()
+                    .map(|ty| ty.name(self.vm))
+                    .collect::<Vec<_>>();
+                let msg = SemError::EnumValueArgsIncompatible(name, types, args);
+                self.vm.diag.lock().report(self.file, e.pos, msg);
+            }
+        } else {
+            let msg = SemError::EnumValueWithoutData(name);
+            self.vm.diag.lock().report(self.file, e.pos, msg);
+        }
+
+        let call_type = CallType::EnumValue(enum_id, value);
+        self.src.map_calls.insert(e.id, Arc::new(call_type));
+
+        let ty = xenum.ty();
+        self.src.set_ty(e.id, ty);
+        self.expr_type = ty;
+    }
+
     fn check_expr_call_generic_static_method(
         &mut self,
         e: &'ast ExprCallType,
@@ -1525,7 +1992,7 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             return;
         }
 
-        match self.vm.sym.lock().get(class) {
+        match self.vm.lookup(self.file, class) {
             Some(SymClass(cls_id)) => {
                 let mut lookup = MethodLookup::new(self.vm, self.file)
                     .pos(e.pos)
@@ -1640,6 +2107,18 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
         };
 
         let ident_type = match ident_type {
+            Some(&IdentType::Module(_)) => {
+                // item of the module was already resolved in nameck
+                if self.src.map_idents.get(e.id).is_some() {
+                    self.check_ident_type(e.id, e.pos);
+                } else {
+                    self.src.set_ty(e.id, BuiltinType::Error);
+                    self.expr_type = BuiltinType::Error;
+                }
+
+                return;
+            }
+
             Some(&IdentType::Class(cls_id)) => {
                 let list = self.vm.lists.lock().insert(TypeList::empty());
                 let cls_ty = BuiltinType::Class(cls_id, list);
@@ -1663,6 +2142,18 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                     self.src
                         .map_idents
                         .insert(e.id, IdentType::EnumValue(id, value));
+
+                    let has_data = xenum.value_types[value as usize].is_some();
+
+                    if has_data && !self.used_in_call.contains(&e.id) {
+                        let name = format!(
+                            "{}::{}",
+                            self.vm.interner.str(xenum.name),
+                            self.vm.interner.str(name)
+                        );
+                        let msg = SemError::EnumValueArgsExpected(name);
+                        self.vm.diag.lock().report(self.file, e.pos, msg);
+                    }
                 } else {
                     let name = self.vm.interner.str(name).to_string();
                     self.vm
@@ -1671,8 +2162,8 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
                         .report(self.file, e.pos, SemError::UnknownEnumValue(name));
                 }
 
-                self.src.set_ty(e.id, BuiltinType::Enum(id));
-                self.expr_type = BuiltinType::Enum(id);
+                self.src.set_ty(e.id, xenum.ty());
+                self.expr_type = xenum.ty();
                 return;
             }
 
@@ -1774,6 +2265,30 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
             return;
         }
 
+        if let BuiltinType::Struct(struct_id, _) = object_type {
+            let struc = self.vm.structs.idx(struct_id);
+            let struc = struc.lock();
+
+            if let Some(field_id) = struc.find_field(name) {
+                let ident_type = IdentType::StructField(object_type, field_id);
+                self.src.map_idents.insert_or_replace(e.id, ident_type);
+
+                let fty = struc.fields[field_id].ty;
+                self.src.set_ty(e.id, fty);
+                self.expr_type = fty;
+                return;
+            }
+
+            let struct_name = self.vm.interner.str(struc.name).to_string();
+            let field_name = self.vm.interner.str(name).to_string();
+            let msg = SemError::UnknownStructField(struct_name, field_name);
+            self.vm.diag.lock().report(self.file, e.pos, msg);
+
+            self.src.set_ty(e.id, BuiltinType::Error);
+            self.expr_type = BuiltinType::Error;
+            return;
+        }
+
         if object_type.cls_id(self.vm).is_some() {
             if let Some((cls_ty, field_id)) = find_field_in_class(self.vm, object_type, name) {
                 let ident_type = IdentType::Field(cls_ty, field_id);
@@ -1848,6 +2363,14 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
 
     fn check_expr_this(&mut self, e: &'ast ExprSelfType) {
         match self.fct.parent {
+            FctParent::Function(_) => {
+                // `self` in lambda was captured from enclosing method
+                let var_id = self.src.map_idents.get(e.id).unwrap().var_id();
+                let ty = self.src.vars[var_id].ty;
+                self.src.set_ty(e.id, ty);
+                self.expr_type = ty;
+            }
+
             FctParent::Class(clsid) => {
                 let cls = self.vm.classes.idx(clsid);
                 let cls = cls.read();
@@ -1945,17 +2468,12 @@ impl<'a, 'ast> TypeCheck<'a, 'ast> {
     }
 
     fn check_expr_lambda(&mut self, e: &'ast ExprLambdaType) {
-        let ret = if let Some(ref ty) = e.ret {
-            self.src.ty(ty.id())
-        } else {
-            BuiltinType::Unit
-        };
+        let lambda_id = *self.src.map_lambdas.get(e.id).unwrap();
+        let lambda = self.vm.fcts.idx(lambda_id);
+        let lambda = lambda.read();
 
-        let params = e
-            .params
-            .iter()
-            .map(|p| self.src.ty(p.data_type.id()))
-            .collect::<Vec<_>>();
+        let params = lambda.params_without_self().to_vec();
+        let ret = lambda.return_type;
 
         let ty = self.vm.lambda_types.lock().insert(params, ret);
         let ty = BuiltinType::Lambda(ty);
@@ -2128,7 +2646,9 @@ impl<'a, 'ast> Visitor<'ast> for TypeCheck<'a, 'ast> {
             ExprLambda(ref expr) => self.check_expr_lambda(expr),
             ExprBlock(ref expr) => self.check_expr_block(expr),
             ExprIf(ref expr) => self.check_expr_if(expr),
+            ExprMatch(ref expr) => self.check_expr_match(expr),
             ExprTuple(ref expr) => self.check_expr_tuple(expr),
+            ExprLitStruct(ref expr) => self.check_expr_lit_struct(expr),
         }
     }
 
@@ -2200,7 +2720,8 @@ fn arg_allows(
         | BuiltinType::Long
         | BuiltinType::Float
         | BuiltinType::Double
-        | BuiltinType::Enum(_) => def == arg,
+        | BuiltinType::Enum(_)
+        | BuiltinType::EnumObject(_) => def == arg,
         BuiltinType::Nil => panic!("nil should not occur in fct definition."),
         BuiltinType::Ptr => panic!("ptr should not occur in fct definition."),
         BuiltinType::This => {
@@ -2347,12 +2868,52 @@ fn arg_allows(
             _ => false,
         },
 
-        BuiltinType::Lambda(_) => {
-            // for now expect the exact same params and return types
-            // possible improvement: allow super classes for params,
-            //                             sub class for return type
-            def == arg
-        }
+        BuiltinType::Lambda(lambda_id) => match arg {
+            BuiltinType::Lambda(other_lambda_id) => {
+                if lambda_id == other_lambda_id {
+                    return true;
+                }
+
+                let lambda = vm.lambda_types.lock().get(lambda_id);
+                let other_lambda = vm.lambda_types.lock().get(other_lambda_id);
+
+                if lambda.params.len() != other_lambda.params.len() {
+                    return false;
+                }
+
+                // for now expect the exact same params and return types,
+                // only type params are replaced by their type arguments
+                // possible improvement: allow super classes for params,
+                //                             sub class for return type
+                let types = lambda.params.iter().chain(Some(&lambda.ret));
+                let other_types = other_lambda.params.iter().chain(Some(&other_lambda.ret));
+
+                for (&ty, &other_ty) in types.zip(other_types) {
+                    let allows = if ty.contains_type_param(vm) {
+                        arg_allows(
+                            vm,
+                            ty,
+                            other_ty,
+                            global_cls_id,
+                            global_fct_id,
+                            cls_tps,
+                            fct_tps,
+                            self_ty,
+                        )
+                    } else {
+                        ty == other_ty
+                    };
+
+                    if !allows {
+                        return false;
+                    }
+                }
+
+                true
+            }
+
+            _ => false,
+        },
     }
 }
 
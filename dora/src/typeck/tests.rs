@@ -851,6 +851,40 @@ fn struct_lit() {
         pos(1, 34),
         SemError::ReturnType("Int".into(), "Foo".into()),
     );
+    ok("struct Foo { a: Int, b: Bool } fun foo() -> Foo { Foo { a: 1, b: true } }");
+    err(
+        "struct Foo { a: Int } fun foo() -> Foo { Foo { a: 1, b: true } }",
+        pos(1, 54),
+        SemError::UnknownStructField("Foo".into(), "b".into()),
+    );
+    err(
+        "struct Foo { a: Int, b: Int } fun foo() -> Foo { Foo { a: 1 } }",
+        pos(1, 50),
+        SemError::StructFieldNotInitialized("Foo".into(), "b".into()),
+    );
+    err(
+        "struct Foo { a: Int } fun foo() -> Foo { Foo { a: 1, a: 2 } }",
+        pos(1, 54),
+        SemError::StructFieldInitializedTwice("Foo".into(), "a".into()),
+    );
+}
+
+#[test]
+fn struct_field() {
+    ok("struct Foo { a: Int } fun foo(x: Foo) -> Int { x.a }");
+    ok("struct Foo { a: Int } fun foo() { var x = Foo { a: 1 }; x.a = 2; }");
+    ok("struct Foo { a: Int } class Bar(var foo: Foo)
+        fun foo(x: Bar) { x.foo.a = 2; }");
+    err(
+        "struct Foo { a: Int } fun foo(x: Foo) -> Bool { x.a }",
+        pos(1, 47),
+        SemError::ReturnType("Bool".into(), "Int".into()),
+    );
+    err(
+        "struct Foo { a: Int } fun foo() { let x = Foo { a: 1 }; x.a = 2; }",
+        pos(1, 61),
+        SemError::LetReassigned,
+    );
 }
 
 #[test]
@@ -1350,6 +1384,32 @@ fn lambda_assignment() {
     );
 }
 
+#[test]
+fn lambda_call() {
+    ok("fun f() { let x = |a: Int| -> Int { return a; }; let y: Int = x(1); }");
+    ok("fun f(x: (Int, Bool) -> ()) { x(1, true); }");
+    ok("fun f[T](x: (T) -> T, a: T) -> T { return x(a); }
+        fun g() -> Int { return f[Int](|a: Int| -> Int { return a; }, 1); }");
+    err(
+        "fun f() { let x = |a: Int| {}; x(\"foo\"); }",
+        pos(1, 33),
+        SemError::LambdaParamTypesIncompatible(vec!["Int".into()], vec!["String".into()]),
+    );
+    err(
+        "fun f(x: (Int) -> ()) { x(); }",
+        pos(1, 26),
+        SemError::LambdaParamTypesIncompatible(vec!["Int".into()], Vec::new()),
+    );
+}
+
+#[test]
+fn lambda_capture() {
+    ok("fun f(a: Int) { let b = 1; let x = || -> Int { return a + b; }; }");
+    ok("class Foo(let a: Int) { fun f() { let x = || -> Int { return self.a; }; } }");
+    ok("fun f() { let a = 1; let x = || { let y = || -> Int { return a; }; }; }");
+    ok("fun f() { let a = 1; let x = |a: String| {}; }");
+}
+
 #[test]
 fn method_call_with_multiple_matching_traits() {
     err(
@@ -2084,3 +2144,120 @@ fn test_type_params_with_bounds_in_subclass() {
         SemError::Unimplemented,
     );
 }
+
+#[test]
+fn test_match() {
+    ok("enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::A => 1, Foo::B => 2 } }");
+    ok("enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::A | Foo::B => 1 } }");
+    ok("enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::A => 1, _ => 2 } }");
+    ok("enum Foo { A, B } fun f(x: Foo) -> Int {
+        match x { Foo::A => { return 1; } Foo::B => 2 }
+    }");
+    ok("enum Foo { A, B } fun f(x: Foo) { match x { Foo::A => {}, _ => {} } }");
+
+    err(
+        "enum Foo { A, B } fun f(x: Int) -> Int { match x { _ => 1 } }",
+        pos(1, 48),
+        SemError::EnumExpected("Int".into()),
+    );
+    err(
+        "enum Foo { A, B } enum Bar { A }
+        fun f(x: Foo) -> Int { match x { Bar::A => 1, _ => 2 } }",
+        pos(2, 42),
+        SemError::MatchPatternTypeMismatch("Bar".into(), "Foo".into()),
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::A => 1, _ => true } }",
+        pos(1, 65),
+        SemError::MatchBranchTypesIncompatible("Int".into(), "Bool".into()),
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::C => 1, _ => 2 } }",
+        pos(1, 52),
+        SemError::UnknownEnumValue("C".into()),
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { Baz::A => 1, _ => 2 } }",
+        pos(1, 52),
+        SemError::UnknownIdentifier("Baz".into()),
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { f => 1, _ => 2 } }",
+        pos(1, 52),
+        SemError::EnumValueExpected,
+    );
+}
+
+#[test]
+fn test_match_exhaustiveness() {
+    err(
+        "enum Foo { A, B, C } fun f(x: Foo) -> Int { match x { Foo::A => 1, Foo::B => 2 } }",
+        pos(1, 45),
+        SemError::MatchNotExhaustive(vec!["Foo::C".into()]),
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::A => 1, Foo::B => 2, Foo::A => 3 } }",
+        pos(1, 78),
+        SemError::MatchUnreachableArm,
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { _ => 1, Foo::B => 2 } }",
+        pos(1, 60),
+        SemError::MatchUnreachableArm,
+    );
+    err(
+        "enum Foo { A, B } fun f(x: Foo) -> Int { match x { Foo::A => 1, Foo::B => 2, _ => 3 } }",
+        pos(1, 78),
+        SemError::MatchUnreachableArm,
+    );
+}
+
+#[test]
+fn test_enum_with_payload() {
+    ok("enum Shape { Circle(Double), Empty } fun f() -> Shape { Shape::Circle(1.0) }");
+    ok("enum Shape { Circle(Double), Empty } fun f() -> Shape { Shape::Empty }");
+    ok("enum Shape { Circle(Double), Empty }
+        fun f(s: Shape) -> Double { match s { Shape::Circle(r) => r, Shape::Empty => 0.0 } }");
+    ok("enum Shape { Circle(Double), Empty }
+        fun f(s: Shape) -> Int { match s { Shape::Circle => 1, _ => 0 } }");
+    ok("enum List { Cons(Int, List), Nil }
+        fun f(l: List) -> Int { match l { List::Cons(x, _) => x, List::Nil => 0 } }");
+
+    err(
+        "enum Shape { Circle(Double), Empty } fun f() -> Shape { Shape::Circle(1) }",
+        pos(1, 70),
+        SemError::EnumValueArgsIncompatible(
+            "Shape::Circle".into(),
+            vec!["Double".into()],
+            vec!["Int".into()],
+        ),
+    );
+    err(
+        "enum Shape { Circle(Double), Empty } fun f() -> Shape { Shape::Empty(1) }",
+        pos(1, 69),
+        SemError::EnumValueWithoutData("Shape::Empty".into()),
+    );
+    err(
+        "enum Shape { Circle(Double), Empty } fun f() -> Shape { Shape::Circle }",
+        pos(1, 62),
+        SemError::EnumValueArgsExpected("Shape::Circle".into()),
+    );
+    err(
+        "enum Shape { Circle(Double), Empty }
+        fun f(s: Shape) -> Int { match s { Shape::Circle(a, b) => 1, _ => 0 } }",
+        pos(2, 44),
+        SemError::MatchPatternParamsMismatch("Shape::Circle".into(), 1, 2),
+    );
+    err(
+        "enum Shape { Circle(Double), Square(Double) }
+        fun f(s: Shape) -> Int { match s { Shape::Circle(a) | Shape::Square(_) => 1 } }",
+        pos(2, 58),
+        SemError::MatchBindingInAlternative,
+    );
+    err(
+        "enum Shape { Circle(Double), Empty }
+        fun f(s: Shape) { match s { Shape::Circle(r) => r, _ => 0 }; }",
+        pos(2, 60),
+        SemError::MatchBranchTypesIncompatible("Double".into(), "Int".into()),
+    );
+}
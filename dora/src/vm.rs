@@ -11,10 +11,12 @@ use std::sync::Arc;
 use crate::baseline;
 use crate::baseline::dora_compile;
 use crate::baseline::dora_entry;
+use crate::baseline::dora_interpreter::InterpreterCallStubs;
 use crate::baseline::dora_native::{self, InternalFct, InternalFctDescriptor, NativeThunks};
 use crate::baseline::dora_throw;
 use crate::baseline::fct::{JitFct, JitFctId};
 use crate::baseline::map::{CodeDescriptor, CodeMap};
+use crate::bytecode::interpreter::InterpretedFct;
 use crate::class::{Class, ClassDef, ClassDefId, ClassId};
 use crate::driver::cmd::Args;
 use crate::error::diag::Diagnostic;
@@ -28,7 +30,7 @@ use crate::stdlib;
 use crate::sym::Sym::*;
 use crate::sym::*;
 use crate::threads::{Threads, STACK_SIZE, THREAD};
-use crate::ty::{BuiltinType, LambdaTypes, TypeList, TypeLists, TypeParamId};
+use crate::ty::{BuiltinType, LambdaId, LambdaTypes, TypeList, TypeLists, TypeParamId};
 use crate::utils::GrowableVec;
 
 use dora_parser::ast;
@@ -90,6 +92,9 @@ pub struct VM<'ast> {
     pub files: Vec<File>,
     pub diag: Mutex<Diagnostic>,
     pub sym: Mutex<SymTable>,
+    pub modules: Vec<ModuleData>, // stores all modules, the root module comes first
+    pub file_modules: Vec<ModuleId>, // module of every parsed file
+    pub file_imports: Vec<HashMap<Name, Sym>>, // symbols imported into every file
     pub vips: KnownElements,
     pub consts: GrowableVec<Mutex<ConstData>>, // stores all const definitions
     pub structs: GrowableVec<Mutex<StructData>>, // stores all struct source definitions
@@ -98,6 +103,7 @@ pub struct VM<'ast> {
     pub class_defs: GrowableVec<RwLock<ClassDef>>, // stores all class definitions
     pub fcts: GrowableVec<RwLock<Fct<'ast>>>,  // stores all function definitions
     pub jit_fcts: GrowableVec<JitFct>,         // stores all function implementations
+    pub interpreted_fcts: GrowableVec<InterpretedFct>, // stores all interpreted functions
     pub enums: Vec<RwLock<EnumData>>,          // store all enum definitions
     pub traits: Vec<RwLock<TraitData>>,        // stores all trait definitions
     pub impls: Vec<RwLock<ImplData>>,          // stores all impl definitions
@@ -105,6 +111,7 @@ pub struct VM<'ast> {
     pub globals: GrowableVec<Mutex<GlobalData>>, // stores all global variables
     pub gc: Gc,                                // garbage collector
     pub native_thunks: Mutex<NativeThunks>,
+    pub interpreter_call_stubs: Mutex<InterpreterCallStubs>,
     pub polling_page: PollingPage,
     pub lists: Mutex<TypeLists>,
     pub lambda_types: Mutex<LambdaTypes>,
@@ -185,13 +192,18 @@ impl<'ast> VM<'ast> {
             id_generator: NodeIdGenerator::new(),
             diag: Mutex::new(Diagnostic::new()),
             sym: Mutex::new(SymTable::new()),
+            modules: vec![ModuleData::new(ModuleId::root(), None, None)],
+            file_modules: Vec::new(),
+            file_imports: Vec::new(),
             fcts: GrowableVec::new(),
             jit_fcts: GrowableVec::new(),
+            interpreted_fcts: GrowableVec::new(),
             code_map: Mutex::new(CodeMap::new()),
             polling_page: PollingPage::new(),
             lists: Mutex::new(TypeLists::new()),
             lambda_types: Mutex::new(LambdaTypes::new()),
             native_thunks: Mutex::new(NativeThunks::new()),
+            interpreter_call_stubs: Mutex::new(InterpreterCallStubs::new()),
             compiler_thunk: Mutex::new(Address::null()),
             dora_entry: Mutex::new(Address::null()),
             trap_thunk: Mutex::new(Address::null()),
@@ -260,7 +272,7 @@ impl<'ast> VM<'ast> {
         code_map.insert(start, end, desc);
     }
 
-    pub fn add_fct(&mut self, mut fct: Fct<'ast>) -> FctId {
+    pub fn add_fct(&self, mut fct: Fct<'ast>) -> FctId {
         let mut fcts = self.fcts.lock();
         let fctid = FctId(fcts.len());
 
@@ -271,6 +283,23 @@ impl<'ast> VM<'ast> {
         fctid
     }
 
+    // returns the innermost function that is not a lambda and contains the given function,
+    // lambdas share the type params of this function
+    pub fn lambda_owner(&self, fct: &Fct<'ast>) -> FctId {
+        let mut parent = fct.parent.clone();
+        let mut fct_id = fct.id;
+
+        while let FctParent::Function(outer_id) = parent {
+            let outer = self.fcts.idx(outer_id);
+            let outer = outer.read();
+
+            parent = outer.parent.clone();
+            fct_id = outer_id;
+        }
+
+        fct_id
+    }
+
     pub fn add_fct_to_sym(&mut self, fct: Fct<'ast>) -> Result<FctId, Sym> {
         let name = fct.name;
         let fctid = self.add_fct(fct);
@@ -432,6 +461,100 @@ impl<'ast> VM<'ast> {
     pub fn file(&self, idx: FileId) -> &File {
         &self.files[idx.0 as usize]
     }
+
+    pub fn add_module(&mut self, name: Name, parent: ModuleId) -> ModuleId {
+        let id: ModuleId = self.modules.len().into();
+        self.modules
+            .push(ModuleData::new(id, Some(name), Some(parent)));
+
+        let parent = &mut self.modules[parent.to_usize()];
+        parent.symbols.insert(name, SymModule(id));
+        parent.public.insert(name);
+
+        id
+    }
+
+    pub fn module(&self, id: ModuleId) -> &ModuleData {
+        &self.modules[id.to_usize()]
+    }
+
+    // files without an explicit module (e.g. in tests) belong to the root module
+    pub fn file_module(&self, file: FileId) -> ModuleId {
+        self.file_modules
+            .get(file.to_usize())
+            .cloned()
+            .unwrap_or(ModuleId::root())
+    }
+
+    pub fn module_name(&self, id: ModuleId) -> String {
+        let mut names = Vec::new();
+        let mut module = Some(id);
+
+        while let Some(id) = module {
+            let data = self.module(id);
+
+            if let Some(name) = data.name {
+                names.push(self.interner.str(name).to_string());
+            }
+
+            module = data.parent;
+        }
+
+        names.reverse();
+        names.join("::")
+    }
+
+    // private items are visible in their own module and all its submodules
+    pub fn is_accessible(&self, file: FileId, module: ModuleId, name: Name) -> bool {
+        if self.module(module).public.contains(&name) {
+            return true;
+        }
+
+        let mut current = Some(self.file_module(file));
+
+        while let Some(id) = current {
+            if id == module {
+                return true;
+            }
+
+            current = self.module(id).parent;
+        }
+
+        false
+    }
+
+    pub fn lookup(&self, file: FileId, name: Name) -> Option<Sym> {
+        self.lookup_with_level(file, name).map(|(sym, _)| sym)
+    }
+
+    // local symbols shadow the imports and items of the file's module,
+    // these in turn shadow the global namespace of the root module
+    pub fn lookup_with_level(&self, file: FileId, name: Name) -> Option<(Sym, usize)> {
+        let sym = self.sym.lock();
+
+        if let Some((sym, level)) = sym.get_with_level(name) {
+            if level > 0 {
+                return Some((sym, level));
+            }
+        }
+
+        let imported = self
+            .file_imports
+            .get(file.to_usize())
+            .and_then(|imports| imports.get(&name));
+
+        if let Some(imported) = imported {
+            return Some((imported.clone(), 0));
+        }
+
+        let module = self.module(self.file_module(file));
+
+        if let Some(item) = module.symbols.get(&name) {
+            return Some((item.clone(), 0));
+        }
+
+        sym.get_with_level(name)
+    }
 }
 
 unsafe impl<'ast> Sync for VM<'ast> {}
@@ -488,12 +611,67 @@ impl From<u32> for GlobalId {
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FileId(u32);
 
+impl FileId {
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
 impl From<u32> for FileId {
     fn from(data: u32) -> FileId {
         FileId(data)
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModuleId(u32);
+
+impl ModuleId {
+    pub fn root() -> ModuleId {
+        ModuleId(0)
+    }
+
+    pub fn is_root(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<usize> for ModuleId {
+    fn from(data: usize) -> ModuleId {
+        ModuleId(data.try_into().unwrap())
+    }
+}
+
+#[derive(Debug)]
+pub struct ModuleData {
+    pub id: ModuleId,
+    pub name: Option<Name>,
+    pub parent: Option<ModuleId>,
+
+    // items and submodules of this module, items of the root
+    // module are stored in the global symbol table instead
+    pub symbols: HashMap<Name, Sym>,
+
+    // names of all items marked with @pub
+    pub public: HashSet<Name>,
+}
+
+impl ModuleData {
+    fn new(id: ModuleId, name: Option<Name>, parent: Option<ModuleId>) -> ModuleData {
+        ModuleData {
+            id,
+            name,
+            parent,
+            symbols: HashMap::new(),
+            public: HashSet::new(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GlobalData {
     pub id: GlobalId,
@@ -588,6 +766,26 @@ pub struct EnumData {
     pub name: Name,
     pub values: Vec<Name>,
     pub name_to_value: HashMap<Name, u32>,
+
+    // payload types for every value, `None` for values without data
+    pub value_types: Vec<Option<Vec<BuiltinType>>>,
+
+    // set when at least one value carries data, all values are then
+    // allocated on the heap
+    pub has_payload: bool,
+
+    // object layout for every value, created on first use
+    pub value_defs: Vec<Option<ClassDefId>>,
+}
+
+impl EnumData {
+    pub fn ty(&self) -> BuiltinType {
+        if self.has_payload {
+            BuiltinType::EnumObject(self.id)
+        } else {
+            BuiltinType::Enum(self.id)
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -705,15 +903,46 @@ pub struct StructData {
     pub specializations: RwLock<HashMap<TypeList, StructDefId>>,
 }
 
+impl StructData {
+    pub fn find_field(&self, name: Name) -> Option<StructFieldId> {
+        self.fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.id)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct StructFieldId(u32);
 
+impl StructFieldId {
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
 impl From<u32> for StructFieldId {
     fn from(data: u32) -> StructFieldId {
         StructFieldId(data)
     }
 }
 
+impl Index<StructFieldId> for Vec<StructFieldData> {
+    type Output = StructFieldData;
+
+    fn index(&self, index: StructFieldId) -> &StructFieldData {
+        &self[index.0 as usize]
+    }
+}
+
+impl Index<StructFieldId> for Vec<StructFieldDef> {
+    type Output = StructFieldDef;
+
+    fn index(&self, index: StructFieldId) -> &StructFieldDef {
+        &self[index.0 as usize]
+    }
+}
+
 #[derive(Debug)]
 pub struct StructFieldData {
     pub id: StructFieldId,
@@ -871,6 +1100,7 @@ pub enum FctParent {
     Class(ClassId),
     Trait(TraitId),
     Impl(ImplId),
+    Function(FctId),
     None,
 }
 
@@ -973,6 +1203,13 @@ impl<'ast> Fct<'ast> {
         }
     }
 
+    pub fn is_lambda(&self) -> bool {
+        match self.parent {
+            FctParent::Function(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn full_name(&self, vm: &VM) -> String {
         let mut repr = String::new();
 
@@ -1050,6 +1287,9 @@ impl<'ast> Fct<'ast> {
         match self.parent {
             FctParent::Class(_) | FctParent::Trait(_) | FctParent::Impl(_) => !self.is_static,
 
+            // the closure object is passed as hidden first parameter
+            FctParent::Function(_) => true,
+
             _ => false,
         }
     }
@@ -1245,11 +1485,14 @@ pub struct FctSrc {
     pub map_convs: NodeMap<ConvInfo>,
     pub map_cls: NodeMap<ClassId>,
     pub map_fors: NodeMap<ForTypeInfo>,
+    pub map_lambdas: NodeMap<FctId>,
 
     pub always_returns: bool, // true if function is always exited via return statement
     // false if function execution could reach the closing } of this function
     pub specializations: RwLock<HashMap<(TypeList, TypeList), JitFctId>>,
-    pub vars: Vec<Var>, // variables in functions
+    pub vars: Vec<Var>,         // variables in functions
+    pub captures: Vec<Capture>, // variables captured by lambda from enclosing function
+    pub closure_defs: RwLock<HashMap<(TypeList, TypeList), ClassDefId>>,
 }
 
 impl Clone for FctSrc {
@@ -1262,10 +1505,13 @@ impl Clone for FctSrc {
             map_convs: self.map_convs.clone(),
             map_cls: self.map_cls.clone(),
             map_fors: self.map_fors.clone(),
+            map_lambdas: self.map_lambdas.clone(),
 
             vars: self.vars.clone(),
+            captures: self.captures.clone(),
             always_returns: self.always_returns,
             specializations: RwLock::new(HashMap::new()),
+            closure_defs: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -1280,10 +1526,13 @@ impl FctSrc {
             map_convs: NodeMap::new(),
             map_cls: NodeMap::new(),
             map_fors: NodeMap::new(),
+            map_lambdas: NodeMap::new(),
 
             vars: Vec::new(),
+            captures: Vec::new(),
             always_returns: false,
             specializations: RwLock::new(HashMap::new()),
+            closure_defs: RwLock::new(HashMap::new()),
         }
     }
 
@@ -1353,6 +1602,14 @@ where
     }
 }
 
+// binds variable `inner` of a lambda to variable `outer` of the enclosing function,
+// the value is copied into the closure object when the lambda expression is evaluated
+#[derive(Debug, Copy, Clone)]
+pub struct Capture {
+    pub outer: VarId,
+    pub inner: VarId,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct ConvInfo {
     pub check_type: BuiltinType,
@@ -1387,6 +1644,9 @@ pub enum IdentType {
     /// name of structure
     Struct(StructId),
 
+    /// field of struct value: <expr>.<field_name>
+    StructField(BuiltinType, StructFieldId),
+
     // name of constant
     Const(ConstId),
 
@@ -1425,6 +1685,9 @@ pub enum IdentType {
 
     // specific value in enum
     EnumValue(EnumId, u32),
+
+    // name of module
+    Module(ModuleId),
 }
 
 impl IdentType {
@@ -1491,6 +1754,8 @@ pub enum CallType {
     Trait(TraitId, FctId),
     TraitStatic(TypeParamId, TraitId, FctId),
     Intrinsic(Intrinsic),
+    Lambda(LambdaId),
+    EnumValue(EnumId, u32),
 }
 
 impl CallType {
@@ -1522,6 +1787,13 @@ impl CallType {
         }
     }
 
+    pub fn is_lambda(&self) -> bool {
+        match *self {
+            CallType::Lambda(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_intrinsic(&self) -> Option<Intrinsic> {
         match *self {
             CallType::Intrinsic(intrinsic) => Some(intrinsic),
@@ -1539,19 +1811,22 @@ impl CallType {
             CallType::Trait(_, fctid) => Some(fctid),
             CallType::TraitStatic(_, _, fctid) => Some(fctid),
             CallType::Intrinsic(_) => None,
+            CallType::Lambda(_) => None,
+            CallType::EnumValue(_, _) => None,
         }
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct CallSite<'ast> {
-    pub callee: FctId,
+    pub callee: Option<FctId>, // None for invocation of closure
     pub cls_type_params: TypeList,
     pub fct_type_params: TypeList,
     pub args: Vec<Arg<'ast>>,
     pub argsize: i32,
     pub super_call: bool,
     pub return_type: BuiltinType,
+    pub return_slot: Option<i32>, // stack slot for struct return value
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -7,7 +7,8 @@ require 'open3'
 
 $config = {
   :main => '',
-  :cannon => '--bc=cannon'
+  :cannon => '--bc=cannon',
+  :interpreter => '--bc=interpreter'
 }
 
 $ARGS = ARGV.clone
@@ -383,6 +384,9 @@ def parse_test_file(file)
       when "cannon"
         test_case.optional_configs.push(:cannon)
 
+      when "interpreter"
+        test_case.optional_configs.push(:interpreter)
+
       else
         raise "unkown expectation in #{file}: #{line}"
 